@@ -0,0 +1,594 @@
+/// https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-4.html#jvms-4.7.16
+use serde::Serialize;
+
+use crate::owned::{IntoOwned, owned_str};
+use crate::raw::{AttributeContext, ParseError, Usage, nest};
+use crate::{CpInfo, serialize_double, serialize_long};
+
+#[derive(Debug, Serialize)]
+pub struct Annotation<S: AsRef<str>> {
+    type_descriptor: S,
+    elements: Vec<(S, ElementValue<S>)>,
+}
+
+impl<S: AsRef<str>> Annotation<S> {
+    /// Field descriptor of the annotation interface, e.g. `Ljava/lang/Deprecated;`.
+    pub fn type_descriptor(&self) -> &str {
+        self.type_descriptor.as_ref()
+    }
+
+    /// Element names and values in file order. Elements left to their default are absent.
+    pub fn elements(&self) -> &[(S, ElementValue<S>)] {
+        &self.elements
+    }
+}
+
+/// `Long` and `Double` are serialized as in [`CpInfo`].
+#[derive(Debug, Serialize)]
+pub enum ElementValue<S: AsRef<str>> {
+    Byte(i8),
+    /// A UTF-16 code unit.
+    Char(u16),
+    Double(#[serde(serialize_with = "serialize_double")] f64),
+    Float(f32),
+    Int(i32),
+    Long(#[serde(serialize_with = "serialize_long")] i64),
+    Short(i16),
+    Boolean(bool),
+    String(S),
+    /// `type_name` is the field descriptor of the enum class.
+    EnumConst {
+        type_name: S,
+        const_name: S,
+    },
+    /// Return descriptor of the class, e.g. `Ljava/lang/String;` or `V` for `void.class`.
+    ClassInfo(S),
+    Nested(Box<Annotation<S>>),
+    Array(Vec<ElementValue<S>>),
+}
+
+fn utf8<S: AsRef<str>>(
+    input: &mut &[u8],
+    cx: AttributeContext<'_>,
+    resolve: &mut impl FnMut(u16, Usage) -> Result<Option<CpInfo<S>>, ParseError>,
+    context: &'static str,
+    usage: Usage,
+) -> Result<S, ParseError> {
+    let index = cx.u2(input, context)?;
+    match resolve(index, usage)? {
+        Some(CpInfo::Utf8(value)) => Ok(value),
+        _ => Err(ParseError::UnexpectedCpType {
+            index,
+            expected: "CONSTANT_Utf8",
+        }),
+    }
+}
+
+fn integer<S: AsRef<str>>(
+    input: &mut &[u8],
+    cx: AttributeContext<'_>,
+    resolve: &mut impl FnMut(u16, Usage) -> Result<Option<CpInfo<S>>, ParseError>,
+) -> Result<i32, ParseError> {
+    let index = cx.u2(input, "element_value const_value_index")?;
+    match resolve(index, Usage::Other)? {
+        Some(CpInfo::Integer(value)) => Ok(value),
+        _ => Err(ParseError::UnexpectedCpType {
+            index,
+            expected: "CONSTANT_Integer",
+        }),
+    }
+}
+
+/// Decode an `element_value`, with `depth` levels of nesting left for nested annotations and
+/// arrays.
+pub(crate) fn parse_element_value<S: AsRef<str>>(
+    input: &mut &[u8],
+    resolve: &mut impl FnMut(u16, Usage) -> Result<Option<CpInfo<S>>, ParseError>,
+    cx: AttributeContext<'_>,
+    depth: usize,
+) -> Result<ElementValue<S>, ParseError> {
+    const CONTEXT: &str = "element_value const_value_index";
+    let tag = cx.u1(input, "element_value tag")?;
+    // Narrower types are stored as CONSTANT_Integer, and truncated as by a cast.
+    let value = match tag {
+        b'B' => ElementValue::Byte(integer(input, cx, resolve)? as i8),
+        b'C' => ElementValue::Char(integer(input, cx, resolve)? as u16),
+        b'I' => ElementValue::Int(integer(input, cx, resolve)?),
+        b'S' => ElementValue::Short(integer(input, cx, resolve)? as i16),
+        b'Z' => ElementValue::Boolean(integer(input, cx, resolve)? != 0),
+        b'D' | b'F' | b'J' => {
+            let index = cx.u2(input, CONTEXT)?;
+            match (tag, resolve(index, Usage::Other)?) {
+                (b'D', Some(CpInfo::Double(value))) => ElementValue::Double(value),
+                (b'F', Some(CpInfo::Float(value))) => ElementValue::Float(value),
+                (b'J', Some(CpInfo::Long(value))) => ElementValue::Long(value),
+                _ => {
+                    return Err(ParseError::UnexpectedCpType {
+                        index,
+                        expected: match tag {
+                            b'D' => "CONSTANT_Double",
+                            b'F' => "CONSTANT_Float",
+                            _ => "CONSTANT_Long",
+                        },
+                    });
+                }
+            }
+        }
+        b's' => ElementValue::String(utf8(input, cx, resolve, CONTEXT, Usage::Other)?),
+        b'e' => ElementValue::EnumConst {
+            type_name: utf8(
+                input,
+                cx,
+                resolve,
+                "element_value type_name_index",
+                Usage::Descriptor,
+            )?,
+            const_name: utf8(
+                input,
+                cx,
+                resolve,
+                "element_value const_name_index",
+                Usage::Other,
+            )?,
+        },
+        b'c' => ElementValue::ClassInfo(utf8(
+            input,
+            cx,
+            resolve,
+            "element_value class_info_index",
+            Usage::Descriptor,
+        )?),
+        b'@' => ElementValue::Nested(Box::new(parse_annotation(
+            input,
+            resolve,
+            cx,
+            nest(depth)?,
+        )?)),
+        b'[' => {
+            let num_values = cx.u2(input, "element_value num_values")?;
+            // Each value takes at least 3 bytes.
+            let mut values = Vec::with_capacity((num_values as usize).min(input.len() / 3));
+            for _ in 0..num_values {
+                values.push(parse_element_value(input, resolve, cx, nest(depth)?)?);
+            }
+            ElementValue::Array(values)
+        }
+        _ => return Err(cx.malformed(format!("element_value tag {tag:#04x}"))),
+    };
+    Ok(value)
+}
+
+pub(crate) fn parse_annotation<S: AsRef<str>>(
+    input: &mut &[u8],
+    resolve: &mut impl FnMut(u16, Usage) -> Result<Option<CpInfo<S>>, ParseError>,
+    cx: AttributeContext<'_>,
+    depth: usize,
+) -> Result<Annotation<S>, ParseError> {
+    let type_descriptor = utf8(
+        input,
+        cx,
+        resolve,
+        "annotation type_index",
+        Usage::Descriptor,
+    )?;
+    let num_element_value_pairs = cx.u2(input, "annotation num_element_value_pairs")?;
+    // Each pair takes at least 5 bytes.
+    let mut elements = Vec::with_capacity((num_element_value_pairs as usize).min(input.len() / 5));
+    for _ in 0..num_element_value_pairs {
+        let name = utf8(
+            input,
+            cx,
+            resolve,
+            "annotation element_name_index",
+            Usage::Other,
+        )?;
+        elements.push((name, parse_element_value(input, resolve, cx, depth)?));
+    }
+    Ok(Annotation {
+        type_descriptor,
+        elements,
+    })
+}
+
+/// Decode the `info` of a `RuntimeVisibleAnnotations` or `RuntimeInvisibleAnnotations`,
+/// resolving constant pool indexes with `resolve`, which is told how each index is used and
+/// gives `None` for index 0 and the unusable slots.
+pub(crate) fn parse_annotations<S: AsRef<str>>(
+    cx: AttributeContext<'_>,
+    resolve: &mut impl FnMut(u16, Usage) -> Result<Option<CpInfo<S>>, ParseError>,
+    depth: usize,
+) -> Result<Vec<Annotation<S>>, ParseError> {
+    let input = &mut &cx.info[..];
+    let num_annotations = cx.u2(input, "annotations num_annotations")?;
+    let mut annotations = Vec::with_capacity((num_annotations as usize).min(input.len() / 4));
+    for _ in 0..num_annotations {
+        annotations.push(parse_annotation(input, resolve, cx, depth)?);
+    }
+    if !input.is_empty() {
+        return Err(cx.length_mismatch("annotations attribute_length"));
+    }
+    Ok(annotations)
+}
+
+/// An annotation on a use of a type, e.g. `List<@NonNull String>`.
+///
+/// https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-4.html#jvms-4.7.20
+#[derive(Debug, Serialize)]
+pub struct TypeAnnotation<S: AsRef<str>> {
+    target: AnnotationTarget,
+    target_path: Vec<TypePathEntry>,
+    annotation: Annotation<S>,
+}
+
+impl<S: AsRef<str>> TypeAnnotation<S> {
+    /// The kind of type annotated, by `target_type`.
+    pub fn target(&self) -> &AnnotationTarget {
+        &self.target
+    }
+
+    /// The steps into the type at the target to reach the annotated part, empty for the type
+    /// itself.
+    pub fn target_path(&self) -> &[TypePathEntry] {
+        &self.target_path
+    }
+
+    pub fn annotation(&self) -> &Annotation<S> {
+        &self.annotation
+    }
+}
+
+/// `target_type` with its `target_info`. Offsets are the ones of instructions in the code.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum AnnotationTarget {
+    /// 0x00, a type parameter of a generic class or interface.
+    ClassTypeParameter { type_parameter_index: u8 },
+    /// 0x01, a type parameter of a generic method or constructor.
+    MethodTypeParameter { type_parameter_index: u8 },
+    /// 0x10, `supertype_index` is 65535 for the superclass, otherwise an index into the
+    /// interfaces.
+    Supertype { supertype_index: u16 },
+    /// 0x11, a bound of a type parameter of a generic class or interface.
+    ClassTypeParameterBound {
+        type_parameter_index: u8,
+        bound_index: u8,
+    },
+    /// 0x12, a bound of a type parameter of a generic method or constructor.
+    MethodTypeParameterBound {
+        type_parameter_index: u8,
+        bound_index: u8,
+    },
+    /// 0x13, the type of a field or record component.
+    Field,
+    /// 0x14, the return type of a method, or the type of a newly constructed object.
+    Return,
+    /// 0x15, the receiver type of a method or constructor.
+    Receiver,
+    /// 0x16, the type of a formal parameter, not counting any implicit one.
+    FormalParameter { formal_parameter_index: u8 },
+    /// 0x17, a type in the `throws` clause, by index into the `Exceptions` attribute.
+    Throws { throws_type_index: u16 },
+    /// 0x40, the type of a local variable, in each of its ranges.
+    LocalVariable(Vec<LocalVariableTarget>),
+    /// 0x41, the type of a resource variable of `try`-with-resources.
+    ResourceVariable(Vec<LocalVariableTarget>),
+    /// 0x42, the type in a `catch` clause, by index into the exception table.
+    Catch { exception_table_index: u16 },
+    /// 0x43, the type in an `instanceof` expression.
+    Instanceof { offset: u16 },
+    /// 0x44, the type in a `new` expression.
+    New { offset: u16 },
+    /// 0x45, the type in a `::new` method reference.
+    ConstructorReference { offset: u16 },
+    /// 0x46, the type in a `::Identifier` method reference.
+    MethodReference { offset: u16 },
+    /// 0x47, the type in a cast, which may be one of an intersection.
+    Cast {
+        offset: u16,
+        type_argument_index: u8,
+    },
+    /// 0x48, a type argument of a generic constructor in a `new` expression or an explicit
+    /// constructor invocation.
+    ConstructorInvocationTypeArgument {
+        offset: u16,
+        type_argument_index: u8,
+    },
+    /// 0x49, a type argument of a generic method invocation.
+    MethodInvocationTypeArgument {
+        offset: u16,
+        type_argument_index: u8,
+    },
+    /// 0x4A, a type argument of a generic constructor in a `::new` method reference.
+    ConstructorReferenceTypeArgument {
+        offset: u16,
+        type_argument_index: u8,
+    },
+    /// 0x4B, a type argument of a generic method in a `::Identifier` method reference.
+    MethodReferenceTypeArgument {
+        offset: u16,
+        type_argument_index: u8,
+    },
+}
+
+/// A local variable is in `index` from `start_pc` for `length` bytes of bytecode.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LocalVariableTarget {
+    start_pc: u16,
+    length: u16,
+    index: u16,
+}
+
+impl LocalVariableTarget {
+    pub fn start_pc(&self) -> u16 {
+        self.start_pc
+    }
+
+    pub fn length(&self) -> u16 {
+        self.length
+    }
+
+    /// The local variable slot.
+    pub fn index(&self) -> u16 {
+        self.index
+    }
+}
+
+/// One step of a `type_path`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct TypePathEntry {
+    kind: TypePathKind,
+    type_argument_index: u8,
+}
+
+impl TypePathEntry {
+    pub fn kind(&self) -> TypePathKind {
+        self.kind
+    }
+
+    /// Which type argument of a parameterized type, 0 for the other kinds.
+    pub fn type_argument_index(&self) -> u8 {
+        self.type_argument_index
+    }
+}
+
+/// `type_path_kind`.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum TypePathKind {
+    /// Deeper in an array type, to its component type.
+    Array = 0,
+    /// Deeper in a nested type, to the one it is a member of.
+    Nested = 1,
+    /// On the bound of a wildcard type argument.
+    WildcardBound = 2,
+    /// On a type argument of a parameterized type.
+    TypeArgument = 3,
+}
+
+fn u1_u1(
+    input: &mut &[u8],
+    cx: AttributeContext<'_>,
+    context: &'static str,
+) -> Result<(u8, u8), ParseError> {
+    Ok((cx.u1(input, context)?, cx.u1(input, context)?))
+}
+
+fn offset_and_index(input: &mut &[u8], cx: AttributeContext<'_>) -> Result<(u16, u8), ParseError> {
+    Ok((
+        cx.u2(input, "type_argument_target offset")?,
+        cx.u1(input, "type_argument_target type_argument_index")?,
+    ))
+}
+
+fn local_variables(
+    input: &mut &[u8],
+    cx: AttributeContext<'_>,
+) -> Result<Vec<LocalVariableTarget>, ParseError> {
+    let table_length = cx.u2(input, "localvar_target table_length")?;
+    // Each entry takes 6 bytes.
+    let mut table = Vec::with_capacity((table_length as usize).min(input.len() / 6));
+    for _ in 0..table_length {
+        table.push(LocalVariableTarget {
+            start_pc: cx.u2(input, "localvar_target start_pc")?,
+            length: cx.u2(input, "localvar_target length")?,
+            index: cx.u2(input, "localvar_target index")?,
+        });
+    }
+    Ok(table)
+}
+
+fn parse_target(
+    input: &mut &[u8],
+    cx: AttributeContext<'_>,
+) -> Result<AnnotationTarget, ParseError> {
+    use AnnotationTarget::*;
+
+    let target_type = cx.u1(input, "type_annotation target_type")?;
+    let target = match target_type {
+        0x00 => ClassTypeParameter {
+            type_parameter_index: cx.u1(input, "type_parameter_target type_parameter_index")?,
+        },
+        0x01 => MethodTypeParameter {
+            type_parameter_index: cx.u1(input, "type_parameter_target type_parameter_index")?,
+        },
+        0x10 => Supertype {
+            supertype_index: cx.u2(input, "supertype_target supertype_index")?,
+        },
+        0x11 | 0x12 => {
+            let (type_parameter_index, bound_index) =
+                u1_u1(input, cx, "type_parameter_bound_target")?;
+            if target_type == 0x11 {
+                ClassTypeParameterBound {
+                    type_parameter_index,
+                    bound_index,
+                }
+            } else {
+                MethodTypeParameterBound {
+                    type_parameter_index,
+                    bound_index,
+                }
+            }
+        }
+        0x13 => Field,
+        0x14 => Return,
+        0x15 => Receiver,
+        0x16 => FormalParameter {
+            formal_parameter_index: cx
+                .u1(input, "formal_parameter_target formal_parameter_index")?,
+        },
+        0x17 => Throws {
+            throws_type_index: cx.u2(input, "throws_target throws_type_index")?,
+        },
+        0x40 => LocalVariable(local_variables(input, cx)?),
+        0x41 => ResourceVariable(local_variables(input, cx)?),
+        0x42 => Catch {
+            exception_table_index: cx.u2(input, "catch_target exception_table_index")?,
+        },
+        0x43..=0x46 => {
+            let offset = cx.u2(input, "offset_target offset")?;
+            match target_type {
+                0x43 => Instanceof { offset },
+                0x44 => New { offset },
+                0x45 => ConstructorReference { offset },
+                _ => MethodReference { offset },
+            }
+        }
+        0x47..=0x4b => {
+            let (offset, type_argument_index) = offset_and_index(input, cx)?;
+            match target_type {
+                0x47 => Cast {
+                    offset,
+                    type_argument_index,
+                },
+                0x48 => ConstructorInvocationTypeArgument {
+                    offset,
+                    type_argument_index,
+                },
+                0x49 => MethodInvocationTypeArgument {
+                    offset,
+                    type_argument_index,
+                },
+                0x4a => ConstructorReferenceTypeArgument {
+                    offset,
+                    type_argument_index,
+                },
+                _ => MethodReferenceTypeArgument {
+                    offset,
+                    type_argument_index,
+                },
+            }
+        }
+        _ => return Err(cx.malformed(format!("target_type {target_type:#04x}"))),
+    };
+    Ok(target)
+}
+
+fn parse_type_path(
+    input: &mut &[u8],
+    cx: AttributeContext<'_>,
+) -> Result<Vec<TypePathEntry>, ParseError> {
+    let path_length = cx.u1(input, "type_path path_length")?;
+    let mut path = Vec::with_capacity(path_length as usize);
+    for _ in 0..path_length {
+        let kind = match cx.u1(input, "type_path type_path_kind")? {
+            0 => TypePathKind::Array,
+            1 => TypePathKind::Nested,
+            2 => TypePathKind::WildcardBound,
+            3 => TypePathKind::TypeArgument,
+            kind => return Err(cx.malformed(format!("type_path_kind {kind}"))),
+        };
+        let type_argument_index = cx.u1(input, "type_path type_argument_index")?;
+        if kind != TypePathKind::TypeArgument && type_argument_index != 0 {
+            return Err(cx.malformed(format!(
+                "type_argument_index {type_argument_index} of type_path_kind {}",
+                kind as u8
+            )));
+        }
+        path.push(TypePathEntry {
+            kind,
+            type_argument_index,
+        });
+    }
+    Ok(path)
+}
+
+/// Decode the `info` of a `RuntimeVisibleTypeAnnotations` or `RuntimeInvisibleTypeAnnotations`,
+/// resolving constant pool indexes with `resolve` as in [`parse_annotations`].
+pub(crate) fn parse_type_annotations<S: AsRef<str>>(
+    cx: AttributeContext<'_>,
+    resolve: &mut impl FnMut(u16, Usage) -> Result<Option<CpInfo<S>>, ParseError>,
+    depth: usize,
+) -> Result<Vec<TypeAnnotation<S>>, ParseError> {
+    let input = &mut &cx.info[..];
+    let num_annotations = cx.u2(input, "type annotations num_annotations")?;
+    // Each annotation takes at least 6 bytes.
+    let mut annotations = Vec::with_capacity((num_annotations as usize).min(input.len() / 6));
+    for _ in 0..num_annotations {
+        let target = parse_target(input, cx)?;
+        let target_path = parse_type_path(input, cx)?;
+        let annotation = parse_annotation(input, resolve, cx, depth)?;
+        annotations.push(TypeAnnotation {
+            target,
+            target_path,
+            annotation,
+        });
+    }
+    if !input.is_empty() {
+        return Err(cx.length_mismatch("type annotations attribute_length"));
+    }
+    Ok(annotations)
+}
+
+impl<S: AsRef<str>> IntoOwned for Annotation<S> {
+    type Owned = Annotation<String>;
+
+    fn into_owned(self) -> Self::Owned {
+        Annotation {
+            type_descriptor: owned_str(self.type_descriptor),
+            elements: self
+                .elements
+                .into_iter()
+                .map(|(name, value)| (owned_str(name), value.into_owned()))
+                .collect(),
+        }
+    }
+}
+
+impl<S: AsRef<str>> IntoOwned for ElementValue<S> {
+    type Owned = ElementValue<String>;
+
+    fn into_owned(self) -> Self::Owned {
+        match self {
+            Self::Byte(value) => ElementValue::Byte(value),
+            Self::Char(value) => ElementValue::Char(value),
+            Self::Double(value) => ElementValue::Double(value),
+            Self::Float(value) => ElementValue::Float(value),
+            Self::Int(value) => ElementValue::Int(value),
+            Self::Long(value) => ElementValue::Long(value),
+            Self::Short(value) => ElementValue::Short(value),
+            Self::Boolean(value) => ElementValue::Boolean(value),
+            Self::String(value) => ElementValue::String(owned_str(value)),
+            Self::EnumConst {
+                type_name,
+                const_name,
+            } => ElementValue::EnumConst {
+                type_name: owned_str(type_name),
+                const_name: owned_str(const_name),
+            },
+            Self::ClassInfo(descriptor) => ElementValue::ClassInfo(owned_str(descriptor)),
+            Self::Nested(annotation) => ElementValue::Nested(annotation.into_owned()),
+            Self::Array(values) => ElementValue::Array(values.into_owned()),
+        }
+    }
+}
+
+impl<S: AsRef<str>> IntoOwned for TypeAnnotation<S> {
+    type Owned = TypeAnnotation<String>;
+
+    fn into_owned(self) -> Self::Owned {
+        TypeAnnotation {
+            target: self.target,
+            target_path: self.target_path,
+            annotation: self.annotation.into_owned(),
+        }
+    }
+}
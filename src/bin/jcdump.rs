@@ -1,15 +1,149 @@
-use std::io;
+use std::env;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
 
+use anyhow::Context as _;
+use libjcdump::ParseOptions;
 use libjcdump::parse_raw;
-use libjcdump::wrap;
+use libjcdump::parse_raw_bytes;
+use libjcdump::wrap_with_options;
+use serde::Serialize;
+
+enum Format {
+    /// A single object for stdin or a single path, and an array for several paths, even when
+    /// all but one of them cannot be read.
+    Json,
+    /// An object per line.
+    Ndjson,
+}
 
 pub fn main() -> anyhow::Result<()> {
-    let mut stdin = io::stdin().lock();
-    let mut stdout = io::stdout().lock();
+    let mut options = ParseOptions::default();
+    let mut format = Format::Json;
+    let mut pretty = false;
+    let mut output = None;
+    let mut paths = vec![];
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--hex-unknown" => options.hex_unknown = true,
+            "--omit-magic" => options.omit_magic = true,
+            "--max-java-version" => {
+                let version = args.next().and_then(|version| version.parse().ok());
+                let Some(version) = version else {
+                    anyhow::bail!("--max-java-version must be a Java SE version, e.g. 17");
+                };
+                options.max_java_version = Some(version);
+            }
+            "--pretty" | "-p" => pretty = true,
+            "--output" | "-o" => {
+                let Some(path) = args.next() else {
+                    anyhow::bail!("--output must be followed by a file name");
+                };
+                output = Some(PathBuf::from(path));
+            }
+            "--format" => {
+                format = match args.next().as_deref() {
+                    Some("json") => Format::Json,
+                    Some("ndjson") => Format::Ndjson,
+                    _ => anyhow::bail!("--format must be json or ndjson"),
+                }
+            }
+            _ if arg.starts_with('-') => anyhow::bail!("unknown argument: {arg}"),
+            _ => paths.push(PathBuf::from(arg)),
+        }
+    }
+
+    if pretty && matches!(format, Format::Ndjson) {
+        anyhow::bail!("--pretty cannot be used with --format ndjson");
+    }
+
+    let mut out: Box<dyn Write> = match output {
+        Some(path) => Box::new(io::BufWriter::new(
+            fs::File::create(&path).with_context(|| format!("cannot create {}", path.display()))?,
+        )),
+        None => Box::new(io::stdout().lock()),
+    };
+
+    if paths.is_empty() {
+        let mut stdin = io::stdin().lock();
+        let raw = parse_raw(&mut stdin)?;
+        let data = wrap_with_options(&raw, &options)?;
+        write_json(&mut out, &data, pretty)?;
+        if let Format::Ndjson = format {
+            writeln!(out)?;
+        }
+        out.flush()?;
+        return Ok(());
+    }
 
-    let raw = parse_raw(&mut stdin)?;
-    let data = wrap(&raw)?;
-    serde_json::to_writer(&mut stdout, &data)?;
+    let read = |path: &Path| {
+        let mut input = io::BufReader::new(
+            fs::File::open(path).with_context(|| format!("cannot open {}", path.display()))?,
+        );
+        match parse_raw(&mut input) {
+            Ok(raw) => Ok(raw),
+            // Only now read the whole file, to tell whether the constants are misaligned.
+            Err(err) => match fs::read(path) {
+                Ok(bytes) => parse_raw_bytes(&bytes),
+                Err(_) => Err(err),
+            },
+        }
+        .with_context(|| path.display().to_string())
+    };
+
+    let array = matches!(format, Format::Json) && paths.len() > 1;
+    if array {
+        write!(out, "[")?;
+    }
+    // A class file which cannot be read is reported and left out, without stopping the others,
+    // while a failure to write the output stops them all.
+    let mut written = 0;
+    let mut failed = 0;
+    for path in &paths {
+        let raw = match read(path) {
+            Ok(raw) => raw,
+            Err(err) => {
+                eprintln!("{err:#}");
+                failed += 1;
+                continue;
+            }
+        };
+        let data =
+            match wrap_with_options(&raw, &options).with_context(|| path.display().to_string()) {
+                Ok(data) => data,
+                Err(err) => {
+                    eprintln!("{err:#}");
+                    failed += 1;
+                    continue;
+                }
+            };
+        if array && written > 0 {
+            write!(out, ",")?;
+        }
+        write_json(&mut out, &data, pretty)?;
+        if let Format::Ndjson = format {
+            writeln!(out)?;
+        }
+        written += 1;
+    }
+    if array {
+        write!(out, "]")?;
+    }
+    out.flush()?;
+    if failed > 0 {
+        anyhow::bail!("{failed} of {} class files could not be read", paths.len());
+    }
+
+    Ok(())
+}
 
+fn write_json(output: &mut impl Write, data: &impl Serialize, pretty: bool) -> anyhow::Result<()> {
+    if pretty {
+        serde_json::to_writer_pretty(output, data)?;
+    } else {
+        serde_json::to_writer(output, data)?;
+    }
     Ok(())
 }
@@ -0,0 +1,181 @@
+/// https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-4.html#jvms-4.3
+use std::fmt;
+
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::raw::ParseError;
+
+#[derive(Debug, Error)]
+#[error("invalid descriptor {value:?} at position {position}")]
+pub struct DescriptorError {
+    pub value: String,
+    pub position: usize,
+}
+
+impl From<DescriptorError> for ParseError {
+    fn from(DescriptorError { value, position }: DescriptorError) -> Self {
+        Self::InvalidDescriptor { value, position }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum BaseType {
+    Byte,
+    Char,
+    Double,
+    Float,
+    Int,
+    Long,
+    Short,
+    Boolean,
+}
+
+impl BaseType {
+    fn java_name(&self) -> &'static str {
+        match self {
+            Self::Byte => "byte",
+            Self::Char => "char",
+            Self::Double => "double",
+            Self::Float => "float",
+            Self::Int => "int",
+            Self::Long => "long",
+            Self::Short => "short",
+            Self::Boolean => "boolean",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum FieldType {
+    Base(BaseType),
+    /// Internal binary name, e.g. `java/lang/String`.
+    Object(String),
+    Array(Box<FieldType>),
+}
+
+/// Formats the type as it is written in Java source, e.g. `java.lang.String[]`.
+impl fmt::Display for FieldType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Base(base) => f.write_str(base.java_name()),
+            Self::Object(name) => f.write_str(&name.replace('/', ".")),
+            Self::Array(component) => write!(f, "{component}[]"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MethodDescriptor {
+    pub parameters: Vec<FieldType>,
+    /// `None` for `void`.
+    pub return_type: Option<FieldType>,
+}
+
+/// The most array dimensions a descriptor may have (JVMS §4.3.2).
+const MAX_ARRAY_DIMENSIONS: usize = 255;
+
+struct Parser<'a> {
+    value: &'a str,
+    position: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self) -> DescriptorError {
+        DescriptorError {
+            value: self.value.to_string(),
+            position: self.position,
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.value.as_bytes().get(self.position).copied()
+    }
+
+    fn field_type(&mut self) -> Result<FieldType, DescriptorError> {
+        let mut dimensions = 0;
+        while self.peek() == Some(b'[') {
+            if dimensions == MAX_ARRAY_DIMENSIONS {
+                return Err(self.error());
+            }
+            dimensions += 1;
+            self.position += 1;
+        }
+        let mut field_type = self.component_type()?;
+        for _ in 0..dimensions {
+            field_type = FieldType::Array(Box::new(field_type));
+        }
+        Ok(field_type)
+    }
+
+    /// A field type other than an array type.
+    fn component_type(&mut self) -> Result<FieldType, DescriptorError> {
+        let Some(c) = self.peek() else {
+            return Err(self.error());
+        };
+        let base = match c {
+            b'B' => BaseType::Byte,
+            b'C' => BaseType::Char,
+            b'D' => BaseType::Double,
+            b'F' => BaseType::Float,
+            b'I' => BaseType::Int,
+            b'J' => BaseType::Long,
+            b'S' => BaseType::Short,
+            b'Z' => BaseType::Boolean,
+            b'L' => {
+                let start = self.position + 1;
+                let Some(len) = self.value[start..].find(';') else {
+                    return Err(self.error());
+                };
+                let name = &self.value[start..start + len];
+                if name.is_empty() || name.contains(['.', '[']) {
+                    self.position = start;
+                    return Err(self.error());
+                }
+                self.position = start + len + 1;
+                return Ok(FieldType::Object(name.to_string()));
+            }
+            _ => return Err(self.error()),
+        };
+        self.position += 1;
+        Ok(FieldType::Base(base))
+    }
+}
+
+pub fn parse_field_descriptor(value: &str) -> Result<FieldType, DescriptorError> {
+    let mut parser = Parser { value, position: 0 };
+    let field_type = parser.field_type()?;
+    if parser.position != value.len() {
+        return Err(parser.error());
+    }
+    Ok(field_type)
+}
+
+pub fn parse_method_descriptor(value: &str) -> Result<MethodDescriptor, DescriptorError> {
+    let mut parser = Parser { value, position: 0 };
+    if parser.peek() != Some(b'(') {
+        return Err(parser.error());
+    }
+    parser.position += 1;
+
+    let mut parameters = vec![];
+    while parser.peek() != Some(b')') {
+        parameters.push(parser.field_type()?);
+    }
+    parser.position += 1;
+
+    let return_type = if parser.peek() == Some(b'V') {
+        parser.position += 1;
+        None
+    } else {
+        Some(parser.field_type()?)
+    };
+    if parser.position != value.len() {
+        return Err(parser.error());
+    }
+
+    Ok(MethodDescriptor {
+        parameters,
+        return_type,
+    })
+}
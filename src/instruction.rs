@@ -0,0 +1,1029 @@
+/// https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-6.html
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::raw::{self, ParseError, read_u1, read_u2, read_u4};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ArrayType {
+    TBoolean,
+    TChar,
+    TFloat,
+    TDouble,
+    TByte,
+    TShort,
+    TInt,
+    TLong,
+}
+
+impl ArrayType {
+    /// The element type, e.g. `int`.
+    pub fn keyword(&self) -> &'static str {
+        match self {
+            Self::TBoolean => "boolean",
+            Self::TChar => "char",
+            Self::TFloat => "float",
+            Self::TDouble => "double",
+            Self::TByte => "byte",
+            Self::TShort => "short",
+            Self::TInt => "int",
+            Self::TLong => "long",
+        }
+    }
+}
+
+/// Operations which the `wide` instruction extends with a 16-bit local variable index.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum WideOperation {
+    Iload(u16),
+    Lload(u16),
+    Fload(u16),
+    Dload(u16),
+    Aload(u16),
+    Istore(u16),
+    Lstore(u16),
+    Fstore(u16),
+    Dstore(u16),
+    Astore(u16),
+    Ret(u16),
+    Iinc { index: u16, value: i16 },
+}
+
+impl WideOperation {
+    /// The mnemonic of the widened opcode, e.g. `iload`.
+    pub fn mnemonic(&self) -> &'static str {
+        match self {
+            Self::Iload(_) => "iload",
+            Self::Lload(_) => "lload",
+            Self::Fload(_) => "fload",
+            Self::Dload(_) => "dload",
+            Self::Aload(_) => "aload",
+            Self::Istore(_) => "istore",
+            Self::Lstore(_) => "lstore",
+            Self::Fstore(_) => "fstore",
+            Self::Dstore(_) => "dstore",
+            Self::Astore(_) => "astore",
+            Self::Ret(_) => "ret",
+            Self::Iinc { .. } => "iinc",
+        }
+    }
+}
+
+/// Branch operands are relative to the offset of the instruction, as encoded.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum Operation {
+    Nop,
+    AconstNull,
+    IconstM1,
+    Iconst0,
+    Iconst1,
+    Iconst2,
+    Iconst3,
+    Iconst4,
+    Iconst5,
+    Lconst0,
+    Lconst1,
+    Fconst0,
+    Fconst1,
+    Fconst2,
+    Dconst0,
+    Dconst1,
+    Bipush(i8),
+    Sipush(i16),
+    Ldc(u8),
+    LdcW(u16),
+    Ldc2W(u16),
+    Iload(u8),
+    Lload(u8),
+    Fload(u8),
+    Dload(u8),
+    Aload(u8),
+    Iload0,
+    Iload1,
+    Iload2,
+    Iload3,
+    Lload0,
+    Lload1,
+    Lload2,
+    Lload3,
+    Fload0,
+    Fload1,
+    Fload2,
+    Fload3,
+    Dload0,
+    Dload1,
+    Dload2,
+    Dload3,
+    Aload0,
+    Aload1,
+    Aload2,
+    Aload3,
+    Iaload,
+    Laload,
+    Faload,
+    Daload,
+    Aaload,
+    Baload,
+    Caload,
+    Saload,
+    Istore(u8),
+    Lstore(u8),
+    Fstore(u8),
+    Dstore(u8),
+    Astore(u8),
+    Istore0,
+    Istore1,
+    Istore2,
+    Istore3,
+    Lstore0,
+    Lstore1,
+    Lstore2,
+    Lstore3,
+    Fstore0,
+    Fstore1,
+    Fstore2,
+    Fstore3,
+    Dstore0,
+    Dstore1,
+    Dstore2,
+    Dstore3,
+    Astore0,
+    Astore1,
+    Astore2,
+    Astore3,
+    Iastore,
+    Lastore,
+    Fastore,
+    Dastore,
+    Aastore,
+    Bastore,
+    Castore,
+    Sastore,
+    Pop,
+    Pop2,
+    Dup,
+    DupX1,
+    DupX2,
+    Dup2,
+    Dup2X1,
+    Dup2X2,
+    Swap,
+    Iadd,
+    Ladd,
+    Fadd,
+    Dadd,
+    Isub,
+    Lsub,
+    Fsub,
+    Dsub,
+    Imul,
+    Lmul,
+    Fmul,
+    Dmul,
+    Idiv,
+    Ldiv,
+    Fdiv,
+    Ddiv,
+    Irem,
+    Lrem,
+    Frem,
+    Drem,
+    Ineg,
+    Lneg,
+    Fneg,
+    Dneg,
+    Ishl,
+    Lshl,
+    Ishr,
+    Lshr,
+    Iushr,
+    Lushr,
+    Iand,
+    Land,
+    Ior,
+    Lor,
+    Ixor,
+    Lxor,
+    Iinc {
+        index: u8,
+        value: i8,
+    },
+    I2l,
+    I2f,
+    I2d,
+    L2i,
+    L2f,
+    L2d,
+    F2i,
+    F2l,
+    F2d,
+    D2i,
+    D2l,
+    D2f,
+    I2b,
+    I2c,
+    I2s,
+    Lcmp,
+    Fcmpl,
+    Fcmpg,
+    Dcmpl,
+    Dcmpg,
+    Ifeq(i16),
+    Ifne(i16),
+    Iflt(i16),
+    Ifge(i16),
+    Ifgt(i16),
+    Ifle(i16),
+    IfIcmpeq(i16),
+    IfIcmpne(i16),
+    IfIcmplt(i16),
+    IfIcmpge(i16),
+    IfIcmpgt(i16),
+    IfIcmple(i16),
+    IfAcmpeq(i16),
+    IfAcmpne(i16),
+    Goto(i16),
+    Jsr(i16),
+    Ret(u8),
+    Tableswitch {
+        default: i32,
+        low: i32,
+        high: i32,
+        offsets: Vec<i32>,
+    },
+    Lookupswitch {
+        default: i32,
+        pairs: Vec<(i32, i32)>,
+    },
+    Ireturn,
+    Lreturn,
+    Freturn,
+    Dreturn,
+    Areturn,
+    Return,
+    Getstatic(u16),
+    Putstatic(u16),
+    Getfield(u16),
+    Putfield(u16),
+    Invokevirtual(u16),
+    Invokespecial(u16),
+    Invokestatic(u16),
+    Invokeinterface {
+        index: u16,
+        count: u8,
+    },
+    Invokedynamic(u16),
+    New(u16),
+    Newarray(ArrayType),
+    Anewarray(u16),
+    Arraylength,
+    Athrow,
+    Checkcast(u16),
+    Instanceof(u16),
+    Monitorenter,
+    Monitorexit,
+    Wide(WideOperation),
+    Multianewarray {
+        index: u16,
+        dimensions: u8,
+    },
+    Ifnull(i16),
+    Ifnonnull(i16),
+    GotoW(i32),
+    JsrW(i32),
+}
+
+impl Operation {
+    /// The mnemonic of the opcode, e.g. `iconst_m1` or `if_icmpeq`.
+    pub fn mnemonic(&self) -> &'static str {
+        match self {
+            Self::Nop => "nop",
+            Self::AconstNull => "aconst_null",
+            Self::IconstM1 => "iconst_m1",
+            Self::Iconst0 => "iconst_0",
+            Self::Iconst1 => "iconst_1",
+            Self::Iconst2 => "iconst_2",
+            Self::Iconst3 => "iconst_3",
+            Self::Iconst4 => "iconst_4",
+            Self::Iconst5 => "iconst_5",
+            Self::Lconst0 => "lconst_0",
+            Self::Lconst1 => "lconst_1",
+            Self::Fconst0 => "fconst_0",
+            Self::Fconst1 => "fconst_1",
+            Self::Fconst2 => "fconst_2",
+            Self::Dconst0 => "dconst_0",
+            Self::Dconst1 => "dconst_1",
+            Self::Bipush(_) => "bipush",
+            Self::Sipush(_) => "sipush",
+            Self::Ldc(_) => "ldc",
+            Self::LdcW(_) => "ldc_w",
+            Self::Ldc2W(_) => "ldc2_w",
+            Self::Iload(_) => "iload",
+            Self::Lload(_) => "lload",
+            Self::Fload(_) => "fload",
+            Self::Dload(_) => "dload",
+            Self::Aload(_) => "aload",
+            Self::Iload0 => "iload_0",
+            Self::Iload1 => "iload_1",
+            Self::Iload2 => "iload_2",
+            Self::Iload3 => "iload_3",
+            Self::Lload0 => "lload_0",
+            Self::Lload1 => "lload_1",
+            Self::Lload2 => "lload_2",
+            Self::Lload3 => "lload_3",
+            Self::Fload0 => "fload_0",
+            Self::Fload1 => "fload_1",
+            Self::Fload2 => "fload_2",
+            Self::Fload3 => "fload_3",
+            Self::Dload0 => "dload_0",
+            Self::Dload1 => "dload_1",
+            Self::Dload2 => "dload_2",
+            Self::Dload3 => "dload_3",
+            Self::Aload0 => "aload_0",
+            Self::Aload1 => "aload_1",
+            Self::Aload2 => "aload_2",
+            Self::Aload3 => "aload_3",
+            Self::Iaload => "iaload",
+            Self::Laload => "laload",
+            Self::Faload => "faload",
+            Self::Daload => "daload",
+            Self::Aaload => "aaload",
+            Self::Baload => "baload",
+            Self::Caload => "caload",
+            Self::Saload => "saload",
+            Self::Istore(_) => "istore",
+            Self::Lstore(_) => "lstore",
+            Self::Fstore(_) => "fstore",
+            Self::Dstore(_) => "dstore",
+            Self::Astore(_) => "astore",
+            Self::Istore0 => "istore_0",
+            Self::Istore1 => "istore_1",
+            Self::Istore2 => "istore_2",
+            Self::Istore3 => "istore_3",
+            Self::Lstore0 => "lstore_0",
+            Self::Lstore1 => "lstore_1",
+            Self::Lstore2 => "lstore_2",
+            Self::Lstore3 => "lstore_3",
+            Self::Fstore0 => "fstore_0",
+            Self::Fstore1 => "fstore_1",
+            Self::Fstore2 => "fstore_2",
+            Self::Fstore3 => "fstore_3",
+            Self::Dstore0 => "dstore_0",
+            Self::Dstore1 => "dstore_1",
+            Self::Dstore2 => "dstore_2",
+            Self::Dstore3 => "dstore_3",
+            Self::Astore0 => "astore_0",
+            Self::Astore1 => "astore_1",
+            Self::Astore2 => "astore_2",
+            Self::Astore3 => "astore_3",
+            Self::Iastore => "iastore",
+            Self::Lastore => "lastore",
+            Self::Fastore => "fastore",
+            Self::Dastore => "dastore",
+            Self::Aastore => "aastore",
+            Self::Bastore => "bastore",
+            Self::Castore => "castore",
+            Self::Sastore => "sastore",
+            Self::Pop => "pop",
+            Self::Pop2 => "pop2",
+            Self::Dup => "dup",
+            Self::DupX1 => "dup_x1",
+            Self::DupX2 => "dup_x2",
+            Self::Dup2 => "dup2",
+            Self::Dup2X1 => "dup2_x1",
+            Self::Dup2X2 => "dup2_x2",
+            Self::Swap => "swap",
+            Self::Iadd => "iadd",
+            Self::Ladd => "ladd",
+            Self::Fadd => "fadd",
+            Self::Dadd => "dadd",
+            Self::Isub => "isub",
+            Self::Lsub => "lsub",
+            Self::Fsub => "fsub",
+            Self::Dsub => "dsub",
+            Self::Imul => "imul",
+            Self::Lmul => "lmul",
+            Self::Fmul => "fmul",
+            Self::Dmul => "dmul",
+            Self::Idiv => "idiv",
+            Self::Ldiv => "ldiv",
+            Self::Fdiv => "fdiv",
+            Self::Ddiv => "ddiv",
+            Self::Irem => "irem",
+            Self::Lrem => "lrem",
+            Self::Frem => "frem",
+            Self::Drem => "drem",
+            Self::Ineg => "ineg",
+            Self::Lneg => "lneg",
+            Self::Fneg => "fneg",
+            Self::Dneg => "dneg",
+            Self::Ishl => "ishl",
+            Self::Lshl => "lshl",
+            Self::Ishr => "ishr",
+            Self::Lshr => "lshr",
+            Self::Iushr => "iushr",
+            Self::Lushr => "lushr",
+            Self::Iand => "iand",
+            Self::Land => "land",
+            Self::Ior => "ior",
+            Self::Lor => "lor",
+            Self::Ixor => "ixor",
+            Self::Lxor => "lxor",
+            Self::Iinc { .. } => "iinc",
+            Self::I2l => "i2l",
+            Self::I2f => "i2f",
+            Self::I2d => "i2d",
+            Self::L2i => "l2i",
+            Self::L2f => "l2f",
+            Self::L2d => "l2d",
+            Self::F2i => "f2i",
+            Self::F2l => "f2l",
+            Self::F2d => "f2d",
+            Self::D2i => "d2i",
+            Self::D2l => "d2l",
+            Self::D2f => "d2f",
+            Self::I2b => "i2b",
+            Self::I2c => "i2c",
+            Self::I2s => "i2s",
+            Self::Lcmp => "lcmp",
+            Self::Fcmpl => "fcmpl",
+            Self::Fcmpg => "fcmpg",
+            Self::Dcmpl => "dcmpl",
+            Self::Dcmpg => "dcmpg",
+            Self::Ifeq(_) => "ifeq",
+            Self::Ifne(_) => "ifne",
+            Self::Iflt(_) => "iflt",
+            Self::Ifge(_) => "ifge",
+            Self::Ifgt(_) => "ifgt",
+            Self::Ifle(_) => "ifle",
+            Self::IfIcmpeq(_) => "if_icmpeq",
+            Self::IfIcmpne(_) => "if_icmpne",
+            Self::IfIcmplt(_) => "if_icmplt",
+            Self::IfIcmpge(_) => "if_icmpge",
+            Self::IfIcmpgt(_) => "if_icmpgt",
+            Self::IfIcmple(_) => "if_icmple",
+            Self::IfAcmpeq(_) => "if_acmpeq",
+            Self::IfAcmpne(_) => "if_acmpne",
+            Self::Goto(_) => "goto",
+            Self::Jsr(_) => "jsr",
+            Self::Ret(_) => "ret",
+            Self::Tableswitch { .. } => "tableswitch",
+            Self::Lookupswitch { .. } => "lookupswitch",
+            Self::Ireturn => "ireturn",
+            Self::Lreturn => "lreturn",
+            Self::Freturn => "freturn",
+            Self::Dreturn => "dreturn",
+            Self::Areturn => "areturn",
+            Self::Return => "return",
+            Self::Getstatic(_) => "getstatic",
+            Self::Putstatic(_) => "putstatic",
+            Self::Getfield(_) => "getfield",
+            Self::Putfield(_) => "putfield",
+            Self::Invokevirtual(_) => "invokevirtual",
+            Self::Invokespecial(_) => "invokespecial",
+            Self::Invokestatic(_) => "invokestatic",
+            Self::Invokeinterface { .. } => "invokeinterface",
+            Self::Invokedynamic(_) => "invokedynamic",
+            Self::New(_) => "new",
+            Self::Newarray(_) => "newarray",
+            Self::Anewarray(_) => "anewarray",
+            Self::Arraylength => "arraylength",
+            Self::Athrow => "athrow",
+            Self::Checkcast(_) => "checkcast",
+            Self::Instanceof(_) => "instanceof",
+            Self::Monitorenter => "monitorenter",
+            Self::Monitorexit => "monitorexit",
+            Self::Wide(_) => "wide",
+            Self::Multianewarray { .. } => "multianewarray",
+            Self::Ifnull(_) => "ifnull",
+            Self::Ifnonnull(_) => "ifnonnull",
+            Self::GotoW(_) => "goto_w",
+            Self::JsrW(_) => "jsr_w",
+        }
+    }
+
+    /// The constant pool index operand, if any.
+    pub fn constant_index(&self) -> Option<u16> {
+        match self {
+            Operation::Ldc(index) => Some(*index as u16),
+            Operation::LdcW(index)
+            | Operation::Ldc2W(index)
+            | Operation::Getstatic(index)
+            | Operation::Putstatic(index)
+            | Operation::Getfield(index)
+            | Operation::Putfield(index)
+            | Operation::Invokevirtual(index)
+            | Operation::Invokespecial(index)
+            | Operation::Invokestatic(index)
+            | Operation::Invokeinterface { index, .. }
+            | Operation::Invokedynamic(index)
+            | Operation::New(index)
+            | Operation::Anewarray(index)
+            | Operation::Checkcast(index)
+            | Operation::Instanceof(index)
+            | Operation::Multianewarray { index, .. } => Some(*index),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Instruction {
+    offset: u32,
+    operation: Operation,
+}
+
+/// One line per instruction, as in [`crate::CodeAttribute::disassembly_text`].
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Branch targets are shown as absolute offsets, as in javap.
+        let target = |relative: i32| self.offset as i64 + relative as i64;
+        write!(f, "{}: {}", self.offset, self.operation.mnemonic())?;
+        match &self.operation {
+            Operation::Bipush(value) => write!(f, " {value}"),
+            Operation::Sipush(value) => write!(f, " {value}"),
+            Operation::Iload(index)
+            | Operation::Lload(index)
+            | Operation::Fload(index)
+            | Operation::Dload(index)
+            | Operation::Aload(index)
+            | Operation::Istore(index)
+            | Operation::Lstore(index)
+            | Operation::Fstore(index)
+            | Operation::Dstore(index)
+            | Operation::Astore(index)
+            | Operation::Ret(index) => write!(f, " {index}"),
+            Operation::Iinc { index, value } => write!(f, " {index}, {value}"),
+            Operation::Ifeq(branch)
+            | Operation::Ifne(branch)
+            | Operation::Iflt(branch)
+            | Operation::Ifge(branch)
+            | Operation::Ifgt(branch)
+            | Operation::Ifle(branch)
+            | Operation::IfIcmpeq(branch)
+            | Operation::IfIcmpne(branch)
+            | Operation::IfIcmplt(branch)
+            | Operation::IfIcmpge(branch)
+            | Operation::IfIcmpgt(branch)
+            | Operation::IfIcmple(branch)
+            | Operation::IfAcmpeq(branch)
+            | Operation::IfAcmpne(branch)
+            | Operation::Goto(branch)
+            | Operation::Jsr(branch)
+            | Operation::Ifnull(branch)
+            | Operation::Ifnonnull(branch) => write!(f, " {}", target(*branch as i32)),
+            Operation::GotoW(branch) | Operation::JsrW(branch) => {
+                write!(f, " {}", target(*branch))
+            }
+            Operation::Tableswitch {
+                default,
+                low,
+                offsets,
+                ..
+            } => {
+                write!(f, " {{")?;
+                for (key, offset) in (*low as i64..).zip(offsets) {
+                    write!(f, " {key}: {},", target(*offset))?;
+                }
+                write!(f, " default: {} }}", target(*default))
+            }
+            Operation::Lookupswitch { default, pairs } => {
+                write!(f, " {{")?;
+                for (key, offset) in pairs {
+                    write!(f, " {key}: {},", target(*offset))?;
+                }
+                write!(f, " default: {} }}", target(*default))
+            }
+            Operation::Invokeinterface { index, count } => write!(f, " #{index}, {count}"),
+            Operation::Multianewarray { index, dimensions } => {
+                write!(f, " #{index}, {dimensions}")
+            }
+            Operation::Newarray(array_type) => write!(f, " {}", array_type.keyword()),
+            Operation::Wide(operation) => {
+                write!(f, " {}", operation.mnemonic())?;
+                match operation {
+                    WideOperation::Iinc { index, value } => write!(f, " {index}, {value}"),
+                    WideOperation::Iload(index)
+                    | WideOperation::Lload(index)
+                    | WideOperation::Fload(index)
+                    | WideOperation::Dload(index)
+                    | WideOperation::Aload(index)
+                    | WideOperation::Istore(index)
+                    | WideOperation::Lstore(index)
+                    | WideOperation::Fstore(index)
+                    | WideOperation::Dstore(index)
+                    | WideOperation::Astore(index)
+                    | WideOperation::Ret(index) => write!(f, " {index}"),
+                }
+            }
+            operation => match operation.constant_index() {
+                Some(index) => write!(f, " #{index}"),
+                None => Ok(()),
+            },
+        }
+    }
+}
+
+impl Instruction {
+    /// Offset of the opcode from the beginning of the code array.
+    pub fn offset(&self) -> u32 {
+        self.offset
+    }
+
+    pub fn operation(&self) -> &Operation {
+        &self.operation
+    }
+}
+
+fn read_i1<I: std::io::Read>(input: &mut I) -> std::io::Result<i8> {
+    Ok(read_u1(input)? as i8)
+}
+
+fn read_i2<I: std::io::Read>(input: &mut I) -> std::io::Result<i16> {
+    Ok(read_u2(input)? as i16)
+}
+
+fn read_i4<I: std::io::Read>(input: &mut I) -> std::io::Result<i32> {
+    Ok(read_u4(input)? as i32)
+}
+
+fn read_wide_operation(code: &[u8], input: &mut &[u8]) -> Result<WideOperation, ParseError> {
+    let offset = (code.len() - input.len()) as u32;
+    let opcode = read_u1(input)?;
+    Ok(match opcode {
+        0x15 => WideOperation::Iload(read_u2(input)?),
+        0x16 => WideOperation::Lload(read_u2(input)?),
+        0x17 => WideOperation::Fload(read_u2(input)?),
+        0x18 => WideOperation::Dload(read_u2(input)?),
+        0x19 => WideOperation::Aload(read_u2(input)?),
+        0x36 => WideOperation::Istore(read_u2(input)?),
+        0x37 => WideOperation::Lstore(read_u2(input)?),
+        0x38 => WideOperation::Fstore(read_u2(input)?),
+        0x39 => WideOperation::Dstore(read_u2(input)?),
+        0x3a => WideOperation::Astore(read_u2(input)?),
+        0xa9 => WideOperation::Ret(read_u2(input)?),
+        0x84 => WideOperation::Iinc {
+            index: read_u2(input)?,
+            value: read_i2(input)?,
+        },
+        _ => return Err(ParseError::IllegalOpcode { opcode, offset }),
+    })
+}
+
+fn read_operation(code: &[u8], input: &mut &[u8]) -> Result<Operation, ParseError> {
+    let offset = (code.len() - input.len()) as u32;
+    let opcode = read_u1(input)?;
+    Ok(match opcode {
+        0x00 => Operation::Nop,
+        0x01 => Operation::AconstNull,
+        0x02 => Operation::IconstM1,
+        0x03 => Operation::Iconst0,
+        0x04 => Operation::Iconst1,
+        0x05 => Operation::Iconst2,
+        0x06 => Operation::Iconst3,
+        0x07 => Operation::Iconst4,
+        0x08 => Operation::Iconst5,
+        0x09 => Operation::Lconst0,
+        0x0a => Operation::Lconst1,
+        0x0b => Operation::Fconst0,
+        0x0c => Operation::Fconst1,
+        0x0d => Operation::Fconst2,
+        0x0e => Operation::Dconst0,
+        0x0f => Operation::Dconst1,
+        0x10 => Operation::Bipush(read_i1(input)?),
+        0x11 => Operation::Sipush(read_i2(input)?),
+        0x12 => Operation::Ldc(read_u1(input)?),
+        0x13 => Operation::LdcW(read_u2(input)?),
+        0x14 => Operation::Ldc2W(read_u2(input)?),
+        0x15 => Operation::Iload(read_u1(input)?),
+        0x16 => Operation::Lload(read_u1(input)?),
+        0x17 => Operation::Fload(read_u1(input)?),
+        0x18 => Operation::Dload(read_u1(input)?),
+        0x19 => Operation::Aload(read_u1(input)?),
+        0x1a => Operation::Iload0,
+        0x1b => Operation::Iload1,
+        0x1c => Operation::Iload2,
+        0x1d => Operation::Iload3,
+        0x1e => Operation::Lload0,
+        0x1f => Operation::Lload1,
+        0x20 => Operation::Lload2,
+        0x21 => Operation::Lload3,
+        0x22 => Operation::Fload0,
+        0x23 => Operation::Fload1,
+        0x24 => Operation::Fload2,
+        0x25 => Operation::Fload3,
+        0x26 => Operation::Dload0,
+        0x27 => Operation::Dload1,
+        0x28 => Operation::Dload2,
+        0x29 => Operation::Dload3,
+        0x2a => Operation::Aload0,
+        0x2b => Operation::Aload1,
+        0x2c => Operation::Aload2,
+        0x2d => Operation::Aload3,
+        0x2e => Operation::Iaload,
+        0x2f => Operation::Laload,
+        0x30 => Operation::Faload,
+        0x31 => Operation::Daload,
+        0x32 => Operation::Aaload,
+        0x33 => Operation::Baload,
+        0x34 => Operation::Caload,
+        0x35 => Operation::Saload,
+        0x36 => Operation::Istore(read_u1(input)?),
+        0x37 => Operation::Lstore(read_u1(input)?),
+        0x38 => Operation::Fstore(read_u1(input)?),
+        0x39 => Operation::Dstore(read_u1(input)?),
+        0x3a => Operation::Astore(read_u1(input)?),
+        0x3b => Operation::Istore0,
+        0x3c => Operation::Istore1,
+        0x3d => Operation::Istore2,
+        0x3e => Operation::Istore3,
+        0x3f => Operation::Lstore0,
+        0x40 => Operation::Lstore1,
+        0x41 => Operation::Lstore2,
+        0x42 => Operation::Lstore3,
+        0x43 => Operation::Fstore0,
+        0x44 => Operation::Fstore1,
+        0x45 => Operation::Fstore2,
+        0x46 => Operation::Fstore3,
+        0x47 => Operation::Dstore0,
+        0x48 => Operation::Dstore1,
+        0x49 => Operation::Dstore2,
+        0x4a => Operation::Dstore3,
+        0x4b => Operation::Astore0,
+        0x4c => Operation::Astore1,
+        0x4d => Operation::Astore2,
+        0x4e => Operation::Astore3,
+        0x4f => Operation::Iastore,
+        0x50 => Operation::Lastore,
+        0x51 => Operation::Fastore,
+        0x52 => Operation::Dastore,
+        0x53 => Operation::Aastore,
+        0x54 => Operation::Bastore,
+        0x55 => Operation::Castore,
+        0x56 => Operation::Sastore,
+        0x57 => Operation::Pop,
+        0x58 => Operation::Pop2,
+        0x59 => Operation::Dup,
+        0x5a => Operation::DupX1,
+        0x5b => Operation::DupX2,
+        0x5c => Operation::Dup2,
+        0x5d => Operation::Dup2X1,
+        0x5e => Operation::Dup2X2,
+        0x5f => Operation::Swap,
+        0x60 => Operation::Iadd,
+        0x61 => Operation::Ladd,
+        0x62 => Operation::Fadd,
+        0x63 => Operation::Dadd,
+        0x64 => Operation::Isub,
+        0x65 => Operation::Lsub,
+        0x66 => Operation::Fsub,
+        0x67 => Operation::Dsub,
+        0x68 => Operation::Imul,
+        0x69 => Operation::Lmul,
+        0x6a => Operation::Fmul,
+        0x6b => Operation::Dmul,
+        0x6c => Operation::Idiv,
+        0x6d => Operation::Ldiv,
+        0x6e => Operation::Fdiv,
+        0x6f => Operation::Ddiv,
+        0x70 => Operation::Irem,
+        0x71 => Operation::Lrem,
+        0x72 => Operation::Frem,
+        0x73 => Operation::Drem,
+        0x74 => Operation::Ineg,
+        0x75 => Operation::Lneg,
+        0x76 => Operation::Fneg,
+        0x77 => Operation::Dneg,
+        0x78 => Operation::Ishl,
+        0x79 => Operation::Lshl,
+        0x7a => Operation::Ishr,
+        0x7b => Operation::Lshr,
+        0x7c => Operation::Iushr,
+        0x7d => Operation::Lushr,
+        0x7e => Operation::Iand,
+        0x7f => Operation::Land,
+        0x80 => Operation::Ior,
+        0x81 => Operation::Lor,
+        0x82 => Operation::Ixor,
+        0x83 => Operation::Lxor,
+        0x84 => Operation::Iinc {
+            index: read_u1(input)?,
+            value: read_i1(input)?,
+        },
+        0x85 => Operation::I2l,
+        0x86 => Operation::I2f,
+        0x87 => Operation::I2d,
+        0x88 => Operation::L2i,
+        0x89 => Operation::L2f,
+        0x8a => Operation::L2d,
+        0x8b => Operation::F2i,
+        0x8c => Operation::F2l,
+        0x8d => Operation::F2d,
+        0x8e => Operation::D2i,
+        0x8f => Operation::D2l,
+        0x90 => Operation::D2f,
+        0x91 => Operation::I2b,
+        0x92 => Operation::I2c,
+        0x93 => Operation::I2s,
+        0x94 => Operation::Lcmp,
+        0x95 => Operation::Fcmpl,
+        0x96 => Operation::Fcmpg,
+        0x97 => Operation::Dcmpl,
+        0x98 => Operation::Dcmpg,
+        0x99 => Operation::Ifeq(read_i2(input)?),
+        0x9a => Operation::Ifne(read_i2(input)?),
+        0x9b => Operation::Iflt(read_i2(input)?),
+        0x9c => Operation::Ifge(read_i2(input)?),
+        0x9d => Operation::Ifgt(read_i2(input)?),
+        0x9e => Operation::Ifle(read_i2(input)?),
+        0x9f => Operation::IfIcmpeq(read_i2(input)?),
+        0xa0 => Operation::IfIcmpne(read_i2(input)?),
+        0xa1 => Operation::IfIcmplt(read_i2(input)?),
+        0xa2 => Operation::IfIcmpge(read_i2(input)?),
+        0xa3 => Operation::IfIcmpgt(read_i2(input)?),
+        0xa4 => Operation::IfIcmple(read_i2(input)?),
+        0xa5 => Operation::IfAcmpeq(read_i2(input)?),
+        0xa6 => Operation::IfAcmpne(read_i2(input)?),
+        0xa7 => Operation::Goto(read_i2(input)?),
+        0xa8 => Operation::Jsr(read_i2(input)?),
+        0xa9 => Operation::Ret(read_u1(input)?),
+        0xaa => {
+            skip_padding(code, input)?;
+            let default = read_i4(input)?;
+            let low = read_i4(input)?;
+            let high = read_i4(input)?;
+            if low > high {
+                return Err(raw::malformed(
+                    "Code",
+                    format!("tableswitch low {low} is greater than high {high}"),
+                ));
+            }
+            // Bounded by the remaining input, as the count itself may be bogus.
+            let count = (high as i64 - low as i64 + 1) as usize;
+            let mut offsets = Vec::with_capacity(count.min(input.len() / 4));
+            for _ in low..=high {
+                offsets.push(read_i4(input)?);
+            }
+            Operation::Tableswitch {
+                default,
+                low,
+                high,
+                offsets,
+            }
+        }
+        0xab => {
+            skip_padding(code, input)?;
+            let default = read_i4(input)?;
+            let npairs = read_i4(input)?;
+            if npairs < 0 {
+                return Err(raw::malformed(
+                    "Code",
+                    format!("lookupswitch npairs {npairs} is negative"),
+                ));
+            }
+            let mut pairs = Vec::with_capacity((npairs as usize).min(input.len() / 8));
+            for _ in 0..npairs {
+                pairs.push((read_i4(input)?, read_i4(input)?));
+            }
+            Operation::Lookupswitch { default, pairs }
+        }
+        0xac => Operation::Ireturn,
+        0xad => Operation::Lreturn,
+        0xae => Operation::Freturn,
+        0xaf => Operation::Dreturn,
+        0xb0 => Operation::Areturn,
+        0xb1 => Operation::Return,
+        0xb2 => Operation::Getstatic(read_u2(input)?),
+        0xb3 => Operation::Putstatic(read_u2(input)?),
+        0xb4 => Operation::Getfield(read_u2(input)?),
+        0xb5 => Operation::Putfield(read_u2(input)?),
+        0xb6 => Operation::Invokevirtual(read_u2(input)?),
+        0xb7 => Operation::Invokespecial(read_u2(input)?),
+        0xb8 => Operation::Invokestatic(read_u2(input)?),
+        0xb9 => {
+            let index = read_u2(input)?;
+            let count = read_u1(input)?;
+            let padding = read_u1(input)?;
+            if padding != 0 {
+                return Err(raw::malformed(
+                    "Code",
+                    format!("invokeinterface padding {padding} is not zero"),
+                ));
+            }
+            Operation::Invokeinterface { index, count }
+        }
+        0xba => {
+            let index = read_u2(input)?;
+            let padding = read_u2(input)?;
+            if padding != 0 {
+                return Err(raw::malformed(
+                    "Code",
+                    format!("invokedynamic padding {padding} is not zero"),
+                ));
+            }
+            Operation::Invokedynamic(index)
+        }
+        0xbb => Operation::New(read_u2(input)?),
+        0xbc => Operation::Newarray(match read_u1(input)? {
+            4 => ArrayType::TBoolean,
+            5 => ArrayType::TChar,
+            6 => ArrayType::TFloat,
+            7 => ArrayType::TDouble,
+            8 => ArrayType::TByte,
+            9 => ArrayType::TShort,
+            10 => ArrayType::TInt,
+            11 => ArrayType::TLong,
+            atype => {
+                return Err(raw::malformed(
+                    "Code",
+                    format!("newarray atype {atype} is not assigned"),
+                ));
+            }
+        }),
+        0xbd => Operation::Anewarray(read_u2(input)?),
+        0xbe => Operation::Arraylength,
+        0xbf => Operation::Athrow,
+        0xc0 => Operation::Checkcast(read_u2(input)?),
+        0xc1 => Operation::Instanceof(read_u2(input)?),
+        0xc2 => Operation::Monitorenter,
+        0xc3 => Operation::Monitorexit,
+        0xc4 => Operation::Wide(read_wide_operation(code, input)?),
+        0xc5 => Operation::Multianewarray {
+            index: read_u2(input)?,
+            dimensions: read_u1(input)?,
+        },
+        0xc6 => Operation::Ifnull(read_i2(input)?),
+        0xc7 => Operation::Ifnonnull(read_i2(input)?),
+        0xc8 => Operation::GotoW(read_i4(input)?),
+        0xc9 => Operation::JsrW(read_i4(input)?),
+        // 0xca (breakpoint), 0xfe and 0xff (impdep1, impdep2) are reserved, the rest is unused.
+        _ => return Err(ParseError::IllegalOpcode { opcode, offset }),
+    })
+}
+
+/// Skip the 0-3 bytes padding `tableswitch` and `lookupswitch` align their operands with.
+fn skip_padding(code: &[u8], input: &mut &[u8]) -> Result<(), ParseError> {
+    let offset = code.len() - input.len();
+    for _ in 0..(4 - offset % 4) % 4 {
+        read_u1(input)?;
+    }
+    Ok(())
+}
+
+pub(crate) fn decode(code: &[u8]) -> Result<Vec<Instruction>, ParseError> {
+    let mut input = code;
+    let mut instructions = vec![];
+    while !input.is_empty() {
+        let offset = (code.len() - input.len()) as u32;
+        let operation = read_operation(code, &mut input)?;
+        instructions.push(Instruction { offset, operation });
+    }
+    Ok(instructions)
+}
+
+/// Length of the instruction at `offset` including its opcode, or `None` when the opcode is
+/// illegal or the operands run past the end of `code`.
+fn instruction_length(code: &[u8], offset: usize) -> Option<usize> {
+    let i4 = |at: usize| {
+        let bytes = code.get(at..at + 4)?;
+        Some(i32::from_be_bytes(bytes.try_into().unwrap()) as i64)
+    };
+    let length = match *code.get(offset)? {
+        0x00..=0x0f | 0x1a..=0x35 | 0x3b..=0x83 | 0x85..=0x98 | 0xac..=0xb1 => 1,
+        0xbe | 0xbf | 0xc2 | 0xc3 => 1,
+        0x10 | 0x12 | 0x15..=0x19 | 0x36..=0x3a | 0xa9 | 0xbc => 2,
+        0x11 | 0x13 | 0x14 | 0x84 | 0x99..=0xa8 | 0xb2..=0xb8 | 0xbb | 0xbd => 3,
+        0xc0 | 0xc1 | 0xc6 | 0xc7 => 3,
+        0xc5 => 4,
+        0xb9 | 0xba | 0xc8 | 0xc9 => 5,
+        // Only the opcodes `read_wide_operation` accepts.
+        0xc4 => match *code.get(offset + 1)? {
+            0x15..=0x19 | 0x36..=0x3a | 0xa9 => 4,
+            0x84 => 6,
+            _ => return None,
+        },
+        opcode @ (0xaa | 0xab) => {
+            let operands = offset + 1 + (4 - (offset + 1) % 4) % 4;
+            let rest = if opcode == 0xaa {
+                12 + 4 * (i4(operands + 8)? - i4(operands + 4)? + 1)
+            } else {
+                8 + 8 * i4(operands + 4)?
+            };
+            operands - offset + usize::try_from(rest).ok()?
+        }
+        _ => return None,
+    };
+    (offset.checked_add(length)? <= code.len()).then_some(length)
+}
+
+/// `(offset, opcode)` of each instruction, up to the first illegal or truncated one.
+pub(crate) fn opcodes(code: &[u8]) -> impl Iterator<Item = (u32, u8)> {
+    let mut offset = 0;
+    std::iter::from_fn(move || {
+        let length = instruction_length(code, offset)?;
+        let item = (offset as u32, code[offset]);
+        offset += length;
+        Some(item)
+    })
+}
@@ -0,0 +1,229 @@
+/// Java keywords for reference kinds and access flags, to print them and to read them back.
+use std::fmt;
+use std::str::FromStr;
+
+use thiserror::Error;
+
+use crate::{
+    ClassAccessFlags, FieldAccessFlags, InnerClassAccessFlags, MethodAccessFlags,
+    MethodParameterAccessFlags, ReferenceKind,
+};
+
+#[derive(Debug, Error)]
+#[error("unknown keyword {keyword:?}")]
+pub struct KeywordError {
+    pub keyword: String,
+}
+
+fn from_keyword<T, const N: usize>(
+    values: [T; N],
+    keyword: impl Fn(&T) -> &'static str,
+    s: &str,
+) -> Result<T, KeywordError> {
+    values
+        .into_iter()
+        .find(|value| keyword(value) == s)
+        .ok_or_else(|| KeywordError {
+            keyword: s.to_string(),
+        })
+}
+
+impl ReferenceKind {
+    const VALUES: [Self; 9] = [
+        Self::RefGetField,
+        Self::RefGetStatic,
+        Self::RefPutField,
+        Self::RefPutStatic,
+        Self::RefInvokeVirtual,
+        Self::RefInvokeStatic,
+        Self::RefInvokeSpecial,
+        Self::RefNewInvokeSpecial,
+        Self::RefNewInvokeInterface,
+    ];
+
+    /// The instruction the method handle behaves as, e.g. `invokevirtual`, or
+    /// `newinvokespecial` for `new` followed by `invokespecial`.
+    pub fn keyword(&self) -> &'static str {
+        match self {
+            Self::RefGetField => "getfield",
+            Self::RefGetStatic => "getstatic",
+            Self::RefPutField => "putfield",
+            Self::RefPutStatic => "putstatic",
+            Self::RefInvokeVirtual => "invokevirtual",
+            Self::RefInvokeStatic => "invokestatic",
+            Self::RefInvokeSpecial => "invokespecial",
+            Self::RefNewInvokeSpecial => "newinvokespecial",
+            Self::RefNewInvokeInterface => "invokeinterface",
+        }
+    }
+}
+
+// Flags without a Java keyword go by their name in the JVMS, lower-cased and without `ACC_`.
+
+impl ClassAccessFlags {
+    /// The Java keyword, e.g. `public`, or the lower-cased name, e.g. `synthetic`.
+    pub fn keyword(&self) -> &'static str {
+        match self {
+            Self::AccPublic => "public",
+            Self::AccFinal => "final",
+            Self::AccSuper => "super",
+            Self::AccInterface => "interface",
+            Self::AccAbstract => "abstract",
+            Self::AccSynthetic => "synthetic",
+            Self::AccAnnotation => "annotation",
+            Self::AccEnum => "enum",
+            Self::AccModule => "module",
+        }
+    }
+}
+
+impl InnerClassAccessFlags {
+    /// The Java keyword, e.g. `static`, or the lower-cased name, e.g. `synthetic`.
+    pub fn keyword(&self) -> &'static str {
+        match self {
+            Self::AccPublic => "public",
+            Self::AccPrivate => "private",
+            Self::AccProtected => "protected",
+            Self::AccStatic => "static",
+            Self::AccFinal => "final",
+            Self::AccInterface => "interface",
+            Self::AccAbstract => "abstract",
+            Self::AccSynthetic => "synthetic",
+            Self::AccAnnotation => "annotation",
+            Self::AccEnum => "enum",
+        }
+    }
+}
+
+impl FieldAccessFlags {
+    /// The Java keyword, e.g. `volatile`, or the lower-cased name, e.g. `synthetic`.
+    pub fn keyword(&self) -> &'static str {
+        match self {
+            Self::AccPublic => "public",
+            Self::AccPrivate => "private",
+            Self::AccProtected => "protected",
+            Self::AccStatic => "static",
+            Self::AccFinal => "final",
+            Self::AccVolatile => "volatile",
+            Self::AccTransient => "transient",
+            Self::AccSynthetic => "synthetic",
+            Self::AccEnum => "enum",
+        }
+    }
+}
+
+impl MethodAccessFlags {
+    /// The Java keyword, e.g. `synchronized` or `strictfp`, or the lower-cased name, e.g.
+    /// `bridge`.
+    pub fn keyword(&self) -> &'static str {
+        match self {
+            Self::AccPublic => "public",
+            Self::AccPrivate => "private",
+            Self::AccProtected => "protected",
+            Self::AccStatic => "static",
+            Self::AccFinal => "final",
+            Self::AccSynchronized => "synchronized",
+            Self::AccBridge => "bridge",
+            Self::AccVarargs => "varargs",
+            Self::AccNative => "native",
+            Self::AccAbstract => "abstract",
+            Self::AccStrict => "strictfp",
+            Self::AccSynthetic => "synthetic",
+        }
+    }
+}
+
+impl MethodParameterAccessFlags {
+    /// The Java keyword, `final`, or the lower-cased name, e.g. `mandated`.
+    pub fn keyword(&self) -> &'static str {
+        match self {
+            Self::AccFinal => "final",
+            Self::AccSynthetic => "synthetic",
+            Self::AccMandated => "mandated",
+        }
+    }
+}
+
+impl fmt::Display for ReferenceKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.keyword())
+    }
+}
+
+impl FromStr for ReferenceKind {
+    type Err = KeywordError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        from_keyword(Self::VALUES, Self::keyword, s)
+    }
+}
+
+impl fmt::Display for ClassAccessFlags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.keyword())
+    }
+}
+
+impl FromStr for ClassAccessFlags {
+    type Err = KeywordError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        from_keyword(Self::VALUES, Self::keyword, s)
+    }
+}
+
+impl fmt::Display for InnerClassAccessFlags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.keyword())
+    }
+}
+
+impl FromStr for InnerClassAccessFlags {
+    type Err = KeywordError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        from_keyword(Self::VALUES, Self::keyword, s)
+    }
+}
+
+impl fmt::Display for FieldAccessFlags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.keyword())
+    }
+}
+
+impl FromStr for FieldAccessFlags {
+    type Err = KeywordError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        from_keyword(Self::VALUES, Self::keyword, s)
+    }
+}
+
+impl fmt::Display for MethodAccessFlags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.keyword())
+    }
+}
+
+impl FromStr for MethodAccessFlags {
+    type Err = KeywordError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        from_keyword(Self::VALUES, Self::keyword, s)
+    }
+}
+
+impl fmt::Display for MethodParameterAccessFlags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.keyword())
+    }
+}
+
+impl FromStr for MethodParameterAccessFlags {
+    type Err = KeywordError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        from_keyword(Self::VALUES, Self::keyword, s)
+    }
+}
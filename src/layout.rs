@@ -0,0 +1,442 @@
+/// The layouts of the predefined attributes, as far as they refer to the constant pool, walked
+/// alike to read the references, to rewrite them and to check attribute lengths.
+use std::io;
+
+use crate::raw::{self, ParseError, Usage};
+
+/// A constant pool index within the `info` of an attribute.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Reference {
+    /// Offset of the index from the beginning of the `info` being walked.
+    pub(crate) position: usize,
+    /// Whether the index takes one byte, as the operand of `ldc`, rather than two.
+    pub(crate) narrow: bool,
+    pub(crate) index: u16,
+    pub(crate) usage: Usage,
+}
+
+/// What a walk does with the parts of an attribute it comes across.
+pub(crate) trait Visit {
+    /// Whether to decode the instructions of `Code` and walk the attributes nested in `Code`
+    /// and `Record`, rather than only skip over them.
+    const DEEP: bool = true;
+
+    fn reference(&mut self, reference: Reference) -> Result<(), ParseError>;
+
+    /// A nested attribute whose layout is not known, skipped over as a whole.
+    fn unknown(&mut self) -> Result<(), ParseError> {
+        Ok(())
+    }
+}
+
+/// Walk the `info` of the attribute `name`, with `depth` levels of nesting left, giving the
+/// length its layout takes, or `None` when the layout of `name` is not known.
+///
+/// Constant pool indexes are handed to `visit` in file order, including the names of nested
+/// attributes but not index 0. Nested attributes must take their `attribute_length` exactly,
+/// while the length taken by the outermost one is left to the caller to compare. Fails with
+/// [`io::ErrorKind::UnexpectedEof`] when the counts point past the end of `info`.
+pub(crate) fn walk_attribute(
+    pool: &[Option<raw::CpInfo>],
+    name: &str,
+    info: &[u8],
+    depth: usize,
+    visit: &mut impl Visit,
+) -> Result<Option<usize>, ParseError> {
+    let mut walker = Walker {
+        pool,
+        info,
+        position: 0,
+        end: info.len(),
+        visit,
+    };
+    if !walker.attribute(name, depth)? {
+        return Ok(None);
+    }
+    Ok(Some(walker.position))
+}
+
+struct Walker<'a, V> {
+    pool: &'a [Option<raw::CpInfo>],
+    info: &'a [u8],
+    position: usize,
+    /// The end of the innermost attribute being walked.
+    end: usize,
+    visit: &'a mut V,
+}
+
+impl<'a, V: Visit> Walker<'a, V> {
+    fn bytes(&mut self, length: usize) -> Result<&'a [u8], ParseError> {
+        let start = self.position;
+        if length > self.end - start {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+        }
+        self.position += length;
+        let info = self.info;
+        Ok(&info[start..self.position])
+    }
+
+    fn u1(&mut self) -> Result<u8, ParseError> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u2(&mut self) -> Result<u16, ParseError> {
+        let bytes = self.bytes(2)?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn u4(&mut self) -> Result<u32, ParseError> {
+        let bytes = self.bytes(4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    /// Visit the index at the current position, returning it.
+    fn index(&mut self, usage: Usage) -> Result<u16, ParseError> {
+        let position = self.position;
+        let index = self.u2()?;
+        if index != 0 {
+            self.visit.reference(Reference {
+                position,
+                narrow: false,
+                index,
+                usage,
+            })?;
+        }
+        Ok(index)
+    }
+
+    /// A `u2` count of indexes and the indexes following it.
+    fn indexes(&mut self) -> Result<(), ParseError> {
+        for _ in 0..self.u2()? {
+            self.index(Usage::Other)?;
+        }
+        Ok(())
+    }
+
+    /// Walk the attribute `name` up to `end`, giving whether its layout is known.
+    fn attribute(&mut self, name: &str, depth: usize) -> Result<bool, ParseError> {
+        match name {
+            "ConstantValue" | "SourceFile" | "NestHost" | "ModuleMainClass" | "ModuleTarget" => {
+                self.index(Usage::Other)?;
+            }
+
+            "Signature" => {
+                self.index(Usage::Descriptor)?;
+            }
+
+            "Exceptions" | "NestMembers" | "PermittedSubclasses" | "ModulePackages" => {
+                self.indexes()?;
+            }
+
+            "Deprecated" | "Synthetic" => {}
+
+            "ModuleResolution" => {
+                let _module_resolution_flags = self.u2()?;
+            }
+
+            "SourceDebugExtension" => {
+                let length = self.end - self.position;
+                self.bytes(length)?;
+            }
+
+            "LineNumberTable" => {
+                let line_number_table_length = self.u2()? as usize;
+                // start_pc and line_number.
+                self.bytes(4 * line_number_table_length)?;
+            }
+
+            "Code" => {
+                let _max_stack = self.u2()?;
+                let _max_locals = self.u2()?;
+                let code_length = self.u4()? as usize;
+                let start = self.position;
+                let code = self.bytes(code_length)?;
+                if V::DEEP {
+                    for instruction in crate::instruction::decode(code)? {
+                        let position = start + instruction.offset() as usize + 1;
+                        let (narrow, index) = match instruction.operation() {
+                            crate::Operation::Ldc(index) => (true, *index as u16),
+                            operation => match operation.constant_index() {
+                                Some(index) => (false, index),
+                                None => continue,
+                            },
+                        };
+                        self.visit.reference(Reference {
+                            position,
+                            narrow,
+                            index,
+                            usage: Usage::Other,
+                        })?;
+                    }
+                }
+
+                for _ in 0..self.u2()? {
+                    let _start_pc = self.u2()?;
+                    let _end_pc = self.u2()?;
+                    let _handler_pc = self.u2()?;
+                    self.index(Usage::Other)?;
+                }
+
+                self.attributes(depth)?;
+            }
+
+            "InnerClasses" => {
+                for _ in 0..self.u2()? {
+                    self.index(Usage::Other)?;
+                    self.index(Usage::Other)?;
+                    self.index(Usage::Other)?;
+                    let _inner_class_access_flags = self.u2()?;
+                }
+            }
+
+            "MethodParameters" => {
+                for _ in 0..self.u1()? {
+                    self.index(Usage::Other)?;
+                    let _access_flags = self.u2()?;
+                }
+            }
+
+            "EnclosingMethod" => {
+                self.index(Usage::Other)?;
+                self.index(Usage::Other)?;
+            }
+
+            "LocalVariableTable" | "LocalVariableTypeTable" => {
+                for _ in 0..self.u2()? {
+                    let _start_pc = self.u2()?;
+                    let _length = self.u2()?;
+                    self.index(Usage::Other)?;
+                    // A descriptor, or a signature for LocalVariableTypeTable.
+                    self.index(Usage::Descriptor)?;
+                    let _index = self.u2()?;
+                }
+            }
+
+            "StackMapTable" => {
+                for _ in 0..self.u2()? {
+                    match self.u1()? {
+                        0..=63 => {}
+                        64..=127 => self.verification_type_info()?,
+                        247 => {
+                            let _offset_delta = self.u2()?;
+                            self.verification_type_info()?;
+                        }
+                        248..=251 => {
+                            let _offset_delta = self.u2()?;
+                        }
+                        frame_type @ 252..=254 => {
+                            let _offset_delta = self.u2()?;
+                            self.verification_type_infos(frame_type as usize - 251)?;
+                        }
+                        255 => {
+                            let _offset_delta = self.u2()?;
+                            let number_of_locals = self.u2()?;
+                            self.verification_type_infos(number_of_locals as usize)?;
+                            let number_of_stack_items = self.u2()?;
+                            self.verification_type_infos(number_of_stack_items as usize)?;
+                        }
+                        frame_type @ 128..=246 => {
+                            return Err(raw::malformed(
+                                "StackMapTable",
+                                format!("reserved frame_type {frame_type}"),
+                            ));
+                        }
+                    }
+                }
+            }
+
+            "RuntimeVisibleAnnotations" | "RuntimeInvisibleAnnotations" => {
+                for _ in 0..self.u2()? {
+                    self.annotation(name, depth)?;
+                }
+            }
+
+            "RuntimeVisibleParameterAnnotations" | "RuntimeInvisibleParameterAnnotations" => {
+                for _ in 0..self.u1()? {
+                    for _ in 0..self.u2()? {
+                        self.annotation(name, depth)?;
+                    }
+                }
+            }
+
+            "RuntimeVisibleTypeAnnotations" | "RuntimeInvisibleTypeAnnotations" => {
+                for _ in 0..self.u2()? {
+                    self.type_annotation(name, depth)?;
+                }
+            }
+
+            "AnnotationDefault" => self.element_value(name, depth)?,
+
+            "Record" => {
+                for _ in 0..self.u2()? {
+                    self.index(Usage::Other)?;
+                    self.index(Usage::Descriptor)?;
+                    self.attributes(depth)?;
+                }
+            }
+
+            "BootstrapMethods" => {
+                for _ in 0..self.u2()? {
+                    self.index(Usage::Other)?;
+                    self.indexes()?;
+                }
+            }
+
+            "Module" => {
+                // module_name_index, module_flags and module_version_index.
+                self.index(Usage::Other)?;
+                let _module_flags = self.u2()?;
+                self.index(Usage::Other)?;
+                for _ in 0..self.u2()? {
+                    // requires_index, requires_flags and requires_version_index.
+                    self.index(Usage::Other)?;
+                    let _requires_flags = self.u2()?;
+                    self.index(Usage::Other)?;
+                }
+                // exports, then opens, each with their flags and the modules they are for.
+                for _ in 0..2 {
+                    for _ in 0..self.u2()? {
+                        self.index(Usage::Other)?;
+                        let _flags = self.u2()?;
+                        self.indexes()?;
+                    }
+                }
+                // uses, then provides with the implementations.
+                self.indexes()?;
+                for _ in 0..self.u2()? {
+                    self.index(Usage::Other)?;
+                    self.indexes()?;
+                }
+            }
+
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    /// `attributes_count` and the attributes following it, as in `Code` and `Record`.
+    fn attributes(&mut self, depth: usize) -> Result<(), ParseError> {
+        for _ in 0..self.u2()? {
+            let attribute_name_index = if V::DEEP {
+                self.index(Usage::Other)?
+            } else {
+                self.u2()?
+            };
+            let attribute_length = self.u4()? as usize;
+            if !V::DEEP {
+                self.bytes(attribute_length)?;
+                continue;
+            }
+
+            let end = match self.position.checked_add(attribute_length) {
+                Some(end) if end <= self.end => end,
+                _ => return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into()),
+            };
+            let outer = std::mem::replace(&mut self.end, end);
+            let pool = self.pool;
+            let name = match pool.get(attribute_name_index as usize) {
+                Some(Some(raw::CpInfo::Utf8(name))) => name.as_str(),
+                _ => "",
+            };
+            if !self.attribute(name, raw::nest(depth)?)? {
+                self.visit.unknown()?;
+                self.position = end;
+            }
+            if self.position != end {
+                return Err(raw::malformed(
+                    name,
+                    format!("{} bytes past the contents", end - self.position),
+                ));
+            }
+            self.end = outer;
+        }
+        Ok(())
+    }
+
+    fn verification_type_info(&mut self) -> Result<(), ParseError> {
+        match self.u1()? {
+            0..=6 => {}
+            // Object_variable_info
+            7 => {
+                self.index(Usage::Other)?;
+            }
+            // Uninitialized_variable_info
+            8 => {
+                let _offset = self.u2()?;
+            }
+            tag => {
+                return Err(raw::malformed(
+                    "StackMapTable",
+                    format!("verification_type_info tag {tag}"),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn verification_type_infos(&mut self, count: usize) -> Result<(), ParseError> {
+        for _ in 0..count {
+            self.verification_type_info()?;
+        }
+        Ok(())
+    }
+
+    fn annotation(&mut self, name: &str, depth: usize) -> Result<(), ParseError> {
+        self.index(Usage::Descriptor)?;
+        for _ in 0..self.u2()? {
+            self.index(Usage::Other)?;
+            self.element_value(name, depth)?;
+        }
+        Ok(())
+    }
+
+    /// A `type_annotation`, skipping its `target_info` and `type_path`.
+    fn type_annotation(&mut self, name: &str, depth: usize) -> Result<(), ParseError> {
+        let target_info_length = match self.u1()? {
+            0x13..=0x15 => 0,
+            0x00 | 0x01 | 0x16 => 1,
+            0x10..=0x12 | 0x17 | 0x42..=0x46 => 2,
+            0x47..=0x4b => 3,
+            // localvar_target, 6 bytes for each entry of the table.
+            0x40 | 0x41 => 6 * self.u2()? as usize,
+            target_type => {
+                return Err(raw::malformed(
+                    name,
+                    format!("target_type {target_type:#04x}"),
+                ));
+            }
+        };
+        self.bytes(target_info_length)?;
+        let path_length = self.u1()? as usize;
+        self.bytes(2 * path_length)?;
+        self.annotation(name, depth)
+    }
+
+    fn element_value(&mut self, name: &str, depth: usize) -> Result<(), ParseError> {
+        match self.u1()? {
+            b'B' | b'C' | b'D' | b'F' | b'I' | b'J' | b'S' | b'Z' | b's' => {
+                self.index(Usage::Other)?;
+            }
+            b'c' => {
+                self.index(Usage::Descriptor)?;
+            }
+            b'e' => {
+                self.index(Usage::Descriptor)?;
+                self.index(Usage::Other)?;
+            }
+            b'@' => self.annotation(name, raw::nest(depth)?)?,
+            b'[' => {
+                for _ in 0..self.u2()? {
+                    self.element_value(name, raw::nest(depth)?)?;
+                }
+            }
+            tag => {
+                return Err(raw::malformed(
+                    name,
+                    format!("element_value tag {tag:#04x}"),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
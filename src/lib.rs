@@ -1,11 +1,53 @@
+mod annotation;
+mod descriptor;
+mod instruction;
+mod keyword;
+mod layout;
+mod loader;
+mod module;
+mod name;
+mod owned;
+mod pool;
 mod raw;
-
-use std::{io::{self, Write}, usize};
+mod signature;
+mod stack_map;
+mod text;
+mod validate;
+mod view;
+mod visitor;
+
+use std::borrow::Cow;
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::io::{self, Write};
 
 use base64::Engine as _;
 use serde::Serialize;
 
-use crate::raw::ParseError;
+pub use crate::annotation::{
+    Annotation, AnnotationTarget, ElementValue, LocalVariableTarget, TypeAnnotation, TypePathEntry,
+    TypePathKind,
+};
+pub use crate::descriptor::{
+    BaseType, DescriptorError, FieldType, MethodDescriptor, parse_field_descriptor,
+    parse_method_descriptor,
+};
+pub use crate::instruction::{ArrayType, Instruction, Operation, WideOperation};
+pub use crate::keyword::KeywordError;
+pub use crate::loader::{ClassLoader, Linkage};
+pub use crate::module::{
+    ExportsAccessFlags, ModuleAccessFlags, ModuleAttribute, ModuleExports, ModuleOpens,
+    ModuleProvides, ModuleRequires, RequiresAccessFlags,
+};
+pub use crate::name::split_nested_name;
+pub use crate::pool::PoolAnalysis;
+pub use crate::raw::{ParseError, ParseLimits};
+pub use crate::stack_map::{StackMapFrame, VerificationTypeInfo};
+pub use crate::text::TextOptions;
+pub use crate::validate::{Finding, ParseReport, validate};
+pub use crate::view::{MemberSignature, SignatureView};
+pub use crate::visitor::ClassVisitor;
 
 #[derive(Debug)]
 pub struct ClassFileVersion {
@@ -22,6 +64,58 @@ impl Serialize for ClassFileVersion {
     }
 }
 
+/// Names of the releases of class file major versions 45 onwards, as in JVMS Table 4.1-A.
+const JAVA_SE_NAMES: [&str; 25] = [
+    "JDK 1.1",
+    "J2SE 1.2",
+    "J2SE 1.3",
+    "J2SE 1.4",
+    "J2SE 5.0",
+    "Java SE 6",
+    "Java SE 7",
+    "Java SE 8",
+    "Java SE 9",
+    "Java SE 10",
+    "Java SE 11",
+    "Java SE 12",
+    "Java SE 13",
+    "Java SE 14",
+    "Java SE 15",
+    "Java SE 16",
+    "Java SE 17",
+    "Java SE 18",
+    "Java SE 19",
+    "Java SE 20",
+    "Java SE 21",
+    "Java SE 22",
+    "Java SE 23",
+    "Java SE 24",
+    "Java SE 25",
+];
+
+impl ClassFileVersion {
+    pub fn major_version(&self) -> u16 {
+        self.major_version
+    }
+
+    /// `0xFFFF` for a class file depending on preview features, since major version 56.
+    pub fn minor_version(&self) -> u16 {
+        self.minor_version
+    }
+
+    /// The release which introduced the major version, e.g. `"Java SE 21"` for 65 or
+    /// `"J2SE 5.0"` for 49, or `None` for a version this crate does not know of.
+    pub fn java_se_name(&self) -> Option<&'static str> {
+        let index = self.major_version.checked_sub(45)?;
+        JAVA_SE_NAMES.get(index as usize).copied()
+    }
+
+    /// See [`ClassFile::uses_preview_features`].
+    fn uses_preview_features(&self) -> bool {
+        self.major_version >= 56 && self.minor_version == 0xFFFF
+    }
+}
+
 #[derive(Debug)]
 pub struct Magic;
 
@@ -34,7 +128,7 @@ impl Serialize for Magic {
     }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
 pub enum ReferenceKind {
     RefGetField,
     RefGetStatic,
@@ -56,13 +150,95 @@ pub struct BootstrapMethod<S: AsRef<str>> {
     bootstrap_arguments: Vec<CpInfo<S>>,
 }
 
+impl<S: AsRef<str>> BootstrapMethod<S> {
+    pub fn reference_kind(&self) -> &ReferenceKind {
+        &self.reference_kind
+    }
+
+    pub fn class(&self) -> &str {
+        self.class.as_ref()
+    }
+
+    pub fn name(&self) -> &str {
+        self.name.as_ref()
+    }
+
+    pub fn descriptor(&self) -> &str {
+        self.descriptor.as_ref()
+    }
+
+    pub fn bootstrap_arguments(&self) -> &[CpInfo<S>] {
+        &self.bootstrap_arguments
+    }
+}
+
+/// The call site an `invokedynamic` instruction is linked to, or the bootstrap linkage of the
+/// dynamically-computed constant loaded by `ldc`, `ldc_w` or `ldc2_w`.
+#[derive(Debug, Serialize)]
+pub struct CallSite<'a, S: AsRef<str>> {
+    bootstrap_method: &'a BootstrapMethod<S>,
+    name: &'a S,
+    descriptor: &'a S,
+}
+
+/// An instruction with its constant pool operand resolved where it needs more than a lookup.
+#[derive(Debug, Serialize)]
+pub struct ResolvedInstruction<'a, S: AsRef<str>> {
+    #[serde(flatten)]
+    instruction: Instruction,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    call_site: Option<CallSite<'a, S>>,
+    /// Only with [`ClassFile::disassemble_with_loader`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    linkage: Option<Linkage>,
+}
+
+impl<'a, S: AsRef<str>> CallSite<'a, S> {
+    pub fn bootstrap_method(&self) -> &'a BootstrapMethod<S> {
+        self.bootstrap_method
+    }
+
+    pub fn name(&self) -> &'a str {
+        self.name.as_ref()
+    }
+
+    pub fn descriptor(&self) -> &'a str {
+        self.descriptor.as_ref()
+    }
+}
+
+impl<'a, S: AsRef<str>> ResolvedInstruction<'a, S> {
+    pub fn instruction(&self) -> &Instruction {
+        &self.instruction
+    }
+
+    pub fn call_site(&self) -> Option<&CallSite<'a, S>> {
+        self.call_site.as_ref()
+    }
+
+    /// Whether the method invoked by `invokevirtual`, `invokespecial` or `invokestatic` exists.
+    pub fn linkage(&self) -> Option<&Linkage> {
+        self.linkage.as_ref()
+    }
+}
+
+/// Serialized externally tagged (`{"Class":{"name":"..."}}`), or adjacently tagged
+/// (`{"tag":"Class","value":{"name":"..."}}`) with the `tagged` feature.
+///
+/// `Long` and `Double` are serialized as the decimal string and the bit pattern, e.g.
+/// `{"value":"9223372036854775807","bits":"0x7fffffffffffffff"}`, as JSON numbers cannot hold
+/// every `long` exactly, nor NaN and the infinities.
+///
+/// Constant kinds may be added in minor releases, so matches need a wildcard arm.
 #[derive(Debug, Serialize)]
+#[cfg_attr(feature = "tagged", serde(tag = "tag", content = "value"))]
+#[non_exhaustive]
 pub enum CpInfo<S: AsRef<str>> {
     Utf8(S),
     Integer(i32),
     Float(f32),
-    Long(i64),
-    Double(f64),
+    Long(#[serde(serialize_with = "serialize_long")] i64),
+    Double(#[serde(serialize_with = "serialize_double")] f64),
     Class {
         name: S,
     },
@@ -93,17 +269,24 @@ pub enum CpInfo<S: AsRef<str>> {
         class: S,
         name: S,
         descriptor: S,
+        /// Index of the `Fieldref`, `Methodref` or `InterfaceMethodref` in the constant pool of
+        /// the class file. Only in the constant pool, with
+        /// [`ParseOptions::method_handle_reference_index`].
+        #[serde(skip_serializing_if = "Option::is_none")]
+        reference_index: Option<u16>,
     },
     MethodType {
         descriptor: S,
     },
     Dynamic {
-        bootstrap_method_attr: (), // TODO
+        /// Index into the `BootstrapMethods` attribute.
+        bootstrap_method_attr: u16,
         name: S,
         descriptor: S,
     },
     InvokeDynamic {
-        bootstrap_method_attr: (), // TODO
+        /// Index into the `BootstrapMethods` attribute.
+        bootstrap_method_attr: u16,
         name: S,
         descriptor: S,
     },
@@ -115,17 +298,18 @@ pub enum CpInfo<S: AsRef<str>> {
     },
 }
 
+/// `Long` and `Double` are serialized as in [`CpInfo`].
 #[derive(Debug, Serialize)]
 pub enum ConstantValueAttribute<S: AsRef<str>> {
     Integer(i32),
     Float(f32),
-    Long(i64),
-    Double(f64),
+    Long(#[serde(serialize_with = "serialize_long")] i64),
+    Double(#[serde(serialize_with = "serialize_double")] f64),
     String(S),
 }
 
 #[repr(u16)]
-#[derive(Debug, Serialize, Clone, Copy)]
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
 pub enum InnerClassAccessFlags {
     AccPublic = 0x0001,
     AccPrivate = 0x0002,
@@ -162,23 +346,611 @@ pub struct InnerClass<S: AsRef<str>> {
     inner_class_access_flags: Vec<InnerClassAccessFlags>,
 }
 
+/// `AccMandated` marks a parameter implicitly declared in source, e.g. the enclosing instance
+/// of an inner class constructor, and `AccSynthetic` one neither explicitly nor implicitly
+/// declared, e.g. added by a compiler for its own purposes.
+#[repr(u16)]
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+pub enum MethodParameterAccessFlags {
+    AccFinal = 0x0010,
+    AccSynthetic = 0x1000,
+    AccMandated = 0x8000,
+}
+
+impl MethodParameterAccessFlags {
+    const VALUES: [Self; 3] = [Self::AccFinal, Self::AccSynthetic, Self::AccMandated];
+}
+
+#[derive(Debug, Serialize)]
+pub struct MethodParameter<S: AsRef<str>> {
+    /// `None` for a formal parameter without a name.
+    name: Option<S>,
+    access_flags: Vec<MethodParameterAccessFlags>,
+}
+
+impl<S: AsRef<str>> MethodParameter<S> {
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_ref().map(AsRef::as_ref)
+    }
+
+    pub fn access_flags(&self) -> &[MethodParameterAccessFlags] {
+        &self.access_flags
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExceptionHandler<S: AsRef<str>> {
+    start_pc: u16,
+    end_pc: u16,
+    handler_pc: u16,
+    /// `None` for handlers catching any exception, e.g. `finally` blocks.
+    catch_type: Option<S>,
+}
+
+impl<S: AsRef<str>> ExceptionHandler<S> {
+    /// Start of the range of code covered, inclusive.
+    pub fn start_pc(&self) -> u16 {
+        self.start_pc
+    }
+
+    /// End of the range of code covered, exclusive.
+    pub fn end_pc(&self) -> u16 {
+        self.end_pc
+    }
+
+    pub fn handler_pc(&self) -> u16 {
+        self.handler_pc
+    }
+
+    /// Internal name of the exception class caught, `None` for any exception.
+    pub fn catch_type(&self) -> Option<&str> {
+        self.catch_type.as_ref().map(AsRef::as_ref)
+    }
+}
+
+#[derive(Serialize)]
+pub struct CodeAttribute<S: AsRef<str>, B: AsRef<[u8]>> {
+    max_stack: u16,
+    max_locals: u16,
+    #[serde(serialize_with = "as_base64")]
+    bytecode: B,
+    exception_table: Vec<ExceptionHandler<S>>,
+    attributes: Attributes<S, B>,
+}
+
+impl<S: AsRef<str> + fmt::Debug, B: AsRef<[u8]> + fmt::Debug> fmt::Debug for CodeAttribute<S, B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CodeAttribute")
+            .field("max_stack", &self.max_stack)
+            .field("max_locals", &self.max_locals)
+            .field("bytecode", &raw::DebugBytes(self.bytecode.as_ref()))
+            .field("exception_table", &self.exception_table)
+            .field("attributes", &self.attributes)
+            .finish()
+    }
+}
+
+impl<S: AsRef<str>, B: AsRef<[u8]>> CodeAttribute<S, B> {
+    pub fn max_stack(&self) -> u16 {
+        self.max_stack
+    }
+
+    pub fn max_locals(&self) -> u16 {
+        self.max_locals
+    }
+
+    pub fn bytecode(&self) -> &[u8] {
+        self.bytecode.as_ref()
+    }
+
+    /// Handlers in file order, which is the order they are tried in.
+    pub fn exception_table(&self) -> &[ExceptionHandler<S>] {
+        &self.exception_table
+    }
+
+    pub fn attributes(&self) -> &Attributes<S, B> {
+        &self.attributes
+    }
+
+    pub fn instructions(&self) -> Result<Vec<Instruction>, ParseError> {
+        instruction::decode(self.bytecode.as_ref())
+    }
+
+    /// The decoded instructions, one line each, e.g. `7: if_icmpge 20` or `12: invokevirtual #4`,
+    /// for diffing methods as text.
+    ///
+    /// Constant pool indexes are shown as `#index`, branch targets as absolute offsets, and
+    /// `tableswitch` and `lookupswitch` on a single line as `{ key: target, ..., default:
+    /// target }`. Each line ends with a newline.
+    pub fn disassembly_text(&self) -> Result<String, ParseError> {
+        Ok(self
+            .instructions()?
+            .iter()
+            .map(|instruction| format!("{instruction}\n"))
+            .collect())
+    }
+
+    /// `(offset, opcode)` of each instruction, without decoding the operands.
+    ///
+    /// Unlike [`CodeAttribute::instructions`], operands are not checked, and iteration stops at
+    /// an illegal opcode or an instruction running past the end of the code.
+    pub fn opcodes(&self) -> impl Iterator<Item = (u32, u8)> + '_ {
+        instruction::opcodes(self.bytecode.as_ref())
+    }
+
+    /// The `LineNumberTable` entries as a map from `start_pc` to line number.
+    ///
+    /// A line applies from its `start_pc` until the next one, so the line of an instruction at
+    /// `pc` is the value of the greatest key not after it, i.e. `map.range(..=pc).next_back()`.
+    /// Of several entries with the same `start_pc`, the last one wins.
+    pub fn line_number_map(&self) -> BTreeMap<u16, u16> {
+        self.attributes
+            .iter()
+            .filter_map(|attribute| match attribute {
+                AttributeInfo::LineNumberTable(entries) => Some(entries),
+                _ => None,
+            })
+            .flatten()
+            .map(|entry| (entry.start_pc, entry.line_number))
+            .collect()
+    }
+
+    /// The frames of the `StackMapTable` attribute, each with its offset in the code, decoded
+    /// from the `offset_delta` of the frames before it.
+    pub fn stack_map_frames(&self) -> impl Iterator<Item = (u32, &StackMapFrame<S>)> + '_ {
+        let mut previous = None;
+        self.attributes
+            .iter()
+            .filter_map(|attribute| match attribute {
+                AttributeInfo::StackMapTable(frames) => Some(frames),
+                _ => None,
+            })
+            .flatten()
+            .map(move |frame| {
+                let offset = match previous {
+                    None => frame.offset_delta() as u32,
+                    Some(previous) => previous + frame.offset_delta() as u32 + 1,
+                };
+                previous = Some(offset);
+                (offset, frame)
+            })
+    }
+
+    /// The name of the local variable in `slot` at the instruction at `pc`, from the
+    /// `LocalVariableTable` attributes.
+    ///
+    /// Slots are reused by variables of disjoint scopes, so the name depends on `pc`. A
+    /// variable is only in scope from the instruction after the one storing its initial value.
+    pub fn local_name_at(&self, slot: u16, pc: u16) -> Option<&str> {
+        self.attributes
+            .iter()
+            .filter_map(|attribute| match attribute {
+                AttributeInfo::LocalVariableTable(entries) => Some(entries),
+                _ => None,
+            })
+            .flatten()
+            .filter(|entry| {
+                let end = entry.start_pc as u32 + entry.length as u32;
+                entry.slot == slot && (entry.start_pc as u32..end).contains(&(pc as u32))
+            })
+            // Scopes are nested or disjoint, so the innermost one starts last.
+            .max_by_key(|entry| entry.start_pc)
+            .map(|entry| entry.name.as_ref())
+    }
+
+    /// Decoded instructions, each with the source line it belongs to, as in
+    /// [`CodeAttribute::line_number_map`].
+    pub fn instructions_with_lines(&self) -> Result<Vec<(Instruction, Option<u16>)>, ParseError> {
+        let lines = self.line_number_map();
+        Ok(self
+            .instructions()?
+            .into_iter()
+            .map(|instruction| {
+                let pc = u16::try_from(instruction.offset()).unwrap_or(u16::MAX);
+                let line = lines.range(..=pc).next_back().map(|(_, line)| *line);
+                (instruction, line)
+            })
+            .collect())
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct LineNumberEntry {
+    start_pc: u16,
+    line_number: u16,
+}
+
+impl LineNumberEntry {
+    /// Offset of the first instruction of the line, see [`CodeAttribute::line_number_map`].
+    pub fn start_pc(&self) -> u16 {
+        self.start_pc
+    }
+
+    pub fn line_number(&self) -> u16 {
+        self.line_number
+    }
+}
+
+/// A local variable is in `slot` from `start_pc` for `length` bytes of bytecode.
+#[derive(Debug, Serialize)]
+pub struct LocalVariable<S: AsRef<str>> {
+    start_pc: u16,
+    length: u16,
+    name: S,
+    descriptor: S,
+    slot: u16,
+}
+
+impl<S: AsRef<str>> LocalVariable<S> {
+    pub fn start_pc(&self) -> u16 {
+        self.start_pc
+    }
+
+    pub fn length(&self) -> u16 {
+        self.length
+    }
+
+    pub fn name(&self) -> &str {
+        self.name.as_ref()
+    }
+
+    /// Field descriptor of the variable, e.g. `Ljava/lang/String;`.
+    pub fn descriptor(&self) -> &str {
+        self.descriptor.as_ref()
+    }
+
+    /// The local variable slot, `index` in the class file; `long` and `double` take two.
+    pub fn slot(&self) -> u16 {
+        self.slot
+    }
+}
+
 #[derive(Debug, Serialize)]
+pub struct RecordComponent<S: AsRef<str>, B: AsRef<[u8]>> {
+    name: S,
+    descriptor: S,
+    attributes: Attributes<S, B>,
+}
+
+impl<S: AsRef<str>, B: AsRef<[u8]>> RecordComponent<S, B> {
+    pub fn name(&self) -> &str {
+        self.name.as_ref()
+    }
+
+    /// Field descriptor of the component, e.g. `Ljava/lang/String;`.
+    pub fn descriptor(&self) -> &str {
+        self.descriptor.as_ref()
+    }
+
+    /// e.g. `Signature` and `RuntimeVisibleAnnotations`.
+    pub fn attributes(&self) -> &Attributes<S, B> {
+        &self.attributes
+    }
+}
+
+/// Serialized externally tagged, or adjacently tagged with the `tagged` feature.
+///
+/// Attributes parsed into their own variant instead of [`AttributeInfo::Unknown`] may be added
+/// in minor releases, so matches need a wildcard arm.
+#[derive(Serialize)]
+#[cfg_attr(feature = "tagged", serde(tag = "tag", content = "value"))]
+#[non_exhaustive]
 pub enum AttributeInfo<S: AsRef<str>, B: AsRef<[u8]>> {
     ConstantValue(ConstantValueAttribute<S>),
-    Code(#[serde(serialize_with = "as_base64")] B),
+    Code(CodeAttribute<S, B>),
     Exceptions(Vec<S>),
     SourceFile(S),
+    Signature(S),
     BootstrapMethods(Vec<BootstrapMethod<S>>),
     InnerClasses(Vec<InnerClass<S>>),
-    Unknown(S, #[serde(serialize_with = "as_base64")] B),
+    LineNumberTable(Vec<LineNumberEntry>),
+    LocalVariableTable(Vec<LocalVariable<S>>),
+    StackMapTable(Vec<StackMapFrame<S>>),
+    RuntimeVisibleAnnotations(Vec<Annotation<S>>),
+    RuntimeInvisibleAnnotations(Vec<Annotation<S>>),
+    RuntimeVisibleTypeAnnotations(Vec<TypeAnnotation<S>>),
+    RuntimeInvisibleTypeAnnotations(Vec<TypeAnnotation<S>>),
+    /// Default value of an element of an annotation interface.
+    AnnotationDefault(ElementValue<S>),
+    MethodParameters(Vec<MethodParameter<S>>),
+    EnclosingMethod {
+        class: S,
+        /// `None` when the class is enclosed by an instance, static or field initializer.
+        method: Option<(S, S)>,
+    },
+    Module(ModuleAttribute<S>),
+    /// The components of a record class, in declaration order.
+    Record(Vec<RecordComponent<S, B>>),
+    /// The class at the top of the nest the class is a member of.
+    NestHost(S),
+    /// The classes of the nest the class is the host of, besides itself.
+    NestMembers(Vec<S>),
+    /// The classes allowed to directly extend or implement a sealed class.
+    PermittedSubclasses(Vec<S>),
+    /// Marks a deprecated class, field or method, without any content.
+    Deprecated,
+    /// Marks a class or member not in the source code, independently of the `ACC_SYNTHETIC`
+    /// flag; without any content.
+    Synthetic,
+    /// `info` is serialized in base64, or as the lines of a hex dump within a [`ClassFile`]
+    /// wrapped with [`ParseOptions::hex_unknown`].
+    #[serde(serialize_with = "serialize_unknown")]
+    Unknown {
+        name: S,
+        length: usize,
+        /// Offset of the attribute from the beginning of the class file.
+        offset: u64,
+        info: B,
+    },
+}
+
+impl<S: AsRef<str> + fmt::Debug, B: AsRef<[u8]> + fmt::Debug> fmt::Debug for AttributeInfo<S, B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ConstantValue(value) => f.debug_tuple("ConstantValue").field(value).finish(),
+            Self::Code(value) => f.debug_tuple("Code").field(value).finish(),
+            Self::Exceptions(value) => f.debug_tuple("Exceptions").field(value).finish(),
+            Self::SourceFile(value) => f.debug_tuple("SourceFile").field(value).finish(),
+            Self::Signature(value) => f.debug_tuple("Signature").field(value).finish(),
+            Self::BootstrapMethods(value) => {
+                f.debug_tuple("BootstrapMethods").field(value).finish()
+            }
+            Self::InnerClasses(value) => f.debug_tuple("InnerClasses").field(value).finish(),
+            Self::LineNumberTable(value) => f.debug_tuple("LineNumberTable").field(value).finish(),
+            Self::LocalVariableTable(value) => {
+                f.debug_tuple("LocalVariableTable").field(value).finish()
+            }
+            Self::StackMapTable(value) => f.debug_tuple("StackMapTable").field(value).finish(),
+            Self::RuntimeVisibleAnnotations(value) => f
+                .debug_tuple("RuntimeVisibleAnnotations")
+                .field(value)
+                .finish(),
+            Self::RuntimeInvisibleAnnotations(value) => f
+                .debug_tuple("RuntimeInvisibleAnnotations")
+                .field(value)
+                .finish(),
+            Self::RuntimeVisibleTypeAnnotations(value) => f
+                .debug_tuple("RuntimeVisibleTypeAnnotations")
+                .field(value)
+                .finish(),
+            Self::RuntimeInvisibleTypeAnnotations(value) => f
+                .debug_tuple("RuntimeInvisibleTypeAnnotations")
+                .field(value)
+                .finish(),
+            Self::AnnotationDefault(value) => {
+                f.debug_tuple("AnnotationDefault").field(value).finish()
+            }
+            Self::MethodParameters(value) => {
+                f.debug_tuple("MethodParameters").field(value).finish()
+            }
+            Self::EnclosingMethod { class, method } => f
+                .debug_struct("EnclosingMethod")
+                .field("class", class)
+                .field("method", method)
+                .finish(),
+            Self::Module(value) => f.debug_tuple("Module").field(value).finish(),
+            Self::Record(value) => f.debug_tuple("Record").field(value).finish(),
+            Self::NestHost(value) => f.debug_tuple("NestHost").field(value).finish(),
+            Self::NestMembers(value) => f.debug_tuple("NestMembers").field(value).finish(),
+            Self::PermittedSubclasses(value) => {
+                f.debug_tuple("PermittedSubclasses").field(value).finish()
+            }
+            Self::Deprecated => f.write_str("Deprecated"),
+            Self::Synthetic => f.write_str("Synthetic"),
+            Self::Unknown {
+                name,
+                length,
+                offset,
+                info,
+            } => f
+                .debug_struct("Unknown")
+                .field("name", name)
+                .field("length", length)
+                .field("offset", offset)
+                .field("info", &raw::DebugBytes(info.as_ref()))
+                .finish(),
+        }
+    }
+}
+
+thread_local! {
+    /// Whether the [`ClassFile`] being serialized was wrapped with
+    /// [`ParseOptions::hex_unknown`], for its unknown attributes however deeply nested.
+    static HEX_UNKNOWN: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Restores [`HEX_UNKNOWN`] once a [`ClassFile`] is serialized, even if serializing panics.
+struct HexUnknownScope(bool);
+
+impl HexUnknownScope {
+    fn enter(hex_unknown: bool) -> Self {
+        Self(HEX_UNKNOWN.replace(hex_unknown))
+    }
+}
+
+impl Drop for HexUnknownScope {
+    fn drop(&mut self) {
+        HEX_UNKNOWN.set(self.0);
+    }
+}
+
+#[derive(Serialize)]
+struct UnknownAttribute<'a> {
+    name: &'a str,
+    length: usize,
+    offset: u64,
+    info: UnknownInfo<'a>,
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+enum UnknownInfo<'a> {
+    Base64(#[serde(serialize_with = "as_base64")] &'a [u8]),
+    HexDump(Vec<String>),
+}
+
+fn serialize_unknown<S: AsRef<str>, B: AsRef<[u8]>, Ser: serde::Serializer>(
+    name: &S,
+    length: &usize,
+    offset: &u64,
+    info: &B,
+    serializer: Ser,
+) -> Result<Ser::Ok, Ser::Error> {
+    let info = info.as_ref();
+    UnknownAttribute {
+        name: name.as_ref(),
+        length: *length,
+        offset: *offset,
+        info: if HEX_UNKNOWN.get() {
+            UnknownInfo::HexDump(hex_dump_lines(info))
+        } else {
+            UnknownInfo::Base64(info)
+        },
+    }
+    .serialize(serializer)
+}
+
+/// 16 bytes per line, e.g. `0000: ca fe ba be  ....`.
+fn hex_dump_lines(bytes: &[u8]) -> Vec<String> {
+    use std::fmt::Write as _;
+
+    bytes
+        .chunks(16)
+        .enumerate()
+        .map(|(i, chunk)| {
+            let mut line = format!("{:04x}:", i * 16);
+            for byte in chunk {
+                write!(line, " {byte:02x}").unwrap();
+            }
+            line.push_str(&"   ".repeat(16 - chunk.len()));
+            line.push_str("  ");
+            line.extend(chunk.iter().map(|&byte| {
+                if byte.is_ascii_graphic() || byte == b' ' {
+                    byte as char
+                } else {
+                    '.'
+                }
+            }));
+            line
+        })
+        .collect()
+}
+
+impl<S: AsRef<str>, B: AsRef<[u8]>> AttributeInfo<S, B> {
+    /// The attribute name as it appears in the constant pool, e.g. `"Code"`.
+    pub fn name(&self) -> &str {
+        match self {
+            Self::ConstantValue(..) => "ConstantValue",
+            Self::Code(..) => "Code",
+            Self::Exceptions(..) => "Exceptions",
+            Self::SourceFile(..) => "SourceFile",
+            Self::Signature(..) => "Signature",
+            Self::BootstrapMethods(..) => "BootstrapMethods",
+            Self::InnerClasses(..) => "InnerClasses",
+            Self::LineNumberTable(..) => "LineNumberTable",
+            Self::LocalVariableTable(..) => "LocalVariableTable",
+            Self::StackMapTable(..) => "StackMapTable",
+            Self::RuntimeVisibleAnnotations(..) => "RuntimeVisibleAnnotations",
+            Self::RuntimeInvisibleAnnotations(..) => "RuntimeInvisibleAnnotations",
+            Self::RuntimeVisibleTypeAnnotations(..) => "RuntimeVisibleTypeAnnotations",
+            Self::RuntimeInvisibleTypeAnnotations(..) => "RuntimeInvisibleTypeAnnotations",
+            Self::AnnotationDefault(..) => "AnnotationDefault",
+            Self::MethodParameters(..) => "MethodParameters",
+            Self::EnclosingMethod { .. } => "EnclosingMethod",
+            Self::Module(..) => "Module",
+            Self::Record(..) => "Record",
+            Self::NestHost(..) => "NestHost",
+            Self::NestMembers(..) => "NestMembers",
+            Self::PermittedSubclasses(..) => "PermittedSubclasses",
+            Self::Deprecated => "Deprecated",
+            Self::Synthetic => "Synthetic",
+            Self::Unknown { name, .. } => name.as_ref(),
+        }
+    }
+}
+
+/// Attributes of a class, field, method or `Code` attribute, in file order.
+#[derive(Debug, Serialize)]
+#[serde(transparent)]
+pub struct Attributes<S: AsRef<str>, B: AsRef<[u8]>>(Vec<AttributeInfo<S, B>>);
+
+impl<S: AsRef<str>, B: AsRef<[u8]>> Attributes<S, B> {
+    /// The first attribute named `name`.
+    pub fn by_name(&self, name: &str) -> Option<&AttributeInfo<S, B>> {
+        self.0.iter().find(|attribute| attribute.name() == name)
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, AttributeInfo<S, B>> {
+        self.0.iter()
+    }
+
+    /// The value of the `Signature` attribute.
+    fn signature(&self) -> Option<&str> {
+        match self.by_name("Signature") {
+            Some(AttributeInfo::Signature(signature)) => Some(signature.as_ref()),
+            _ => None,
+        }
+    }
+
+    fn is_deprecated(&self) -> bool {
+        self.by_name("Deprecated").is_some()
+    }
+
+    fn is_synthetic(&self) -> bool {
+        self.by_name("Synthetic").is_some()
+    }
+
+    /// Entry `index` of the `BootstrapMethods` attribute.
+    fn bootstrap_method(&self, index: u16) -> Result<&BootstrapMethod<S>, ParseError> {
+        let Some(AttributeInfo::BootstrapMethods(bootstrap_methods)) =
+            self.by_name("BootstrapMethods")
+        else {
+            return Err(ParseError::MissingBootstrapMethods);
+        };
+        bootstrap_methods
+            .get(index as usize)
+            .ok_or(ParseError::BootstrapMethodIndexOutOfRange {
+                index,
+                len: bootstrap_methods.len(),
+            })
+    }
+}
+
+impl<S: AsRef<str>, B: AsRef<[u8]>> FromIterator<AttributeInfo<S, B>> for Attributes<S, B> {
+    fn from_iter<I: IntoIterator<Item = AttributeInfo<S, B>>>(iter: I) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl<'a, S: AsRef<str>, B: AsRef<[u8]>> IntoIterator for &'a Attributes<S, B> {
+    type Item = &'a AttributeInfo<S, B>;
+    type IntoIter = std::slice::Iter<'a, AttributeInfo<S, B>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+/// Where an attribute yielded by [`ClassFile::all_attributes`] belongs.
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+pub enum AttributeLocation {
+    Class,
+    /// The field at this index of [`ClassFile::fields`].
+    Field(usize),
+    /// The method at this index of [`ClassFile::methods`].
+    Method(usize),
+    /// The `Code` attribute of the method at this index of [`ClassFile::methods`].
+    Code(usize),
+    /// The record component at this index of [`ClassFile::record_components`].
+    RecordComponent(usize),
 }
 
 #[repr(u16)]
-#[derive(Debug, Serialize, Clone, Copy)]
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
 pub enum FieldAccessFlags {
     AccPublic = 0x0001,
     AccPrivate = 0x0002,
-    AccProcted = 0x0004,
+    AccProtected = 0x0004,
     AccStatic = 0x0008,
     AccFinal = 0x0010,
     AccVolatile = 0x0040,
@@ -191,7 +963,7 @@ impl FieldAccessFlags {
     const VALUES: [Self; 9] = [
         Self::AccPublic,
         Self::AccPrivate,
-        Self::AccProcted,
+        Self::AccProtected,
         Self::AccStatic,
         Self::AccFinal,
         Self::AccVolatile,
@@ -199,6 +971,10 @@ impl FieldAccessFlags {
         Self::AccSynthetic,
         Self::AccEnum,
     ];
+
+    #[deprecated(note = "renamed to AccProtected")]
+    #[allow(non_upper_case_globals)]
+    pub const AccProcted: Self = Self::AccProtected;
 }
 
 #[derive(Debug, Serialize)]
@@ -206,18 +982,18 @@ pub struct FieldInfo<S: AsRef<str>, B: AsRef<[u8]>> {
     access_flags: Vec<FieldAccessFlags>,
     name: S,
     descriptor: S,
-    attributes: Vec<AttributeInfo<S, B>>,
+    attributes: Attributes<S, B>,
 }
 
 #[repr(u16)]
-#[derive(Debug, Serialize, Clone, Copy)]
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
 pub enum MethodAccessFlags {
     AccPublic = 0x0001,
     AccPrivate = 0x0002,
-    AccProcted = 0x0004,
+    AccProtected = 0x0004,
     AccStatic = 0x0008,
     AccFinal = 0x0010,
-    AccSynthronized = 0x0020,
+    AccSynchronized = 0x0020,
     AccBridge = 0x0040,
     AccVarargs = 0x0080,
     AccNative = 0x0100,
@@ -230,10 +1006,10 @@ impl MethodAccessFlags {
     const VALUES: [Self; 12] = [
         Self::AccPublic,
         Self::AccPrivate,
-        Self::AccProcted,
+        Self::AccProtected,
         Self::AccStatic,
         Self::AccFinal,
-        Self::AccSynthronized,
+        Self::AccSynchronized,
         Self::AccBridge,
         Self::AccVarargs,
         Self::AccNative,
@@ -241,6 +1017,14 @@ impl MethodAccessFlags {
         Self::AccStrict,
         Self::AccSynthetic,
     ];
+
+    #[deprecated(note = "renamed to AccProtected")]
+    #[allow(non_upper_case_globals)]
+    pub const AccProcted: Self = Self::AccProtected;
+
+    #[deprecated(note = "renamed to AccSynchronized")]
+    #[allow(non_upper_case_globals)]
+    pub const AccSynthronized: Self = Self::AccSynchronized;
 }
 
 #[derive(Debug, Serialize)]
@@ -248,66 +1032,1315 @@ pub struct MethodInfo<S: AsRef<str>, B: AsRef<[u8]>> {
     access_flags: Vec<MethodAccessFlags>,
     name: S,
     descriptor: S,
-    attributes: Vec<AttributeInfo<S, B>>,
+    attributes: Attributes<S, B>,
 }
 
-#[repr(u16)]
-#[derive(Debug, Serialize, Clone, Copy)]
-pub enum ClassAccessFlags {
-    AccPublic = 0x0001,
-    AccFinal = 0x0010,
-    AccSuper = 0x0020,
-    AccInterface = 0x0200,
-    AccAbstract = 0x0400,
-    AccSynthetic = 0x1000,
-    AccAnnotation = 0x2000,
-    AccEnum = 0x4000,
-    AccModule = 0x8000,
-}
+impl<S: AsRef<str>, B: AsRef<[u8]>> FieldInfo<S, B> {
+    pub fn name(&self) -> &str {
+        self.name.as_ref()
+    }
 
-impl ClassAccessFlags {
-    const VALUES: [Self; 9] = [
-        Self::AccPublic,
-        Self::AccFinal,
-        Self::AccSuper,
-        Self::AccInterface,
-        Self::AccAbstract,
-        Self::AccSynthetic,
-        Self::AccAnnotation,
-        Self::AccEnum,
-        Self::AccModule,
-    ];
-}
+    pub fn descriptor(&self) -> &str {
+        self.descriptor.as_ref()
+    }
 
-#[derive(Debug, Serialize)]
-pub struct ClassFile<S: AsRef<str>, B: AsRef<[u8]>> {
-    magic: Magic,
-    version: ClassFileVersion,
-    constant_pool: Vec<Option<CpInfo<S>>>,
-    access_flags: Vec<ClassAccessFlags>,
-    this_class: S,
-    super_class: Option<S>,
-    interfaces: Vec<S>,
-    fields: Vec<FieldInfo<S, B>>,
-    methods: Vec<MethodInfo<S, B>>,
-    attributes: Vec<AttributeInfo<S, B>>,
-}
+    pub fn attributes(&self) -> &Attributes<S, B> {
+        &self.attributes
+    }
 
-fn as_base64<T: AsRef<[u8]>, S: serde::Serializer>(
-    val: &T,
-    serializer: S,
-) -> Result<S::Ok, S::Error> {
-    serializer.serialize_str(&base64::engine::general_purpose::STANDARD.encode(val.as_ref()))
-}
+    /// The type of the field, parsed from its descriptor.
+    pub fn field_type(&self) -> Result<FieldType, DescriptorError> {
+        parse_field_descriptor(self.descriptor.as_ref())
+    }
 
-fn parse_cp_info<'a>(
-    pool: &'a [Option<raw::CpInfo>],
-    item: &'a Option<raw::CpInfo>,
-) -> Result<Option<CpInfo<&'a str>>, ParseError> {
-    let Some(item) = item else { return Ok(None) };
+    fn has_flag(&self, flag: FieldAccessFlags) -> bool {
+        self.access_flags.contains(&flag)
+    }
 
+    pub fn is_public(&self) -> bool {
+        self.has_flag(FieldAccessFlags::AccPublic)
+    }
+
+    pub fn is_private(&self) -> bool {
+        self.has_flag(FieldAccessFlags::AccPrivate)
+    }
+
+    pub fn is_protected(&self) -> bool {
+        self.has_flag(FieldAccessFlags::AccProtected)
+    }
+
+    pub fn is_static(&self) -> bool {
+        self.has_flag(FieldAccessFlags::AccStatic)
+    }
+
+    /// Whether `ACC_FINAL` is set, which excludes [`Self::is_volatile`].
+    pub fn is_final(&self) -> bool {
+        self.has_flag(FieldAccessFlags::AccFinal)
+    }
+
+    pub fn is_volatile(&self) -> bool {
+        self.has_flag(FieldAccessFlags::AccVolatile)
+    }
+
+    pub fn is_transient(&self) -> bool {
+        self.has_flag(FieldAccessFlags::AccTransient)
+    }
+
+    /// Whether `ACC_SYNTHETIC` is set, e.g. for `this$0` of inner classes; the `Synthetic`
+    /// attribute is independent, see [`Self::is_synthetic_attribute`].
+    pub fn is_synthetic(&self) -> bool {
+        self.has_flag(FieldAccessFlags::AccSynthetic)
+    }
+
+    /// Whether the field holds a constant of an enum class, `ACC_ENUM`.
+    pub fn is_enum_constant(&self) -> bool {
+        self.has_flag(FieldAccessFlags::AccEnum)
+    }
+
+    /// The generic type of the field, e.g. `Ljava/util/List<Ljava/lang/String;>;`, present
+    /// only when it is not the same as the descriptor.
+    pub fn signature(&self) -> Option<&str> {
+        self.attributes.signature()
+    }
+
+    /// Whether the `Deprecated` attribute is present, as for `@Deprecated`.
+    pub fn is_deprecated(&self) -> bool {
+        self.attributes.is_deprecated()
+    }
+
+    /// Whether the `Synthetic` attribute is present; the `ACC_SYNTHETIC` flag is independent.
+    pub fn is_synthetic_attribute(&self) -> bool {
+        self.attributes.is_synthetic()
+    }
+}
+
+impl<S: AsRef<str>, B: AsRef<[u8]>> MethodInfo<S, B> {
+    pub fn name(&self) -> &str {
+        self.name.as_ref()
+    }
+
+    pub fn descriptor(&self) -> &str {
+        self.descriptor.as_ref()
+    }
+
+    pub fn attributes(&self) -> &Attributes<S, B> {
+        &self.attributes
+    }
+
+    /// The generic signature of the method, e.g. `<T:Ljava/lang/Object;>(TT;)V`, present only
+    /// when the method uses type variables or parameterized types.
+    pub fn signature(&self) -> Option<&str> {
+        self.attributes.signature()
+    }
+
+    /// Whether the `Deprecated` attribute is present, as for `@Deprecated`.
+    pub fn is_deprecated(&self) -> bool {
+        self.attributes.is_deprecated()
+    }
+
+    /// Whether the `Synthetic` attribute is present; the `ACC_SYNTHETIC` flag is independent.
+    pub fn is_synthetic_attribute(&self) -> bool {
+        self.attributes.is_synthetic()
+    }
+
+    /// The default value of an element of an annotation interface, from the
+    /// `AnnotationDefault` attribute.
+    pub fn annotation_default(&self) -> Option<&ElementValue<S>> {
+        match self.attributes.by_name("AnnotationDefault") {
+            Some(AttributeInfo::AnnotationDefault(value)) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// The `Code` attribute, absent for `abstract` and `native` methods.
+    pub fn code(&self) -> Option<&CodeAttribute<S, B>> {
+        match self.attributes.by_name("Code") {
+            Some(AttributeInfo::Code(code)) => Some(code),
+            _ => None,
+        }
+    }
+
+    /// The declaration in Java source form, e.g.
+    /// `public static <T> java.util.List<T> of(T... arg0) throws java.io.IOException`.
+    ///
+    /// Constructors and the static initializer keep their `<init>` and `<clinit>` names, see
+    /// [`ClassFile::java_signature`].
+    pub fn java_signature(&self) -> String {
+        text::java_signature(self, None)
+    }
+
+    /// The name of the local variable in `slot` at the instruction at `pc`, as in
+    /// [`CodeAttribute::local_name_at`].
+    ///
+    /// Slot 0 of instance methods and constructors holds `this`, which is not always listed in
+    /// `LocalVariableTable`, and never without `-g`, so it is named `this` when not listed.
+    pub fn local_name_at(&self, slot: u16, pc: u16) -> Option<&str> {
+        let code = self.code()?;
+        code.local_name_at(slot, pc).or_else(|| {
+            let in_code = (pc as usize) < code.bytecode.as_ref().len();
+            (slot == 0 && !self.is_static() && in_code).then_some("this")
+        })
+    }
+
+    /// Whether this is a `default` method of an interface, i.e. an instance method with code
+    /// declared by an interface, as told by `owner_is_interface`.
+    ///
+    /// Interfaces may also declare `static` methods and, since Java 9, `private` ones, both with
+    /// code too, which are not `default`.
+    pub fn is_default_method(&self, owner_is_interface: bool) -> bool {
+        owner_is_interface && !self.is_static() && !self.is_abstract() && !self.is_private()
+    }
+
+    fn has_flag(&self, flag: MethodAccessFlags) -> bool {
+        self.access_flags.contains(&flag)
+    }
+
+    pub fn is_public(&self) -> bool {
+        self.has_flag(MethodAccessFlags::AccPublic)
+    }
+
+    pub fn is_private(&self) -> bool {
+        self.has_flag(MethodAccessFlags::AccPrivate)
+    }
+
+    pub fn is_protected(&self) -> bool {
+        self.has_flag(MethodAccessFlags::AccProtected)
+    }
+
+    pub fn is_static(&self) -> bool {
+        self.has_flag(MethodAccessFlags::AccStatic)
+    }
+
+    pub fn is_final(&self) -> bool {
+        self.has_flag(MethodAccessFlags::AccFinal)
+    }
+
+    pub fn is_synchronized(&self) -> bool {
+        self.has_flag(MethodAccessFlags::AccSynchronized)
+    }
+
+    /// Whether this is a bridge method generated by the compiler, e.g. for a covariant return
+    /// type or an erased generic parameter.
+    pub fn is_bridge(&self) -> bool {
+        self.has_flag(MethodAccessFlags::AccBridge)
+    }
+
+    /// Whether the last parameter is variable arity, `T...`.
+    pub fn is_varargs(&self) -> bool {
+        self.has_flag(MethodAccessFlags::AccVarargs)
+    }
+
+    pub fn is_native(&self) -> bool {
+        self.has_flag(MethodAccessFlags::AccNative)
+    }
+
+    /// Whether `ACC_ABSTRACT` is set, as for interface methods without code.
+    pub fn is_abstract(&self) -> bool {
+        self.has_flag(MethodAccessFlags::AccAbstract)
+    }
+
+    /// Whether `ACC_STRICT` is set, only meaningful for major versions 46 to 60, as floating
+    /// point is always strict since Java SE 17.
+    pub fn is_strict(&self) -> bool {
+        self.has_flag(MethodAccessFlags::AccStrict)
+    }
+
+    /// Whether `ACC_SYNTHETIC` is set, e.g. for lambda bodies and bridge methods; the
+    /// `Synthetic` attribute is independent, see [`Self::is_synthetic_attribute`].
+    pub fn is_synthetic(&self) -> bool {
+        self.has_flag(MethodAccessFlags::AccSynthetic)
+    }
+
+    /// Whether this is an instance initialization method, `<init>`.
+    pub fn is_constructor(&self) -> bool {
+        self.name.as_ref() == "<init>"
+    }
+
+    /// Whether this is the class initialization method, `<clinit>`, of the static initializers.
+    pub fn is_class_initializer(&self) -> bool {
+        self.name.as_ref() == "<clinit>"
+    }
+
+    /// `(max_stack, max_locals, code_length)` of the `Code` attribute.
+    pub fn code_metrics(&self) -> Option<(u16, u16, u32)> {
+        self.code().map(|code| {
+            (
+                code.max_stack,
+                code.max_locals,
+                code.bytecode.as_ref().len() as u32,
+            )
+        })
+    }
+}
+
+#[repr(u16)]
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+pub enum ClassAccessFlags {
+    AccPublic = 0x0001,
+    AccFinal = 0x0010,
+    AccSuper = 0x0020,
+    AccInterface = 0x0200,
+    AccAbstract = 0x0400,
+    AccSynthetic = 0x1000,
+    AccAnnotation = 0x2000,
+    AccEnum = 0x4000,
+    AccModule = 0x8000,
+}
+
+impl ClassAccessFlags {
+    const VALUES: [Self; 9] = [
+        Self::AccPublic,
+        Self::AccFinal,
+        Self::AccSuper,
+        Self::AccInterface,
+        Self::AccAbstract,
+        Self::AccSynthetic,
+        Self::AccAnnotation,
+        Self::AccEnum,
+        Self::AccModule,
+    ];
+}
+
+/// Serialized as laid out in the class file. [`raw::ClassFile::compact_pool`] leaves out the
+/// constants nothing refers to and the duplicates beforehand.
+#[derive(Debug)]
+struct ConstantPool<S: AsRef<str>> {
+    entries: Vec<Option<CpInfo<S>>>,
+    /// Set by [`ParseOptions::parse_descriptors`].
+    parse_descriptors: bool,
+}
+
+impl<S: AsRef<str>> ConstantPool<S> {
+    fn get(&self, index: usize) -> Option<&Option<CpInfo<S>>> {
+        self.entries.get(index)
+    }
+
+    fn iter(&self) -> std::slice::Iter<'_, Option<CpInfo<S>>> {
+        self.entries.iter()
+    }
+
+    /// The error for a constant at `index` which is not `expected`, or is out of range.
+    fn mismatch(&self, index: u16, expected: &'static str) -> ParseError {
+        if index as usize >= self.entries.len() {
+            ParseError::InvalidCpIndex {
+                index,
+                pool_size: self.entries.len(),
+            }
+        } else {
+            ParseError::UnexpectedCpType { index, expected }
+        }
+    }
+}
+
+impl<S: AsRef<str> + Serialize> Serialize for ConstantPool<S> {
+    fn serialize<T: serde::Serializer>(&self, serializer: T) -> Result<T::Ok, T::Error> {
+        use serde::ser::SerializeSeq as _;
+
+        if !self.parse_descriptors {
+            return self.entries.serialize(serializer);
+        }
+
+        let mut seq = serializer.serialize_seq(Some(self.entries.len()))?;
+        for entry in &self.entries {
+            match entry.as_ref().and_then(with_parsed_descriptor) {
+                Some(parsed) => seq.serialize_element(&parsed)?,
+                None => seq.serialize_element(entry)?,
+            }
+        }
+        seq.end()
+    }
+}
+
+/// A string of a constant, serialized as the parsed form of the descriptor it may be.
+#[derive(Debug)]
+enum ParsedDescriptor<'a> {
+    Field(&'a str, FieldType),
+    Method(&'a str, MethodDescriptor),
+    Plain(&'a str),
+}
+
+impl AsRef<str> for ParsedDescriptor<'_> {
+    fn as_ref(&self) -> &str {
+        match self {
+            Self::Field(descriptor, _) | Self::Method(descriptor, _) | Self::Plain(descriptor) => {
+                descriptor
+            }
+        }
+    }
+}
+
+impl Serialize for ParsedDescriptor<'_> {
+    fn serialize<T: serde::Serializer>(&self, serializer: T) -> Result<T::Ok, T::Error> {
+        match self {
+            Self::Field(_, field_type) => field_type.serialize(serializer),
+            Self::Method(_, method_descriptor) => method_descriptor.serialize(serializer),
+            Self::Plain(string) => string.serialize(serializer),
+        }
+    }
+}
+
+impl<'a> ParsedDescriptor<'a> {
+    fn field(descriptor: &'a str) -> Option<Self> {
+        let field_type = parse_field_descriptor(descriptor).ok()?;
+        Some(Self::Field(descriptor, field_type))
+    }
+
+    fn method(descriptor: &'a str) -> Option<Self> {
+        let method_descriptor = parse_method_descriptor(descriptor).ok()?;
+        Some(Self::Method(descriptor, method_descriptor))
+    }
+}
+
+/// `entry` with its descriptor replaced by the parsed [`FieldType`] or [`MethodDescriptor`],
+/// if it has one which parses.
+fn with_parsed_descriptor<S: AsRef<str>>(
+    entry: &CpInfo<S>,
+) -> Option<CpInfo<ParsedDescriptor<'_>>> {
+    Some(match entry {
+        CpInfo::Fieldref {
+            class,
+            name,
+            descriptor,
+        } => CpInfo::Fieldref {
+            class: ParsedDescriptor::Plain(class.as_ref()),
+            name: ParsedDescriptor::Plain(name.as_ref()),
+            descriptor: ParsedDescriptor::field(descriptor.as_ref())?,
+        },
+        CpInfo::Methodref {
+            class,
+            name,
+            descriptor,
+        } => CpInfo::Methodref {
+            class: ParsedDescriptor::Plain(class.as_ref()),
+            name: ParsedDescriptor::Plain(name.as_ref()),
+            descriptor: ParsedDescriptor::method(descriptor.as_ref())?,
+        },
+        CpInfo::InterfaceMethodref {
+            class,
+            name,
+            descriptor,
+        } => CpInfo::InterfaceMethodref {
+            class: ParsedDescriptor::Plain(class.as_ref()),
+            name: ParsedDescriptor::Plain(name.as_ref()),
+            descriptor: ParsedDescriptor::method(descriptor.as_ref())?,
+        },
+        CpInfo::NameAndType { name, descriptor } => CpInfo::NameAndType {
+            name: ParsedDescriptor::Plain(name.as_ref()),
+            descriptor: if descriptor.as_ref().starts_with('(') {
+                ParsedDescriptor::method(descriptor.as_ref())?
+            } else {
+                ParsedDescriptor::field(descriptor.as_ref())?
+            },
+        },
+        _ => return None,
+    })
+}
+
+#[derive(Debug)]
+pub struct ClassFile<S: AsRef<str>, B: AsRef<[u8]>> {
+    /// `None` with [`ParseOptions::omit_magic`].
+    magic: Option<Magic>,
+    version: ClassFileVersion,
+    constant_pool: ConstantPool<S>,
+    access_flags: Vec<ClassAccessFlags>,
+    this_class: S,
+    super_class: Option<S>,
+    interfaces: Vec<S>,
+    fields: Vec<FieldInfo<S, B>>,
+    methods: Vec<MethodInfo<S, B>>,
+    attributes: Attributes<S, B>,
+    /// Set by [`ParseOptions::hex_unknown`].
+    hex_unknown: bool,
+}
+
+impl<S: AsRef<str> + Serialize, B: AsRef<[u8]> + Serialize> Serialize for ClassFile<S, B> {
+    fn serialize<T: serde::Serializer>(&self, serializer: T) -> Result<T::Ok, T::Error> {
+        use serde::ser::SerializeStruct as _;
+
+        let _scope = HexUnknownScope::enter(self.hex_unknown);
+        let mut state = serializer.serialize_struct("ClassFile", 10)?;
+        match &self.magic {
+            Some(magic) => state.serialize_field("magic", magic)?,
+            None => state.skip_field("magic")?,
+        }
+        state.serialize_field("version", &self.version)?;
+        state.serialize_field("constant_pool", &self.constant_pool)?;
+        state.serialize_field("access_flags", &self.access_flags)?;
+        state.serialize_field("this_class", &self.this_class)?;
+        state.serialize_field("super_class", &self.super_class)?;
+        state.serialize_field("interfaces", &self.interfaces)?;
+        state.serialize_field("fields", &self.fields)?;
+        state.serialize_field("methods", &self.methods)?;
+        state.serialize_field("attributes", &self.attributes)?;
+        state.end()
+    }
+}
+
+/// A [`ClassFile`] owning its strings and bytes, see [`ClassFile::into_owned`].
+pub type OwnedClassFile = ClassFile<String, Vec<u8>>;
+
+/// A declared method and the [`Linkage`] of the method it overrides.
+pub type Overriding<'a, S, B> = (&'a MethodInfo<S, B>, Linkage);
+
+impl<S: AsRef<str>, B: AsRef<[u8]>> ClassFile<S, B> {
+    /// Internal name of the class, e.g. `com/example/Main`.
+    pub fn this_class(&self) -> &str {
+        self.this_class.as_ref()
+    }
+
+    /// The class file with every string and byte slice copied, so that it no longer borrows
+    /// from the `raw::ClassFile` it was wrapped from and can be returned, stored or sent to
+    /// another thread on its own.
+    pub fn into_owned(self) -> OwnedClassFile {
+        owned::IntoOwned::into_owned(self)
+    }
+
+    pub fn version(&self) -> &ClassFileVersion {
+        &self.version
+    }
+
+    /// Number of constants in the constant pool, which is less than `constant_pool_count` by
+    /// index 0 and the unusable slots after `CONSTANT_Long` and `CONSTANT_Double`.
+    pub fn real_constant_count(&self) -> usize {
+        self.constant_pool
+            .iter()
+            .filter(|entry| entry.is_some())
+            .count()
+    }
+
+    /// Whether this is a `package-info` class, which holds the annotations of its package.
+    pub fn is_package_info(&self) -> bool {
+        let this_class = self.this_class.as_ref();
+        this_class == "package-info" || this_class.ends_with("/package-info")
+    }
+
+    fn has_flag(&self, flag: ClassAccessFlags) -> bool {
+        self.access_flags.contains(&flag)
+    }
+
+    /// Whether `ACC_INTERFACE` is set, as it is for annotation interfaces too.
+    pub fn is_interface(&self) -> bool {
+        self.has_flag(ClassAccessFlags::AccInterface)
+    }
+
+    /// Whether `ACC_ABSTRACT` is set, as it always is for interfaces.
+    pub fn is_abstract(&self) -> bool {
+        self.has_flag(ClassAccessFlags::AccAbstract)
+    }
+
+    pub fn is_final(&self) -> bool {
+        self.has_flag(ClassAccessFlags::AccFinal)
+    }
+
+    /// Whether the class is an enum class, or the anonymous class of an enum constant with a
+    /// body.
+    pub fn is_enum(&self) -> bool {
+        self.has_flag(ClassAccessFlags::AccEnum)
+    }
+
+    /// Whether `ACC_ANNOTATION` is set, which implies [`Self::is_interface`].
+    pub fn is_annotation(&self) -> bool {
+        self.has_flag(ClassAccessFlags::AccAnnotation)
+    }
+
+    /// Whether this is a `module-info` class, with no other flag set.
+    pub fn is_module(&self) -> bool {
+        self.has_flag(ClassAccessFlags::AccModule)
+    }
+
+    /// Whether `ACC_SYNTHETIC` is set; the `Synthetic` attribute is independent, see
+    /// [`Self::is_synthetic_attribute`].
+    pub fn is_synthetic(&self) -> bool {
+        self.has_flag(ClassAccessFlags::AccSynthetic)
+    }
+
+    /// Whether `ACC_PUBLIC` is set. Nested classes declared `private` or `protected` are
+    /// `public` or package access here, their declared access being in `InnerClasses`.
+    pub fn is_public(&self) -> bool {
+        self.has_flag(ClassAccessFlags::AccPublic)
+    }
+
+    /// The generic signature of the class, e.g.
+    /// `<T:Ljava/lang/Object;>Ljava/lang/Object;Ljava/util/List<TT;>;`, present only when the
+    /// class is generic or extends or implements parameterized types.
+    pub fn signature(&self) -> Option<&str> {
+        self.attributes.signature()
+    }
+
+    /// Whether the `Deprecated` attribute is present, as for `@Deprecated`.
+    pub fn is_deprecated(&self) -> bool {
+        self.attributes.is_deprecated()
+    }
+
+    /// Whether the `Synthetic` attribute is present; the `ACC_SYNTHETIC` flag is independent.
+    pub fn is_synthetic_attribute(&self) -> bool {
+        self.attributes.is_synthetic()
+    }
+
+    /// The class immediately enclosing a local or anonymous class, from the `EnclosingMethod`
+    /// attribute.
+    pub fn enclosing_class(&self) -> Option<&str> {
+        match self.attributes.by_name("EnclosingMethod") {
+            Some(AttributeInfo::EnclosingMethod { class, .. }) => Some(class.as_ref()),
+            _ => None,
+        }
+    }
+
+    /// `(name, descriptor)` of the method immediately enclosing a local or anonymous class,
+    /// `None` also when it is enclosed by an initializer instead, see [`Self::enclosing_class`].
+    pub fn enclosing_method(&self) -> Option<(&str, &str)> {
+        match self.attributes.by_name("EnclosingMethod") {
+            Some(AttributeInfo::EnclosingMethod {
+                method: Some((name, descriptor)),
+                ..
+            }) => Some((name.as_ref(), descriptor.as_ref())),
+            _ => None,
+        }
+    }
+
+    /// Whether the class is a record class, having the `Record` attribute.
+    pub fn is_record(&self) -> bool {
+        self.record_components().is_some()
+    }
+
+    /// The components of a record class, from the `Record` attribute.
+    pub fn record_components(&self) -> Option<&[RecordComponent<S, B>]> {
+        match self.attributes.by_name("Record") {
+            Some(AttributeInfo::Record(components)) => Some(components),
+            _ => None,
+        }
+    }
+
+    /// Whether the class is sealed, having the `PermittedSubclasses` attribute.
+    pub fn is_sealed(&self) -> bool {
+        self.permitted_subclasses().is_some()
+    }
+
+    /// The classes allowed to directly extend or implement a sealed class, from the
+    /// `PermittedSubclasses` attribute.
+    pub fn permitted_subclasses(&self) -> Option<&[S]> {
+        match self.attributes.by_name("PermittedSubclasses") {
+            Some(AttributeInfo::PermittedSubclasses(classes)) => Some(classes),
+            _ => None,
+        }
+    }
+
+    /// The host of the nest the class is a member of, from the `NestHost` attribute.
+    pub fn nest_host(&self) -> Option<&str> {
+        match self.attributes.by_name("NestHost") {
+            Some(AttributeInfo::NestHost(host)) => Some(host.as_ref()),
+            _ => None,
+        }
+    }
+
+    /// The other members of the nest the class is the host of, from the `NestMembers`
+    /// attribute.
+    pub fn nest_members(&self) -> Option<&[S]> {
+        match self.attributes.by_name("NestMembers") {
+            Some(AttributeInfo::NestMembers(members)) => Some(members),
+            _ => None,
+        }
+    }
+
+    /// The value of the `SourceFile` attribute, e.g. `Main.java`.
+    pub fn source_file(&self) -> Option<&str> {
+        match self.attributes.by_name("SourceFile") {
+            Some(AttributeInfo::SourceFile(source_file)) => Some(source_file.as_ref()),
+            _ => None,
+        }
+    }
+
+    /// [`Self::source_file`], or when the attribute has been stripped, the file javac would have
+    /// compiled the class from: the one named after the top-level class enclosing it, e.g.
+    /// `Outer.java` for `com/example/Outer$Inner$1`, as split by [`split_nested_name`].
+    ///
+    /// Top-level classes declared in a file of another name cannot be told from the others.
+    pub fn source_file_or_synthesized(&self) -> Cow<'_, str> {
+        if let Some(source_file) = self.source_file() {
+            return Cow::Borrowed(source_file);
+        }
+        let mut name = self.this_class.as_ref();
+        while let (Some(enclosing), _) = split_nested_name(name) {
+            name = enclosing;
+        }
+        let (_, top_level) = split_nested_name(name);
+        Cow::Owned(format!("{top_level}.java"))
+    }
+
+    pub fn fields(&self) -> &[FieldInfo<S, B>] {
+        &self.fields
+    }
+
+    pub fn methods(&self) -> &[MethodInfo<S, B>] {
+        &self.methods
+    }
+
+    /// The declaration of `method`, one of [`ClassFile::methods`], in Java source form as in
+    /// [`MethodInfo::java_signature`], but with constructors named after the class, e.g.
+    /// `public Main()`, and the static initializer as `static {}`.
+    pub fn java_signature(&self, method: &MethodInfo<S, B>) -> String {
+        text::java_signature(method, Some(&text::constructor_name(self)))
+    }
+
+    /// The method declared with `name` and, if given, `descriptor`; without a descriptor, the
+    /// first of the overloads in file order, see [`Self::find_methods`].
+    pub fn find_method(&self, name: &str, descriptor: Option<&str>) -> Option<&MethodInfo<S, B>> {
+        self.methods.iter().find(|method| {
+            method.name.as_ref() == name
+                && descriptor.is_none_or(|descriptor| method.descriptor.as_ref() == descriptor)
+        })
+    }
+
+    /// The methods declared with `name`, overloads and bridge methods alike, in file order.
+    pub fn find_methods(&self, name: &str) -> Vec<&MethodInfo<S, B>> {
+        self.methods
+            .iter()
+            .filter(|method| method.name.as_ref() == name)
+            .collect()
+    }
+
+    pub fn attributes(&self) -> &Attributes<S, B> {
+        &self.attributes
+    }
+
+    /// Decode the instructions of `code`, a `Code` attribute of this class, linking
+    /// `invokedynamic` instructions, and `ldc` instructions loading a `CONSTANT_Dynamic`, to
+    /// their bootstrap method.
+    pub fn disassemble(
+        &self,
+        code: &CodeAttribute<S, B>,
+    ) -> Result<Vec<ResolvedInstruction<'_, S>>, ParseError> {
+        code.instructions()?
+            .into_iter()
+            .map(|instruction| {
+                let call_site = match instruction.operation() {
+                    Operation::Invokedynamic(index) => Some(self.call_site(*index)?),
+                    Operation::Ldc(..) | Operation::LdcW(..) | Operation::Ldc2W(..) => {
+                        self.ldc_call_site(instruction.operation())?
+                    }
+                    _ => None,
+                };
+                Ok(ResolvedInstruction {
+                    instruction,
+                    call_site,
+                    linkage: None,
+                })
+            })
+            .collect()
+    }
+
+    /// [`Self::disassemble`], also checking that the methods invoked by `invokevirtual`,
+    /// `invokespecial` and `invokestatic` exist in the classes on the class path of `loader`.
+    pub fn disassemble_with_loader(
+        &self,
+        code: &CodeAttribute<S, B>,
+        loader: &ClassLoader,
+    ) -> Result<Vec<ResolvedInstruction<'_, S>>, ParseError> {
+        let mut resolved = HashMap::<u16, Linkage>::new();
+        let mut instructions = self.disassemble(code)?;
+        for instruction in &mut instructions {
+            let (Operation::Invokevirtual(index)
+            | Operation::Invokespecial(index)
+            | Operation::Invokestatic(index)) = instruction.instruction.operation()
+            else {
+                continue;
+            };
+            let Some(Some(
+                CpInfo::Methodref {
+                    class,
+                    name,
+                    descriptor,
+                }
+                | CpInfo::InterfaceMethodref {
+                    class,
+                    name,
+                    descriptor,
+                },
+            )) = self.constant_pool.get(*index as usize)
+            else {
+                return Err(self
+                    .constant_pool
+                    .mismatch(*index, "CONSTANT_Methodref or CONSTANT_InterfaceMethodref"));
+            };
+
+            let linkage = match resolved.get(index) {
+                Some(linkage) => linkage.clone(),
+                None => {
+                    let linkage = loader.resolve_method(
+                        class.as_ref(),
+                        name.as_ref(),
+                        descriptor.as_ref(),
+                    )?;
+                    resolved.insert(*index, linkage.clone());
+                    linkage
+                }
+            };
+            instruction.linkage = Some(linkage);
+        }
+        Ok(instructions)
+    }
+
+    /// The declared methods which override a method of a supertype, each with the [`Linkage`]
+    /// of the overridden method, as found by [`ClassLoader::resolve_method`] from the
+    /// superclass, then from each direct superinterface.
+    ///
+    /// Constructors and static and private methods do not override, nor are they overridden;
+    /// package access methods are only overridden from the same package. Methods for which no
+    /// overridden method is found are left out, unless a supertype is not on the class path,
+    /// in which case they come with [`Linkage::Unknown`].
+    pub fn overriding_methods(
+        &self,
+        loader: &ClassLoader,
+    ) -> Result<Vec<Overriding<'_, S, B>>, ParseError> {
+        fn package(class: &str) -> &str {
+            class.rsplit_once('/').map_or("", |(package, _)| package)
+        }
+        let overridable = |class: &str, access_flags: &[MethodAccessFlags]| {
+            let has = |flag| access_flags.contains(&flag);
+            !has(MethodAccessFlags::AccStatic)
+                && !has(MethodAccessFlags::AccPrivate)
+                && (has(MethodAccessFlags::AccPublic)
+                    || has(MethodAccessFlags::AccProtected)
+                    || package(class) == package(self.this_class.as_ref()))
+        };
+
+        let mut overriding = vec![];
+        for method in &self.methods {
+            let name = method.name.as_ref();
+            if name.starts_with('<') || !overridable(self.this_class.as_ref(), &method.access_flags)
+            {
+                continue;
+            }
+
+            let mut overridden = None;
+            let supertypes = self.super_class.iter().chain(&self.interfaces);
+            for supertype in supertypes {
+                let linkage =
+                    loader.resolve_method(supertype.as_ref(), name, method.descriptor.as_ref())?;
+                match &linkage {
+                    Linkage::Found {
+                        class,
+                        access_flags,
+                    } if overridable(class, access_flags) => {
+                        overridden = Some(linkage);
+                        break;
+                    }
+                    Linkage::Unknown { .. } if overridden.is_none() => overridden = Some(linkage),
+                    _ => {}
+                }
+            }
+            if let Some(linkage) = overridden {
+                overriding.push((method, linkage));
+            }
+        }
+        Ok(overriding)
+    }
+
+    /// The call site of a `CONSTANT_InvokeDynamic` or `CONSTANT_Dynamic`.
+    fn call_site(&self, index: u16) -> Result<CallSite<'_, S>, ParseError> {
+        let Some(Some(
+            CpInfo::InvokeDynamic {
+                bootstrap_method_attr,
+                name,
+                descriptor,
+            }
+            | CpInfo::Dynamic {
+                bootstrap_method_attr,
+                name,
+                descriptor,
+            },
+        )) = self.constant_pool.get(index as usize)
+        else {
+            return Err(self
+                .constant_pool
+                .mismatch(index, "CONSTANT_InvokeDynamic or CONSTANT_Dynamic"));
+        };
+
+        let bootstrap_method = self.attributes.bootstrap_method(*bootstrap_method_attr)?;
+
+        Ok(CallSite {
+            bootstrap_method,
+            name,
+            descriptor,
+        })
+    }
+
+    /// The call site of the constant loaded by an `ldc` family instruction when it is
+    /// dynamically-computed, checking that the constant is loadable by the instruction:
+    /// `ldc2_w` loads `long` and `double` values, and `ldc` and `ldc_w` the others.
+    fn ldc_call_site(&self, operation: &Operation) -> Result<Option<CallSite<'_, S>>, ParseError> {
+        let Some(index) = operation.constant_index() else {
+            return Ok(None);
+        };
+        let wide = matches!(operation, Operation::Ldc2W(..));
+        match self.constant_pool.get(index as usize) {
+            Some(Some(CpInfo::Long(..) | CpInfo::Double(..))) if wide => Ok(None),
+            Some(Some(
+                CpInfo::Integer(..)
+                | CpInfo::Float(..)
+                | CpInfo::String { .. }
+                | CpInfo::Class { .. }
+                | CpInfo::MethodHandle { .. }
+                | CpInfo::MethodType { .. },
+            )) if !wide => Ok(None),
+            Some(Some(CpInfo::Dynamic { descriptor, .. }))
+                if matches!(descriptor.as_ref(), "J" | "D") == wide =>
+            {
+                Ok(Some(self.call_site(index)?))
+            }
+            _ => Err(self.constant_pool.mismatch(
+                index,
+                if wide {
+                    "a CONSTANT_Long, CONSTANT_Double or CONSTANT_Dynamic of long or double"
+                } else {
+                    "a loadable constant other than long or double"
+                },
+            )),
+        }
+    }
+
+    /// A `javap`-like listing of the class and its members.
+    pub fn to_text(&self, options: &TextOptions) -> String {
+        text::render(self, options)
+    }
+
+    /// The JSON representation, for embedding into a larger document.
+    pub fn to_json_value(&self) -> Result<serde_json::Value, ParseError>
+    where
+        Self: Serialize,
+    {
+        Ok(serde_json::to_value(self)?)
+    }
+
+    /// Whether this class file depends on the preview features of its Java SE release.
+    pub fn uses_preview_features(&self) -> bool {
+        self.version.uses_preview_features()
+    }
+
+    /// All `CONSTANT_Utf8` and `CONSTANT_String` values of the constant pool, in pool order.
+    ///
+    /// A `CONSTANT_String` refers to a `CONSTANT_Utf8`, so its value is yielded twice.
+    pub fn string_constants(&self) -> impl Iterator<Item = &str> {
+        self.constant_pool.iter().filter_map(|item| match item {
+            Some(CpInfo::Utf8(val)) => Some(val.as_ref()),
+            Some(CpInfo::String { string }) => Some(string.as_ref()),
+            _ => None,
+        })
+    }
+
+    /// The attributes of the class, then of each field and each method, with the ones of a
+    /// `Code` attribute or of the components of a `Record` attribute right after it, in file
+    /// order.
+    pub fn all_attributes(
+        &self,
+    ) -> impl Iterator<Item = (AttributeLocation, &AttributeInfo<S, B>)> {
+        // Popped from the end, so that the class comes first.
+        let mut pending =
+            self.methods
+                .iter()
+                .enumerate()
+                .rev()
+                .map(|(index, method)| (AttributeLocation::Method(index), method.attributes.iter()))
+                .chain(self.fields.iter().enumerate().rev().map(|(index, field)| {
+                    (AttributeLocation::Field(index), field.attributes.iter())
+                }))
+                .chain([(AttributeLocation::Class, self.attributes.iter())])
+                .collect::<Vec<_>>();
+        std::iter::from_fn(move || {
+            loop {
+                let (location, attributes) = pending.last_mut()?;
+                let location = *location;
+                let Some(attribute) = attributes.next() else {
+                    pending.pop();
+                    continue;
+                };
+                if let AttributeInfo::Code(code) = attribute {
+                    let code_location = match location {
+                        AttributeLocation::Method(index) | AttributeLocation::Code(index) => {
+                            AttributeLocation::Code(index)
+                        }
+                        location => location,
+                    };
+                    pending.push((code_location, code.attributes.iter()));
+                }
+                if let AttributeInfo::Record(components) = attribute {
+                    pending.extend(components.iter().enumerate().rev().map(
+                        |(index, component)| {
+                            (
+                                AttributeLocation::RecordComponent(index),
+                                component.attributes.iter(),
+                            )
+                        },
+                    ));
+                }
+                return Some((location, attribute));
+            }
+        })
+    }
+
+    /// Access flags of this class, preferring its own `InnerClasses` entry when it is nested.
+    ///
+    /// The modifiers of a nested class as declared in source are only recorded in
+    /// `InnerClasses`; the top-level `access_flags` are rewritten by the compiler (e.g. a
+    /// `private` nested class becomes package-private). Inner class flags that have no class
+    /// counterpart (`private`, `protected` and `static`) cannot be represented and are dropped.
+    pub fn effective_access_flags(&self) -> Vec<ClassAccessFlags> {
+        let inner_class = match self.attributes.by_name("InnerClasses") {
+            Some(AttributeInfo::InnerClasses(classes)) => classes
+                .iter()
+                .find(|class| class.inner_class_info.as_ref() == self.this_class.as_ref()),
+            _ => None,
+        };
+        let Some(inner_class) = inner_class else {
+            return self.access_flags.clone();
+        };
+
+        let mut flags = 0;
+        for flag in &inner_class.inner_class_access_flags {
+            flags |= *flag as u16;
+        }
+        // ACC_SUPER and ACC_MODULE never appear in InnerClasses.
+        for flag in &self.access_flags {
+            if let ClassAccessFlags::AccSuper | ClassAccessFlags::AccModule = flag {
+                flags |= *flag as u16;
+            }
+        }
+
+        ClassAccessFlags::VALUES
+            .into_iter()
+            .filter(|value| flags & *value as u16 != 0)
+            .collect()
+    }
+}
+
+#[cfg(feature = "fingerprint")]
+impl<S: AsRef<str> + Serialize, B: AsRef<[u8]> + Serialize> ClassFile<S, B> {
+    /// SHA-256 of the parts of this class that matter to reproducible builds.
+    ///
+    /// `SourceFile` and `LineNumberTable` attributes are left out and the order of attributes
+    /// is ignored. Constant pool indexes in bytecode are replaced by the constants they refer
+    /// to, so the layout of the constant pool does not matter either.
+    pub fn reproducibility_fingerprint(&self) -> Result<[u8; 32], ParseError> {
+        use sha2::Digest as _;
+
+        let canonical = serde_json::json!({
+            "version": self.version,
+            "access_flags": self.access_flags,
+            "this_class": self.this_class,
+            "super_class": self.super_class,
+            "interfaces": self.interfaces,
+            "fields": self
+                .fields
+                .iter()
+                .map(|field| Ok(serde_json::json!({
+                    "access_flags": field.access_flags,
+                    "name": field.name,
+                    "descriptor": field.descriptor,
+                    "attributes": self.canonical_attributes(&field.attributes)?,
+                })))
+                .collect::<Result<Vec<_>, ParseError>>()?,
+            "methods": self
+                .methods
+                .iter()
+                .map(|method| Ok(serde_json::json!({
+                    "access_flags": method.access_flags,
+                    "name": method.name,
+                    "descriptor": method.descriptor,
+                    "attributes": self.canonical_attributes(&method.attributes)?,
+                })))
+                .collect::<Result<Vec<_>, ParseError>>()?,
+            "attributes": self.canonical_attributes(&self.attributes)?,
+        });
+
+        Ok(sha2::Sha256::digest(serde_json::to_vec(&canonical)?).into())
+    }
+
+    fn canonical_attributes(
+        &self,
+        attributes: &Attributes<S, B>,
+    ) -> Result<serde_json::Value, ParseError> {
+        let mut items = vec![];
+        for attribute in attributes {
+            let item = match attribute {
+                AttributeInfo::SourceFile(..) | AttributeInfo::LineNumberTable(..) => continue,
+
+                AttributeInfo::Code(code) => {
+                    // Zero the constant pool indexes out and list the constants separately.
+                    let mut bytecode = code.bytecode.as_ref().to_vec();
+                    let mut constants = vec![];
+                    for instruction in code.instructions()? {
+                        let Some(index) = instruction.operation().constant_index() else {
+                            continue;
+                        };
+                        let start = instruction.offset() as usize + 1;
+                        let len = if let Operation::Ldc(..) = instruction.operation() {
+                            1
+                        } else {
+                            2
+                        };
+                        bytecode[start..start + len].fill(0);
+                        constants.push(self.constant_pool.get(index as usize));
+                    }
+
+                    serde_json::json!({
+                        "Code": {
+                            "max_stack": code.max_stack,
+                            "max_locals": code.max_locals,
+                            "bytecode": bytecode,
+                            "constants": constants,
+                            "exception_table": code.exception_table,
+                            "attributes": self.canonical_attributes(&code.attributes)?,
+                        }
+                    })
+                }
+
+                AttributeInfo::Unknown { name, info, .. } => serde_json::json!({
+                    "Unknown": { "name": name.as_ref(), "info": info.as_ref() }
+                }),
+
+                attribute => serde_json::to_value(attribute)?,
+            };
+            items.push(item);
+        }
+        items.sort_by_cached_key(|item| item.to_string());
+        Ok(items.into())
+    }
+}
+
+/// Options for [`wrap_with_options`].
+#[derive(Debug, Clone)]
+pub struct ParseOptions {
+    /// Fail with [`ParseError::PreviewFeaturesNotAllowed`] for class files depending on preview features.
+    pub reject_preview: bool,
+    /// Serialize the contents of unknown attributes as a hex dump instead of base64.
+    pub hex_unknown: bool,
+    /// Serialize the descriptors of `Fieldref`, `Methodref`, `InterfaceMethodref` and
+    /// `NameAndType` constants as [`FieldType`] or [`MethodDescriptor`] instead of strings.
+    ///
+    /// `NameAndType` descriptors which cannot be parsed are kept as strings; [`wrap`] rejects the
+    /// other ones beforehand.
+    pub parse_descriptors: bool,
+    /// Leave the `magic` field, always `0xCAFEBABE`, out of the output.
+    pub omit_magic: bool,
+    /// Fail with [`ParseError::AttributeNewerThanTarget`] for attributes introduced after this
+    /// Java SE version, e.g. `11` for `NestHost`.
+    pub max_java_version: Option<u16>,
+    /// Keep the `reference_index` of `MethodHandle` constants, which are otherwise only
+    /// resolved to the class, name and descriptor of the member they refer to.
+    pub method_handle_reference_index: bool,
+    /// Fail with [`ParseError::MaxNestingExceeded`] for attributes nested within `Code`, or
+    /// annotations and array element values nested within each other, more than this many
+    /// levels deep, rather than risking a stack overflow. 64 by default.
+    pub max_nesting_depth: usize,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            reject_preview: false,
+            hex_unknown: false,
+            parse_descriptors: false,
+            omit_magic: false,
+            max_java_version: None,
+            method_handle_reference_index: false,
+            max_nesting_depth: raw::MAX_NESTING_DEPTH,
+        }
+    }
+}
+
+/// The Java SE versions which introduced the attributes newer than 1.1.
+const ATTRIBUTE_VERSIONS: &[(&str, u16)] = &[
+    ("EnclosingMethod", 5),
+    ("Signature", 5),
+    ("SourceDebugExtension", 5),
+    ("LocalVariableTypeTable", 5),
+    ("RuntimeVisibleAnnotations", 5),
+    ("RuntimeInvisibleAnnotations", 5),
+    ("RuntimeVisibleParameterAnnotations", 5),
+    ("RuntimeInvisibleParameterAnnotations", 5),
+    ("AnnotationDefault", 5),
+    ("StackMapTable", 6),
+    ("BootstrapMethods", 7),
+    ("RuntimeVisibleTypeAnnotations", 8),
+    ("RuntimeInvisibleTypeAnnotations", 8),
+    ("MethodParameters", 8),
+    ("Module", 9),
+    ("ModulePackages", 9),
+    ("ModuleMainClass", 9),
+    ("NestHost", 11),
+    ("NestMembers", 11),
+    ("Record", 16),
+    ("PermittedSubclasses", 17),
+];
+
+fn check_attribute_versions<S: AsRef<str>, B: AsRef<[u8]>>(
+    attributes: &Attributes<S, B>,
+    target: u16,
+) -> Result<(), ParseError> {
+    for attribute in attributes {
+        let name = attribute.name();
+        if let Some(&(_, introduced_in)) = ATTRIBUTE_VERSIONS.iter().find(|(n, _)| *n == name)
+            && introduced_in > target
+        {
+            return Err(ParseError::AttributeNewerThanTarget {
+                name: name.to_string(),
+                introduced_in,
+                target,
+            });
+        }
+        if let AttributeInfo::Code(code) = attribute {
+            check_attribute_versions(&code.attributes, target)?;
+        }
+    }
+    Ok(())
+}
+
+struct ParsedClassCart {
+    bytes: Vec<u8>,
+    raw: raw::ClassFile,
+}
+
+#[derive(yoke::Yokeable)]
+struct Wrapped<'a>(ClassFile<&'a str, &'a [u8]>);
+
+/// A [`ClassFile`] owning the class file it was parsed from.
+///
+/// Unlike the result of [`wrap`], it does not borrow anything, so it can be stored and moved
+/// freely.
+pub struct ParsedClass {
+    inner: yoke::Yoke<Wrapped<'static>, Box<ParsedClassCart>>,
+}
+
+impl ParsedClass {
+    pub fn from_bytes<T: Into<Vec<u8>>>(bytes: T) -> Result<Self, ParseError> {
+        let bytes = bytes.into();
+        let raw = raw::parse(&mut bytes.as_slice())?;
+        let cart = Box::new(ParsedClassCart { bytes, raw });
+        let inner = yoke::Yoke::try_attach_to_cart(cart, |cart: &ParsedClassCart| {
+            wrap(&cart.raw).map(Wrapped)
+        })?;
+        Ok(Self { inner })
+    }
+
+    /// The bytes the class was parsed from.
+    pub fn bytes(&self) -> &[u8] {
+        &self.inner.backing_cart().bytes
+    }
+
+    pub fn get(&self) -> &ClassFile<&str, &[u8]> {
+        &self.inner.get().0
+    }
+}
+
+impl Serialize for ParsedClass {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.get().serialize(serializer)
+    }
+}
+
+fn as_base64<T: AsRef<[u8]>, S: serde::Serializer>(
+    val: &T,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&base64::engine::general_purpose::STANDARD.encode(val.as_ref()))
+}
+
+#[derive(Serialize)]
+struct WideValue {
+    value: String,
+    bits: String,
+}
+
+fn serialize_long<S: serde::Serializer>(val: &i64, serializer: S) -> Result<S::Ok, S::Error> {
+    WideValue {
+        value: val.to_string(),
+        bits: format!("{:#018x}", *val as u64),
+    }
+    .serialize(serializer)
+}
+
+fn serialize_double<S: serde::Serializer>(val: &f64, serializer: S) -> Result<S::Ok, S::Error> {
+    WideValue {
+        value: val.to_string(),
+        bits: format!("{:#018x}", val.to_bits()),
+    }
+    .serialize(serializer)
+}
+
+/// The slot at `index`, failing with [`ParseError::InvalidCpIndex`] past the end of `pool`.
+fn cp_slot(pool: &[Option<raw::CpInfo>], index: u16) -> Result<&Option<raw::CpInfo>, ParseError> {
+    pool.get(index as usize).ok_or(ParseError::InvalidCpIndex {
+        index,
+        pool_size: pool.len(),
+    })
+}
+
+/// The constant at `index`, resolved; index 0 and the unusable slots are not constants.
+fn resolve_cp(pool: &[Option<raw::CpInfo>], index: u16) -> Result<CpInfo<&str>, ParseError> {
+    parse_cp_info(pool, index)?.ok_or(ParseError::UnexpectedCpType {
+        index,
+        expected: "a constant",
+    })
+}
+
+/// The value of the `CONSTANT_Utf8` at `index`.
+pub(crate) fn resolve_utf8(pool: &[Option<raw::CpInfo>], index: u16) -> Result<&str, ParseError> {
+    match cp_slot(pool, index)? {
+        Some(raw::CpInfo::Utf8(value)) => Ok(value),
+        _ => Err(ParseError::UnexpectedCpType {
+            index,
+            expected: "CONSTANT_Utf8",
+        }),
+    }
+}
+
+/// The name of the `CONSTANT_Class` at `index`.
+pub(crate) fn resolve_class(pool: &[Option<raw::CpInfo>], index: u16) -> Result<&str, ParseError> {
+    match cp_slot(pool, index)? {
+        Some(raw::CpInfo::Class { name_index }) => resolve_utf8(pool, *name_index),
+        _ => Err(ParseError::UnexpectedCpType {
+            index,
+            expected: "CONSTANT_Class",
+        }),
+    }
+}
+
+/// The name and descriptor of the `CONSTANT_NameAndType` at `index`.
+fn resolve_name_and_type(
+    pool: &[Option<raw::CpInfo>],
+    index: u16,
+) -> Result<(&str, &str), ParseError> {
+    match cp_slot(pool, index)? {
+        Some(raw::CpInfo::NameAndType {
+            name_index,
+            descriptor_index,
+        }) => Ok((
+            resolve_utf8(pool, *name_index)?,
+            resolve_utf8(pool, *descriptor_index)?,
+        )),
+        _ => Err(ParseError::UnexpectedCpType {
+            index,
+            expected: "CONSTANT_NameAndType",
+        }),
+    }
+}
+
+/// The constant at `index`, resolved, or `None` for index 0 and the unusable slots.
+fn parse_cp_info(
+    pool: &[Option<raw::CpInfo>],
+    index: u16,
+) -> Result<Option<CpInfo<&str>>, ParseError> {
+    let Some(item) = cp_slot(pool, index)? else {
+        return Ok(None);
+    };
+
+    // Kinds are checked before resolving, so that malformed entries cannot refer to each other
+    // endlessly.
     Ok(Some(match item {
-        raw::CpInfo::Utf8(val) => CpInfo::Utf8(&val),
+        raw::CpInfo::Utf8(val) => CpInfo::Utf8(val),
 
         raw::CpInfo::Integer(val) => CpInfo::Integer(*val as i32),
 
@@ -319,42 +2352,21 @@ fn parse_cp_info<'a>(
             CpInfo::Double(f64::from_bits((*hi as u64) << 32u64 | *lo as u64))
         }
 
-        raw::CpInfo::Class { name_index } => {
-            let Some(Some(raw::CpInfo::Utf8(name))) = pool.get(*name_index as usize) else {
-                todo!()
-            };
-            CpInfo::Class { name }
-        }
+        raw::CpInfo::Class { name_index } => CpInfo::Class {
+            name: resolve_utf8(pool, *name_index)?,
+        },
 
-        raw::CpInfo::String { string_index } => {
-            let Some(Some(raw::CpInfo::Utf8(string))) = pool.get(*string_index as usize) else {
-                todo!()
-            };
-            CpInfo::String { string }
-        }
+        raw::CpInfo::String { string_index } => CpInfo::String {
+            string: resolve_utf8(pool, *string_index)?,
+        },
 
         raw::CpInfo::Fieldref {
             class_index,
             name_and_type_index,
         } => {
-            let Some(class) = pool.get(*class_index as usize) else {
-                todo!()
-            };
-            let Some(CpInfo::Class { name: class }) = parse_cp_info(pool, class)? else {
-                todo!()
-            };
-
-            let Some(name_and_type) = pool.get(*name_and_type_index as usize) else {
-                todo!()
-            };
-            let Some(CpInfo::NameAndType { name, descriptor }) =
-                parse_cp_info(pool, name_and_type)?
-            else {
-                todo!()
-            };
-
+            let (name, descriptor) = resolve_name_and_type(pool, *name_and_type_index)?;
             CpInfo::Fieldref {
-                class,
+                class: resolve_class(pool, *class_index)?,
                 name,
                 descriptor,
             }
@@ -364,24 +2376,9 @@ fn parse_cp_info<'a>(
             class_index,
             name_and_type_index,
         } => {
-            let Some(class) = pool.get(*class_index as usize) else {
-                todo!()
-            };
-            let Some(CpInfo::Class { name: class }) = parse_cp_info(pool, class)? else {
-                todo!()
-            };
-
-            let Some(name_and_type) = pool.get(*name_and_type_index as usize) else {
-                todo!()
-            };
-            let Some(CpInfo::NameAndType { name, descriptor }) =
-                parse_cp_info(pool, name_and_type)?
-            else {
-                todo!()
-            };
-
+            let (name, descriptor) = resolve_name_and_type(pool, *name_and_type_index)?;
             CpInfo::Methodref {
-                class,
+                class: resolve_class(pool, *class_index)?,
                 name,
                 descriptor,
             }
@@ -391,24 +2388,9 @@ fn parse_cp_info<'a>(
             class_index,
             name_and_type_index,
         } => {
-            let Some(class) = pool.get(*class_index as usize) else {
-                todo!()
-            };
-            let Some(CpInfo::Class { name: class }) = parse_cp_info(pool, class)? else {
-                todo!()
-            };
-
-            let Some(name_and_type) = pool.get(*name_and_type_index as usize) else {
-                todo!()
-            };
-            let Some(CpInfo::NameAndType { name, descriptor }) =
-                parse_cp_info(pool, name_and_type)?
-            else {
-                todo!()
-            };
-
+            let (name, descriptor) = resolve_name_and_type(pool, *name_and_type_index)?;
             CpInfo::InterfaceMethodref {
-                class,
+                class: resolve_class(pool, *class_index)?,
                 name,
                 descriptor,
             }
@@ -417,16 +2399,10 @@ fn parse_cp_info<'a>(
         raw::CpInfo::NameAndType {
             name_index,
             descriptor_index,
-        } => {
-            let Some(Some(raw::CpInfo::Utf8(name))) = pool.get(*name_index as usize) else {
-                todo!()
-            };
-            let Some(Some(raw::CpInfo::Utf8(descriptor))) = pool.get(*descriptor_index as usize)
-            else {
-                todo!()
-            };
-            CpInfo::NameAndType { name, descriptor }
-        }
+        } => CpInfo::NameAndType {
+            name: resolve_utf8(pool, *name_index)?,
+            descriptor: resolve_utf8(pool, *descriptor_index)?,
+        },
 
         raw::CpInfo::MethodHandle {
             reference_kind,
@@ -442,312 +2418,395 @@ fn parse_cp_info<'a>(
                 7 => ReferenceKind::RefInvokeSpecial,
                 8 => ReferenceKind::RefNewInvokeSpecial,
                 9 => ReferenceKind::RefNewInvokeInterface,
-                _ => todo!(),
+                _ => {
+                    return Err(ParseError::InvalidConstant {
+                        index,
+                        field: "reference_kind",
+                        value: *reference_kind,
+                    });
+                }
             };
 
-            let Some(reference) = pool.get(*reference_index as usize) else {
-                todo!()
-            };
             let Some(
-                CpInfo::Fieldref {
-                    class,
-                    name,
-                    descriptor,
+                raw::CpInfo::Fieldref {
+                    class_index,
+                    name_and_type_index,
                 }
-                | CpInfo::Methodref {
-                    class,
-                    name,
-                    descriptor,
+                | raw::CpInfo::Methodref {
+                    class_index,
+                    name_and_type_index,
                 }
-                | CpInfo::InterfaceMethodref {
-                    class,
-                    name,
-                    descriptor,
+                | raw::CpInfo::InterfaceMethodref {
+                    class_index,
+                    name_and_type_index,
                 },
-            ) = parse_cp_info(pool, reference)?
+            ) = cp_slot(pool, *reference_index)?
             else {
-                todo!()
+                return Err(ParseError::UnexpectedCpType {
+                    index: *reference_index,
+                    expected: "CONSTANT_Fieldref, CONSTANT_Methodref or \
+                               CONSTANT_InterfaceMethodref",
+                });
             };
+            let (name, descriptor) = resolve_name_and_type(pool, *name_and_type_index)?;
             CpInfo::MethodHandle {
                 reference_kind,
-                class,
+                class: resolve_class(pool, *class_index)?,
                 name,
                 descriptor,
+                reference_index: None,
             }
         }
 
-        raw::CpInfo::MethodType { descriptor_index } => {
-            let Some(Some(raw::CpInfo::Utf8(descriptor))) = pool.get(*descriptor_index as usize)
-            else {
-                todo!()
-            };
-            CpInfo::MethodType { descriptor }
-        }
+        raw::CpInfo::MethodType { descriptor_index } => CpInfo::MethodType {
+            descriptor: resolve_utf8(pool, *descriptor_index)?,
+        },
 
         raw::CpInfo::Dynamic {
+            bootstrap_method_attr_index,
             name_and_type_index,
-            ..
         } => {
-            let Some(name_and_type) = pool.get(*name_and_type_index as usize) else {
-                todo!()
-            };
-            let Some(CpInfo::NameAndType { name, descriptor }) =
-                parse_cp_info(pool, name_and_type)?
-            else {
-                todo!()
-            };
-
+            let (name, descriptor) = resolve_name_and_type(pool, *name_and_type_index)?;
             CpInfo::Dynamic {
-                bootstrap_method_attr: (), // TODO
+                bootstrap_method_attr: *bootstrap_method_attr_index,
                 name,
                 descriptor,
             }
         }
 
         raw::CpInfo::InvokeDynamic {
-            name_and_type_index,
-            ..
-        } => {
-            let Some(name_and_type) = pool.get(*name_and_type_index as usize) else {
-                todo!()
-            };
-            let Some(CpInfo::NameAndType { name, descriptor }) =
-                parse_cp_info(pool, name_and_type)?
-            else {
-                todo!()
-            };
-
+            bootstrap_method_attr_index,
+            name_and_type_index,
+        } => {
+            let (name, descriptor) = resolve_name_and_type(pool, *name_and_type_index)?;
             CpInfo::InvokeDynamic {
-                bootstrap_method_attr: (), // TODO
+                bootstrap_method_attr: *bootstrap_method_attr_index,
                 name,
                 descriptor,
             }
         }
 
-        raw::CpInfo::Module { name_index } => {
-            let Some(name) = pool.get(*name_index as usize) else {
-                todo!()
-            };
-            let Some(CpInfo::Utf8(name)) = parse_cp_info(pool, name)? else {
-                todo!()
-            };
-
-            CpInfo::Module { name }
-        }
-
-        raw::CpInfo::Package { name_index } => {
-            let Some(name) = pool.get(*name_index as usize) else {
-                todo!()
-            };
-            let Some(CpInfo::Utf8(name)) = parse_cp_info(pool, name)? else {
-                todo!()
-            };
+        raw::CpInfo::Module { name_index } => CpInfo::Module {
+            name: resolve_utf8(pool, *name_index)?,
+        },
 
-            CpInfo::Package { name }
-        }
+        raw::CpInfo::Package { name_index } => CpInfo::Package {
+            name: resolve_utf8(pool, *name_index)?,
+        },
     }))
 }
 
-fn parse_class_access_flags(flags: u16) -> Result<Vec<ClassAccessFlags>, ParseError> {
-    let mut ret = vec![];
-
-    let mut wants = 0;
-    for value in ClassAccessFlags::VALUES {
-        if flags & value as u16 != 0 {
-            ret.push(value);
-            wants |= value as u16;
-        }
-    }
-
-    if flags != wants {
-        todo!() // TODO containts Unknown flag
-    }
-
-    Ok(ret)
+/// Bits not assigned to a flag are reserved and ignored (JVMS §4.1), so that class files
+/// of newer releases still parse.
+fn parse_class_access_flags(flags: u16) -> Vec<ClassAccessFlags> {
+    ClassAccessFlags::VALUES
+        .into_iter()
+        .filter(|value| flags & *value as u16 != 0)
+        .collect()
 }
 
-fn parse_field_access_flags(flags: u16) -> Result<Vec<FieldAccessFlags>, ParseError> {
-    let mut ret = vec![];
-
-    let mut wants = 0;
-    for value in FieldAccessFlags::VALUES {
-        if flags & value as u16 != 0 {
-            ret.push(value);
-            wants |= value as u16;
-        }
-    }
-
-    if flags != wants {
-        todo!() // TODO containts Unknown flag
-    }
-
-    Ok(ret)
+/// Reserved bits are ignored, as in [`parse_class_access_flags`].
+fn parse_field_access_flags(flags: u16) -> Vec<FieldAccessFlags> {
+    FieldAccessFlags::VALUES
+        .into_iter()
+        .filter(|value| flags & *value as u16 != 0)
+        .collect()
 }
 
-fn parse_method_access_flags(flags: u16) -> Result<Vec<MethodAccessFlags>, ParseError> {
-    let mut ret = vec![];
-
-    let mut wants = 0;
-    for value in MethodAccessFlags::VALUES {
-        if flags & value as u16 != 0 {
-            ret.push(value);
-            wants |= value as u16;
-        }
-    }
+/// Reserved bits are ignored, as in [`parse_class_access_flags`].
+fn parse_method_access_flags(flags: u16) -> Vec<MethodAccessFlags> {
+    MethodAccessFlags::VALUES
+        .into_iter()
+        .filter(|value| flags & *value as u16 != 0)
+        .collect()
+}
 
-    if flags != wants {
-        todo!() // TODO containts Unknown flag
-    }
+/// Reserved bits are ignored, as in [`parse_class_access_flags`].
+fn parse_inner_class_access_flags(flags: u16) -> Vec<InnerClassAccessFlags> {
+    InnerClassAccessFlags::VALUES
+        .into_iter()
+        .filter(|value| flags & *value as u16 != 0)
+        .collect()
+}
 
-    Ok(ret)
+/// Reserved bits are ignored, as in [`parse_class_access_flags`].
+fn parse_method_parameter_access_flags(flags: u16) -> Vec<MethodParameterAccessFlags> {
+    MethodParameterAccessFlags::VALUES
+        .into_iter()
+        .filter(|value| flags & *value as u16 != 0)
+        .collect()
 }
 
-fn parse_inner_class_access_flags(flags: u16) -> Result<Vec<InnerClassAccessFlags>, ParseError> {
-    let mut ret = vec![];
+/// The names of the attributes met so far in a class, resolved once for each index, as the
+/// many attributes of a class share a few names.
+struct AttributeNames<'a> {
+    pool: &'a [Option<raw::CpInfo>],
+    interned: RefCell<Vec<(u16, &'a str)>>,
+}
 
-    let mut wants = 0;
-    for value in InnerClassAccessFlags::VALUES {
-        if flags & value as u16 != 0 {
-            ret.push(value);
-            wants |= value as u16;
+impl<'a> AttributeNames<'a> {
+    fn new(pool: &'a [Option<raw::CpInfo>]) -> Self {
+        Self {
+            pool,
+            interned: RefCell::default(),
         }
     }
 
-    if flags != wants {
-        todo!() // TODO containts Unknown flag
+    fn get(&self, attribute_name_index: u16) -> Result<&'a str, ParseError> {
+        let interned = self
+            .interned
+            .borrow()
+            .iter()
+            .find(|(index, _)| *index == attribute_name_index)
+            .map(|(_, name)| *name);
+        if let Some(name) = interned {
+            return Ok(name);
+        }
+        let name = resolve_utf8(self.pool, attribute_name_index)?;
+        self.interned
+            .borrow_mut()
+            .push((attribute_name_index, name));
+        Ok(name)
     }
-
-    Ok(ret)
 }
 
-fn parse_attribute_info<'a>(
+/// `offset` is the offset of the attribute from the beginning of the class file.
+/// `depth` is the number of levels of nesting left, as in [`ParseOptions::max_nesting_depth`].
+///
+/// Lengths and counts which disagree with `info` are reported with its first bytes.
+fn parse_attribute_info<'a, 'p: 'a>(
     pool: &'a [Option<raw::CpInfo>],
-    attribute: &'a raw::AttributeInfo,
+    names: &AttributeNames<'p>,
+    depth: usize,
+    attribute_name_index: u16,
+    offset: u64,
+    info: &'a [u8],
 ) -> Result<AttributeInfo<&'a str, &'a [u8]>, ParseError> {
-    let Some(attribute_name) = pool.get(attribute.attribute_name_index as usize) else {
-        todo!()
+    let cx = raw::AttributeContext {
+        name: names.get(attribute_name_index)?,
+        offset,
+        info,
+    };
+    parse_attribute_contents(pool, names, depth, cx)
+}
+
+/// The classes of an attribute made of a `u2` count and as many `CONSTANT_Class` indexes.
+fn parse_class_table<'a>(
+    pool: &'a [Option<raw::CpInfo>],
+    cx: raw::AttributeContext<'_>,
+    count_context: &'static str,
+) -> Result<Vec<&'a str>, ParseError> {
+    let (chunks, []) = cx.info.as_chunks() else {
+        return Err(cx.length_mismatch(count_context));
     };
-    let Some(CpInfo::Utf8(attribute_name)) = parse_cp_info(pool, attribute_name)? else {
-        todo!()
+    let Some((count, indexes)) = chunks.split_first() else {
+        return Err(cx.length_mismatch(count_context));
     };
+    if indexes.len() != u16::from_be_bytes(*count) as usize {
+        return Err(cx.length_mismatch(count_context));
+    }
+    indexes
+        .iter()
+        .map(|index| resolve_class(pool, u16::from_be_bytes(*index)))
+        .collect()
+}
 
+fn parse_attribute_contents<'a, 'p: 'a>(
+    pool: &'a [Option<raw::CpInfo>],
+    names: &AttributeNames<'p>,
+    depth: usize,
+    cx: raw::AttributeContext<'a>,
+) -> Result<AttributeInfo<&'a str, &'a [u8]>, ParseError> {
+    let raw::AttributeContext {
+        name: attribute_name,
+        offset,
+        info,
+    } = cx;
     Ok(match attribute_name {
         "ConstantValue" => {
-            let (chunks, []) = attribute.info.as_chunks() else {
-                todo!()
+            let (chunks, []) = info.as_chunks() else {
+                return Err(cx.length_mismatch("ConstantValue attribute_length"));
+            };
+            let Some(chunk) = chunks.first() else {
+                return Err(cx.length_mismatch("ConstantValue attribute_length"));
             };
-            let Some(chunk) = chunks.get(0) else { todo!() };
             let index = u16::from_be_bytes(*chunk);
+            if index == 0 {
+                return Err(ParseError::InvalidConstantValueIndex);
+            }
 
-            let Some(item) = pool.get(index as usize) else {
-                todo!()
-            };
-            match parse_cp_info(pool, item)? {
-                Some(CpInfo::Integer(val)) => {
-                    AttributeInfo::ConstantValue(ConstantValueAttribute::Integer(val))
-                }
-                Some(CpInfo::Float(val)) => {
-                    AttributeInfo::ConstantValue(ConstantValueAttribute::Float(val))
-                }
-                Some(CpInfo::Long(val)) => {
-                    AttributeInfo::ConstantValue(ConstantValueAttribute::Long(val))
+            AttributeInfo::ConstantValue(match resolve_cp(pool, index)? {
+                CpInfo::Integer(val) => ConstantValueAttribute::Integer(val),
+                CpInfo::Float(val) => ConstantValueAttribute::Float(val),
+                CpInfo::Long(val) => ConstantValueAttribute::Long(val),
+                CpInfo::Double(val) => ConstantValueAttribute::Double(val),
+                CpInfo::String { string } => ConstantValueAttribute::String(string),
+                _ => {
+                    return Err(ParseError::UnexpectedCpType {
+                        index,
+                        expected: "CONSTANT_Integer, CONSTANT_Float, CONSTANT_Long, \
+                                   CONSTANT_Double or CONSTANT_String",
+                    });
                 }
-                Some(CpInfo::Double(val)) => {
-                    AttributeInfo::ConstantValue(ConstantValueAttribute::Double(val))
-                }
-                Some(CpInfo::String { string }) => {
-                    AttributeInfo::ConstantValue(ConstantValueAttribute::String(string))
-                }
-                _ => todo!(),
-            }
+            })
         }
 
-        "Code" => AttributeInfo::Code(&attribute.info),
+        "Code" => {
+            let mut input = info;
+            let max_stack = cx.u2(&mut input, "Code max_stack")?;
+            let max_locals = cx.u2(&mut input, "Code max_locals")?;
+            let code_length = cx.u4(&mut input, "Code code_length")? as usize;
+            let Some((bytecode, rest)) = input.split_at_checked(code_length) else {
+                return Err(cx.length_mismatch("Code code_length"));
+            };
+            input = rest;
+
+            let exception_table_length = cx.u2(&mut input, "Code exception_table_length")? as usize;
+            let mut exception_table = Vec::with_capacity(exception_table_length);
+            for _ in 0..exception_table_length {
+                let start_pc = cx.u2(&mut input, "Code start_pc")?;
+                let end_pc = cx.u2(&mut input, "Code end_pc")?;
+                let handler_pc = cx.u2(&mut input, "Code handler_pc")?;
+                let catch_type = cx.u2(&mut input, "Code catch_type")?;
+                let catch_type = if catch_type == 0 {
+                    None
+                } else {
+                    Some(resolve_class(pool, catch_type)?)
+                };
+                exception_table.push(ExceptionHandler {
+                    start_pc,
+                    end_pc,
+                    handler_pc,
+                    catch_type,
+                });
+            }
+
+            let attributes_count = cx.u2(&mut input, "Code attributes_count")? as usize;
+            let mut attributes = Vec::with_capacity(attributes_count);
+            for _ in 0..attributes_count {
+                // attribute_name_index and attribute_length precede info.
+                let attribute_offset = offset + 6 + (info.len() - input.len()) as u64;
+                let attribute_name_index = cx.u2(&mut input, "Code attribute_name_index")?;
+                let attribute_length = cx.u4(&mut input, "Code attribute_length")? as usize;
+                let Some((info, rest)) = input.split_at_checked(attribute_length) else {
+                    return Err(cx.length_mismatch("Code attribute_length"));
+                };
+                input = rest;
+                attributes.push(parse_attribute_info(
+                    pool,
+                    names,
+                    raw::nest(depth)?,
+                    attribute_name_index,
+                    attribute_offset,
+                    info,
+                )?);
+            }
+            if !input.is_empty() {
+                return Err(cx.length_mismatch("Code attribute_length"));
+            }
+
+            AttributeInfo::Code(CodeAttribute {
+                max_stack,
+                max_locals,
+                bytecode,
+                exception_table,
+                attributes: Attributes(attributes),
+            })
+        }
 
         "Exceptions" => {
-            let (chunks, []) = attribute.info.as_chunks() else {
-                todo!()
+            let (chunks, []) = info.as_chunks() else {
+                return Err(cx.length_mismatch("Exceptions attribute_length"));
+            };
+            let Some(first) = chunks.first() else {
+                return Err(cx.length_mismatch("Exceptions number_of_exceptions"));
             };
-            let Some(first) = chunks.get(0) else { todo!() };
             let n = u16::from_be_bytes(*first) as usize;
             let exception_index_table = &chunks[1..];
             if exception_index_table.len() != n {
-                todo!()
+                return Err(cx.length_mismatch("Exceptions number_of_exceptions"));
             };
             let exceptions = exception_index_table
                 .iter()
                 .map(|i| u16::from_be_bytes(*i))
-                .map(|i| {
-                    let Some(item) = pool.get(i as usize) else {
-                        todo!()
-                    };
-                    let Some(CpInfo::Class { name }) = parse_cp_info(pool, item)? else {
-                        todo!()
-                    };
-                    Ok::<_, ParseError>(name)
-                })
+                .map(|i| resolve_class(pool, i))
                 .collect::<Result<_, _>>()?;
             AttributeInfo::Exceptions(exceptions)
         }
 
         "SourceFile" => {
-            let (chunks, []) = attribute.info.as_chunks() else {
-                todo!()
+            let (chunks, []) = info.as_chunks() else {
+                return Err(cx.length_mismatch("SourceFile attribute_length"));
+            };
+            let Some(chunk) = chunks.first() else {
+                return Err(cx.length_mismatch("SourceFile attribute_length"));
             };
-            let Some(chunk) = chunks.get(0) else { todo!() };
             let index = u16::from_be_bytes(*chunk);
 
-            let Some(item) = pool.get(index as usize) else {
-                todo!()
+            AttributeInfo::SourceFile(resolve_utf8(pool, index)?)
+        }
+
+        "Deprecated" | "Synthetic" => {
+            if !info.is_empty() {
+                return Err(ParseError::MalformedAttribute {
+                    name: attribute_name.to_string(),
+                    detail: format!("{} bytes in a marker attribute", info.len()),
+                });
+            }
+            if attribute_name == "Deprecated" {
+                AttributeInfo::Deprecated
+            } else {
+                AttributeInfo::Synthetic
+            }
+        }
+
+        "Signature" => {
+            let (chunks, []) = info.as_chunks() else {
+                return Err(cx.length_mismatch("Signature attribute_length"));
             };
-            let Some(CpInfo::Utf8(val)) = parse_cp_info(pool, item)? else {
-                todo!()
+            let Some(chunk) = chunks.first() else {
+                return Err(cx.length_mismatch("Signature attribute_length"));
             };
-            AttributeInfo::SourceFile(val)
+            let index = u16::from_be_bytes(*chunk);
+
+            AttributeInfo::Signature(resolve_utf8(pool, index)?)
         }
 
         "BootstrapMethods" => {
-            let (chunks, []) = attribute.info.as_chunks() else {
-                todo!()
+            let (chunks, []) = info.as_chunks() else {
+                return Err(cx.length_mismatch("BootstrapMethods attribute_length"));
             };
             let mut chunks = chunks.iter().map(|v| u16::from_be_bytes(*v));
             let Some(num_bootstrap_methods) = chunks.next() else {
-                todo!()
+                return Err(cx.length_mismatch("BootstrapMethods num_bootstrap_methods"));
             };
 
             let mut items = Vec::with_capacity(num_bootstrap_methods as usize);
             for _ in 0..num_bootstrap_methods {
                 let Some(bootstrap_method_ref) = chunks.next() else {
-                    todo!()
-                };
-                let Some(item) = pool.get(bootstrap_method_ref as usize) else {
-                    todo!()
+                    return Err(cx.length_mismatch("BootstrapMethods bootstrap_method_ref"));
                 };
-                let Some(CpInfo::MethodHandle {
+                let CpInfo::MethodHandle {
                     reference_kind,
                     class,
                     name,
                     descriptor,
-                }) = parse_cp_info(pool, item)?
+                    ..
+                } = resolve_cp(pool, bootstrap_method_ref)?
                 else {
-                    todo!()
+                    return Err(ParseError::UnexpectedCpType {
+                        index: bootstrap_method_ref,
+                        expected: "CONSTANT_MethodHandle",
+                    });
                 };
 
                 let Some(num_bootstrap_arguments) = chunks.next() else {
-                    todo!()
+                    return Err(cx.length_mismatch("BootstrapMethods num_bootstrap_arguments"));
                 };
                 let bootstrap_arguments = chunks
                     .by_ref()
                     .take(num_bootstrap_arguments as usize)
-                    .map(|v| {
-                        let Some(item) = pool.get(v as usize) else {
-                            todo!()
-                        };
-                        let Some(item) = parse_cp_info(pool, item)? else {
-                            todo!()
-                        };
-                        Ok::<_, ParseError>(item)
-                    })
+                    .map(|v| resolve_cp(pool, v))
                     .collect::<Result<_, _>>()?;
                 items.push(BootstrapMethod {
                     reference_kind,
@@ -757,75 +2816,52 @@ fn parse_attribute_info<'a>(
                     bootstrap_arguments,
                 });
             }
-            if chunks.next() != None {
-                todo!()
+            if chunks.next().is_some() {
+                return Err(cx.length_mismatch("BootstrapMethods attribute_length"));
             }
 
             AttributeInfo::BootstrapMethods(items)
         }
 
         "InnerClasses" => {
-            let (chunks, []) = attribute.info.as_chunks() else {
-                todo!()
+            let (chunks, []) = info.as_chunks() else {
+                return Err(cx.length_mismatch("InnerClasses attribute_length"));
             };
             let mut chunks = chunks.iter().map(|v| u16::from_be_bytes(*v));
             let Some(numer_of_classes) = chunks.next() else {
-                todo!()
+                return Err(cx.length_mismatch("InnerClasses number_of_classes"));
             };
 
             let mut items = Vec::with_capacity(numer_of_classes as usize);
             for _ in 0..numer_of_classes {
                 let Some(inner_class_info) = chunks.next() else {
-                    todo!()
-                };
-                let Some(item) = pool.get(inner_class_info as usize) else {
-                    todo!()
-                };
-                let Some(CpInfo::Class {
-                    name: inner_class_info,
-                }) = parse_cp_info(pool, item)?
-                else {
-                    todo!()
+                    return Err(cx.length_mismatch("InnerClasses inner_class_info_index"));
                 };
+                let inner_class_info = resolve_class(pool, inner_class_info)?;
 
                 let Some(outer_class_info) = chunks.next() else {
-                    todo!()
+                    return Err(cx.length_mismatch("InnerClasses outer_class_info_index"));
                 };
                 let outer_class_info = if outer_class_info == 0 {
                     None
                 } else {
-                    let Some(item) = pool.get(outer_class_info as usize) else {
-                        todo!()
-                    };
-                    let Some(CpInfo::Class {
-                        name: outer_class_info,
-                    }) = parse_cp_info(pool, item)?
-                    else {
-                        todo!()
-                    };
-                    Some(outer_class_info)
+                    Some(resolve_class(pool, outer_class_info)?)
                 };
 
                 let Some(inner_name) = chunks.next() else {
-                    todo!()
+                    return Err(cx.length_mismatch("InnerClasses inner_name_index"));
                 };
                 let inner_name = if inner_name == 0 {
                     None
                 } else {
-                    let Some(item) = pool.get(inner_name as usize) else {
-                        todo!()
-                    };
-                    let Some(CpInfo::Utf8(inner_name)) = parse_cp_info(pool, item)? else {
-                        todo!()
-                    };
-                    Some(inner_name)
+                    Some(resolve_utf8(pool, inner_name)?)
                 };
 
                 let Some(inner_class_access_flags) = chunks.next() else {
-                    todo!()
+                    return Err(cx.length_mismatch("InnerClasses inner_class_access_flags"));
                 };
                 let inner_class_access_flags =
-                    parse_inner_class_access_flags(inner_class_access_flags)?;
+                    parse_inner_class_access_flags(inner_class_access_flags);
 
                 items.push(InnerClass {
                     inner_class_info,
@@ -834,46 +2870,262 @@ fn parse_attribute_info<'a>(
                     inner_class_access_flags,
                 });
             }
-            if chunks.next() != None {
-                todo!()
+            if chunks.next().is_some() {
+                return Err(cx.length_mismatch("InnerClasses attribute_length"));
             }
 
             AttributeInfo::InnerClasses(items)
         }
 
-        // TODO
-        "Module" => AttributeInfo::Unknown(attribute_name, &attribute.info),
+        "LineNumberTable" => {
+            let (chunks, []) = info.as_chunks() else {
+                return Err(cx.length_mismatch("LineNumberTable attribute_length"));
+            };
+            let mut chunks = chunks.iter().map(|v| u16::from_be_bytes(*v));
+            let Some(line_number_table_length) = chunks.next() else {
+                return Err(cx.length_mismatch("LineNumberTable line_number_table_length"));
+            };
+
+            let mut items = Vec::with_capacity(line_number_table_length as usize);
+            for _ in 0..line_number_table_length {
+                let Some(start_pc) = chunks.next() else {
+                    return Err(cx.length_mismatch("LineNumberTable start_pc"));
+                };
+                let Some(line_number) = chunks.next() else {
+                    return Err(cx.length_mismatch("LineNumberTable line_number"));
+                };
+                items.push(LineNumberEntry {
+                    start_pc,
+                    line_number,
+                });
+            }
+            if chunks.next().is_some() {
+                return Err(cx.length_mismatch("LineNumberTable attribute_length"));
+            }
+
+            AttributeInfo::LineNumberTable(items)
+        }
+
+        "LocalVariableTable" => {
+            let (chunks, []) = info.as_chunks() else {
+                return Err(cx.length_mismatch("LocalVariableTable attribute_length"));
+            };
+            let mut chunks = chunks.iter().map(|v| u16::from_be_bytes(*v));
+            let Some(local_variable_table_length) = chunks.next() else {
+                return Err(cx.length_mismatch("LocalVariableTable local_variable_table_length"));
+            };
+
+            let mut items = Vec::with_capacity(local_variable_table_length as usize);
+            for _ in 0..local_variable_table_length {
+                let (Some(start_pc), Some(length)) = (chunks.next(), chunks.next()) else {
+                    return Err(cx.length_mismatch("LocalVariableTable start_pc"));
+                };
+                let mut utf8 = |context| match chunks.next() {
+                    Some(index) => resolve_utf8(pool, index),
+                    None => Err(cx.length_mismatch(context)),
+                };
+                let name = utf8("LocalVariableTable name_index")?;
+                let descriptor = utf8("LocalVariableTable descriptor_index")?;
+                let Some(slot) = chunks.next() else {
+                    return Err(cx.length_mismatch("LocalVariableTable index"));
+                };
+                items.push(LocalVariable {
+                    start_pc,
+                    length,
+                    name,
+                    descriptor,
+                    slot,
+                });
+            }
+            if chunks.next().is_some() {
+                return Err(cx.length_mismatch("LocalVariableTable attribute_length"));
+            }
+
+            AttributeInfo::LocalVariableTable(items)
+        }
+
+        "StackMapTable" => {
+            AttributeInfo::StackMapTable(stack_map::parse_stack_map_table(cx, |index| {
+                resolve_class(pool, index)
+            })?)
+        }
+
+        "RuntimeVisibleAnnotations" | "RuntimeInvisibleAnnotations" => {
+            let mut resolve = |index: u16, _| parse_cp_info(pool, index);
+            let visible = attribute_name == "RuntimeVisibleAnnotations";
+            let annotations = annotation::parse_annotations(cx, &mut resolve, depth)?;
+            if visible {
+                AttributeInfo::RuntimeVisibleAnnotations(annotations)
+            } else {
+                AttributeInfo::RuntimeInvisibleAnnotations(annotations)
+            }
+        }
+
+        "RuntimeVisibleTypeAnnotations" | "RuntimeInvisibleTypeAnnotations" => {
+            let mut resolve = |index: u16, _| parse_cp_info(pool, index);
+            let visible = attribute_name == "RuntimeVisibleTypeAnnotations";
+            let annotations = annotation::parse_type_annotations(cx, &mut resolve, depth)?;
+            if visible {
+                AttributeInfo::RuntimeVisibleTypeAnnotations(annotations)
+            } else {
+                AttributeInfo::RuntimeInvisibleTypeAnnotations(annotations)
+            }
+        }
+
+        "AnnotationDefault" => {
+            let mut resolve = |index: u16, _| parse_cp_info(pool, index);
+            let input = &mut &info[..];
+            let value = annotation::parse_element_value(input, &mut resolve, cx, depth)?;
+            if !input.is_empty() {
+                return Err(cx.length_mismatch("AnnotationDefault attribute_length"));
+            }
+            AttributeInfo::AnnotationDefault(value)
+        }
+
+        "MethodParameters" => {
+            let Some((&parameters_count, rest)) = info.split_first() else {
+                return Err(cx.length_mismatch("MethodParameters parameters_count"));
+            };
+            let (chunks, []) = rest.as_chunks::<4>() else {
+                return Err(cx.length_mismatch("MethodParameters attribute_length"));
+            };
+            if chunks.len() != parameters_count as usize {
+                return Err(cx.length_mismatch("MethodParameters attribute_length"));
+            }
+
+            let mut items = Vec::with_capacity(chunks.len());
+            for [name_0, name_1, flags_0, flags_1] in chunks {
+                let name_index = u16::from_be_bytes([*name_0, *name_1]);
+                let name = if name_index == 0 {
+                    None
+                } else {
+                    Some(resolve_utf8(pool, name_index)?)
+                };
+                let access_flags =
+                    parse_method_parameter_access_flags(u16::from_be_bytes([*flags_0, *flags_1]));
+                items.push(MethodParameter { name, access_flags });
+            }
+
+            AttributeInfo::MethodParameters(items)
+        }
+
+        "EnclosingMethod" => {
+            let ([class_index, method_index], []) = info.as_chunks() else {
+                return Err(cx.length_mismatch("EnclosingMethod attribute_length"));
+            };
+
+            let class = resolve_class(pool, u16::from_be_bytes(*class_index))?;
+            let method_index = u16::from_be_bytes(*method_index);
+            let method = if method_index == 0 {
+                None
+            } else {
+                Some(resolve_name_and_type(pool, method_index)?)
+            };
+
+            AttributeInfo::EnclosingMethod { class, method }
+        }
+
+        "Record" => {
+            let mut input = info;
+            let components_count = cx.u2(&mut input, "Record components_count")? as usize;
+            // Each component takes at least 6 bytes.
+            let mut components = Vec::with_capacity(components_count.min(input.len() / 6));
+            for _ in 0..components_count {
+                let name = resolve_utf8(pool, cx.u2(&mut input, "Record name_index")?)?;
+                let descriptor = resolve_utf8(pool, cx.u2(&mut input, "Record descriptor_index")?)?;
+                parse_field_descriptor(descriptor)?;
+
+                let attributes_count = cx.u2(&mut input, "Record attributes_count")? as usize;
+                let mut attributes = Vec::with_capacity(attributes_count.min(input.len() / 6));
+                for _ in 0..attributes_count {
+                    // attribute_name_index and attribute_length precede info.
+                    let attribute_offset = offset + 6 + (info.len() - input.len()) as u64;
+                    let attribute_name_index = cx.u2(&mut input, "Record attribute_name_index")?;
+                    let attribute_length = cx.u4(&mut input, "Record attribute_length")? as usize;
+                    let Some((info, rest)) = input.split_at_checked(attribute_length) else {
+                        return Err(cx.length_mismatch("Record attribute_length"));
+                    };
+                    input = rest;
+                    attributes.push(parse_attribute_info(
+                        pool,
+                        names,
+                        raw::nest(depth)?,
+                        attribute_name_index,
+                        attribute_offset,
+                        info,
+                    )?);
+                }
+                components.push(RecordComponent {
+                    name,
+                    descriptor,
+                    attributes: Attributes(attributes),
+                });
+            }
+            if !input.is_empty() {
+                return Err(cx.length_mismatch("Record attribute_length"));
+            }
+            AttributeInfo::Record(components)
+        }
+
+        "NestHost" => {
+            let (&[index], []) = info.as_chunks() else {
+                return Err(cx.length_mismatch("NestHost attribute_length"));
+            };
+            AttributeInfo::NestHost(resolve_class(pool, u16::from_be_bytes(index))?)
+        }
+
+        "NestMembers" => AttributeInfo::NestMembers(parse_class_table(
+            pool,
+            cx,
+            "NestMembers number_of_classes",
+        )?),
+
+        "PermittedSubclasses" => AttributeInfo::PermittedSubclasses(parse_class_table(
+            pool,
+            cx,
+            "PermittedSubclasses number_of_classes",
+        )?),
+
+        "Module" => {
+            let resolve = |index: u16| parse_cp_info(pool, index);
+            AttributeInfo::Module(module::parse_module(cx, resolve)?)
+        }
 
-        _ => AttributeInfo::Unknown(attribute_name, &attribute.info),
-        //name => todo!("{name}"),
+        _ => AttributeInfo::Unknown {
+            name: attribute_name,
+            length: info.len(),
+            offset,
+            info,
+        },
     })
 }
 
-fn parse_field<'a>(
+fn parse_field<'a, 'p: 'a>(
     pool: &'a [Option<raw::CpInfo>],
+    names: &AttributeNames<'p>,
+    depth: usize,
     field: &'a raw::FieldInfo,
 ) -> Result<FieldInfo<&'a str, &'a [u8]>, ParseError> {
-    let access_flags = parse_field_access_flags(field.access_flags)?;
-
-    let Some(name) = pool.get(field.name_index as usize) else {
-        todo!()
-    };
-    let Some(CpInfo::Utf8(name)) = parse_cp_info(pool, name)? else {
-        todo!()
-    };
+    let access_flags = parse_field_access_flags(field.access_flags);
 
-    let Some(descriptor) = pool.get(field.descriptor_index as usize) else {
-        todo!()
-    };
-    let Some(CpInfo::Utf8(descriptor)) = parse_cp_info(pool, descriptor)? else {
-        todo!()
-    };
+    let name = resolve_utf8(pool, field.name_index)?;
+    let descriptor = resolve_utf8(pool, field.descriptor_index)?;
+    parse_field_descriptor(descriptor)?;
 
     let attributes = field
         .attributes
         .iter()
-        .map(|item| parse_attribute_info(pool, item))
-        .collect::<Result<Vec<_>, _>>()?;
+        .map(|item| {
+            parse_attribute_info(
+                pool,
+                names,
+                depth,
+                item.attribute_name_index,
+                item.offset,
+                &item.info,
+            )
+        })
+        .collect::<Result<Attributes<_, _>, _>>()?;
 
     Ok(FieldInfo {
         access_flags,
@@ -883,31 +3135,32 @@ fn parse_field<'a>(
     })
 }
 
-fn parse_method<'a>(
+fn parse_method<'a, 'p: 'a>(
     pool: &'a [Option<raw::CpInfo>],
+    names: &AttributeNames<'p>,
+    depth: usize,
     field: &'a raw::MethodInfo,
 ) -> Result<MethodInfo<&'a str, &'a [u8]>, ParseError> {
-    let access_flags = parse_method_access_flags(field.access_flags)?;
+    let access_flags = parse_method_access_flags(field.access_flags);
 
-    let Some(name) = pool.get(field.name_index as usize) else {
-        todo!()
-    };
-    let Some(CpInfo::Utf8(name)) = parse_cp_info(pool, name)? else {
-        todo!()
-    };
-
-    let Some(descriptor) = pool.get(field.descriptor_index as usize) else {
-        todo!()
-    };
-    let Some(CpInfo::Utf8(descriptor)) = parse_cp_info(pool, descriptor)? else {
-        todo!()
-    };
+    let name = resolve_utf8(pool, field.name_index)?;
+    let descriptor = resolve_utf8(pool, field.descriptor_index)?;
+    parse_method_descriptor(descriptor)?;
 
     let attributes = field
         .attributes
         .iter()
-        .map(|item| parse_attribute_info(pool, item))
-        .collect::<Result<Vec<_>, _>>()?;
+        .map(|item| {
+            parse_attribute_info(
+                pool,
+                names,
+                depth,
+                item.attribute_name_index,
+                item.offset,
+                &item.info,
+            )
+        })
+        .collect::<Result<Attributes<_, _>, _>>()?;
 
     Ok(MethodInfo {
         access_flags,
@@ -917,39 +3170,220 @@ fn parse_method<'a>(
     })
 }
 
+/// A string equal for constants equal once resolved.
+///
+/// `Float` is compared by its bits, as JSON holds neither NaN nor the infinities.
+pub(crate) fn equality_key<S: AsRef<str> + Serialize>(
+    entry: &CpInfo<S>,
+) -> Result<String, ParseError> {
+    match entry {
+        CpInfo::Float(value) => Ok(format!("Float {:#010x}", value.to_bits())),
+        entry => Ok(serde_json::to_string(entry)?),
+    }
+}
+
 pub fn parse_raw<I: io::Read>(input: &mut I) -> Result<raw::ClassFile, ParseError> {
     raw::parse(input)
 }
 
-pub fn wrap<'a>(raw: &'a raw::ClassFile) -> Result<ClassFile<&'a str, &'a [u8]>, ParseError> {
-    if raw.magic != 0xCAFEBABE {
-        panic!("magic != 0xCAFEBABE")
+pub fn parse_raw_with_limits<I: io::Read>(
+    input: &mut I,
+    limits: &ParseLimits,
+) -> Result<raw::ClassFile, ParseError> {
+    raw::parse_with_limits(input, limits)
+}
+
+/// [`parse_raw`] from memory, telling a failure apart as due to a `constant_pool_count` which
+/// leaves out the second slots of `CONSTANT_Long` and `CONSTANT_Double`, with
+/// [`ParseError::ConstantPoolSlotMisalignment`]. Worth trying on the bytes of a class file
+/// which [`parse_raw`] failed on.
+pub fn parse_raw_bytes(bytes: &[u8]) -> Result<raw::ClassFile, ParseError> {
+    raw::parse_bytes_with_limits(bytes, &ParseLimits::default())
+}
+
+/// Read only the flags, names and descriptors of a class and its members, skipping past the
+/// bodies of all attributes without allocating them, for scanning the API of many classes.
+///
+/// Much less is checked than by [`wrap`]; the constant pool is read but not resolved beyond the
+/// names needed.
+pub fn parse_signatures_only<I: io::Read>(input: &mut I) -> Result<SignatureView, ParseError> {
+    view::parse(input)
+}
+
+/// Read a class file handing each field, method and class attribute to `visitor` as soon as it
+/// is read, instead of keeping them all, for classes too large to hold resolved at once.
+///
+/// The class file returned has the constant pool and the header, without any member or
+/// attribute.
+pub fn parse_with_visitor<I: io::Read, V: ClassVisitor + ?Sized>(
+    input: &mut I,
+    visitor: &mut V,
+) -> Result<raw::ClassFile, ParseError> {
+    visitor::parse(input, visitor)
+}
+
+/// Parse a class file encoded in standard base64, as `Unknown` attributes are serialized.
+///
+/// Whitespace is ignored, so that wrapped text can be pasted as it is.
+pub fn parse_from_base64(s: &str) -> Result<raw::ClassFile, ParseError> {
+    let s = s
+        .chars()
+        .filter(|c| !c.is_ascii_whitespace())
+        .collect::<String>();
+    let bytes = base64::engine::general_purpose::STANDARD.decode(s)?;
+    raw::parse(&mut bytes.as_slice())
+}
+
+/// Parse the class file at `name`, e.g. `com/example/Main.class`, within the jar or zip
+/// archive at `path`, without reading the other entries.
+#[cfg(feature = "zip")]
+pub fn parse_from_jar<P: AsRef<std::path::Path>>(
+    path: P,
+    name: &str,
+) -> Result<raw::ClassFile, ParseError> {
+    let file = io::BufReader::new(std::fs::File::open(path)?);
+    let mut archive = zip::ZipArchive::new(file)?;
+    let mut entry = match archive.by_name(name) {
+        Ok(entry) => entry,
+        Err(zip::result::ZipError::FileNotFound) => {
+            return Err(ParseError::EntryNotFound {
+                name: name.to_string(),
+            });
+        }
+        Err(err) => return Err(err.into()),
+    };
+    raw::parse(&mut entry)
+}
+
+/// `this_class` must be a `CONSTANT_Class`, named `module-info` for a module.
+fn validate_this_class(raw: &raw::ClassFile) -> Result<&str, ParseError> {
+    if raw.this_class == 0 {
+        return Err(ParseError::InvalidThisClass { found: "index 0" });
+    }
+    match raw.constant_pool.get(raw.this_class as usize) {
+        Some(Some(raw::CpInfo::Class { .. })) => {}
+        Some(Some(item)) => {
+            return Err(ParseError::InvalidThisClass { found: item.kind() });
+        }
+        // Index out of range, or the unusable slot after a long or double.
+        _ => {
+            return Err(ParseError::InvalidThisClass {
+                found: "no constant",
+            });
+        }
+    };
+    let Some(CpInfo::Class { name }) = parse_cp_info(&raw.constant_pool, raw.this_class)? else {
+        unreachable!()
+    };
+
+    let is_module = raw.access_flags & ClassAccessFlags::AccModule as u16 != 0;
+    if is_module && name != "module-info" {
+        return Err(ParseError::InvalidModuleThisClass {
+            name: name.to_string(),
+        });
     }
+    Ok(name)
+}
 
-    let constant_pool = raw
-        .constant_pool
-        .iter()
-        .map(|item| parse_cp_info(&raw.constant_pool, item))
-        .collect::<Result<Vec<_>, _>>()?;
+pub fn wrap(raw: &raw::ClassFile) -> Result<ClassFile<&str, &[u8]>, ParseError> {
+    wrap_with_options(raw, &ParseOptions::default())
+}
 
-    let access_flags = parse_class_access_flags(raw.access_flags)?;
+pub fn wrap_with_options<'a>(
+    raw: &'a raw::ClassFile,
+    options: &ParseOptions,
+) -> Result<ClassFile<&'a str, &'a [u8]>, ParseError> {
+    if raw.magic != 0xCAFEBABE {
+        return Err(ParseError::BadMagicNumber);
+    }
 
-    let Some(this_class) = raw.constant_pool.get(raw.this_class as usize) else {
-        todo!()
+    let version = ClassFileVersion {
+        major_version: raw.major_version,
+        minor_version: raw.minor_version,
     };
-    let Some(CpInfo::Class { name: this_class }) = parse_cp_info(&raw.constant_pool, this_class)?
-    else {
-        todo!()
+    if options.reject_preview && version.uses_preview_features() {
+        return Err(ParseError::PreviewFeaturesNotAllowed);
+    }
+
+    if raw.constant_pool.is_empty() {
+        return Err(ParseError::EmptyConstantPool);
+    }
+
+    let mut constant_pool = (0..raw.constant_pool.len())
+        .map(|index| parse_cp_info(&raw.constant_pool, index as u16))
+        .collect::<Result<Vec<_>, _>>()?;
+    if options.method_handle_reference_index {
+        for (entry, item) in constant_pool.iter_mut().zip(&raw.constant_pool) {
+            if let (
+                Some(CpInfo::MethodHandle {
+                    reference_index, ..
+                }),
+                Some(raw::CpInfo::MethodHandle {
+                    reference_index: index,
+                    ..
+                }),
+            ) = (entry, item)
+            {
+                *reference_index = Some(*index);
+            }
+        }
+    }
+
+    let constant_pool = ConstantPool {
+        entries: constant_pool,
+        parse_descriptors: options.parse_descriptors,
     };
 
+    let access_flags = parse_class_access_flags(raw.access_flags);
+
+    let this_class = validate_this_class(raw)?;
+
+    let is_interface = raw.access_flags & ClassAccessFlags::AccInterface as u16 != 0;
+    for (index, entry) in constant_pool.iter().enumerate() {
+        let (class, descriptor, interface_method_ref) = match entry {
+            Some(CpInfo::Fieldref { descriptor, .. }) => {
+                parse_field_descriptor(descriptor)?;
+                continue;
+            }
+            Some(CpInfo::MethodType { descriptor }) => {
+                parse_method_descriptor(descriptor)?;
+                continue;
+            }
+            Some(CpInfo::Methodref {
+                class, descriptor, ..
+            }) => (class, descriptor, false),
+            Some(CpInfo::InterfaceMethodref {
+                class, descriptor, ..
+            }) => (class, descriptor, true),
+            _ => continue,
+        };
+        parse_method_descriptor(descriptor)?;
+        if *class == this_class && interface_method_ref != is_interface {
+            return Err(ParseError::MethodRefInterfaceMismatch {
+                index: index as u16,
+                interface_method_ref,
+            });
+        }
+    }
+
     let super_class = if raw.super_class == 0 {
         None
     } else {
-        let Some(super_class) = raw.constant_pool.get(raw.super_class as usize) else {
-            todo!()
+        match raw.constant_pool.get(raw.super_class as usize) {
+            Some(Some(raw::CpInfo::Class { .. })) => {}
+            Some(Some(item)) => {
+                return Err(ParseError::InvalidSuperClass { found: item.kind() });
+            }
+            // Index out of range, or the unusable slot after a long or double.
+            _ => {
+                return Err(ParseError::InvalidSuperClass {
+                    found: "no constant",
+                });
+            }
         };
-        let Some(CpInfo::Class { name }) = parse_cp_info(&raw.constant_pool, super_class)? else {
-            todo!()
+        let Some(CpInfo::Class { name }) = parse_cp_info(&raw.constant_pool, raw.super_class)?
+        else {
+            unreachable!()
         };
         Some(name)
     };
@@ -957,41 +3391,65 @@ pub fn wrap<'a>(raw: &'a raw::ClassFile) -> Result<ClassFile<&'a str, &'a [u8]>,
     let interfaces = raw
         .interfaces
         .iter()
-        .map(|v| {
-            let Some(interface) = raw.constant_pool.get(*v as usize) else {
-                todo!()
-            };
-            let Some(CpInfo::Class { name }) = parse_cp_info(&raw.constant_pool, interface)? else {
-                todo!()
-            };
-            Ok::<&'a str, ParseError>(name)
-        })
+        .map(|v| resolve_class(&raw.constant_pool, *v))
         .collect::<Result<_, _>>()?;
 
+    let names = AttributeNames::new(&raw.constant_pool);
+    let depth = options.max_nesting_depth;
     let fields = raw
         .fields
         .iter()
-        .map(|item| parse_field(&raw.constant_pool, item))
+        .map(|item| parse_field(&raw.constant_pool, &names, depth, item))
         .collect::<Result<Vec<_>, _>>()?;
 
     let methods = raw
         .methods
         .iter()
-        .map(|item| parse_method(&raw.constant_pool, item))
+        .map(|item| parse_method(&raw.constant_pool, &names, depth, item))
         .collect::<Result<Vec<_>, _>>()?;
 
     let attributes = raw
         .attributes
         .iter()
-        .map(|item| parse_attribute_info(&raw.constant_pool, item))
-        .collect::<Result<Vec<_>, _>>()?;
+        .map(|item| {
+            parse_attribute_info(
+                &raw.constant_pool,
+                &names,
+                depth,
+                item.attribute_name_index,
+                item.offset,
+                &item.info,
+            )
+        })
+        .collect::<Result<Attributes<_, _>, _>>()?;
+
+    for item in constant_pool.iter().flatten() {
+        if let CpInfo::Dynamic {
+            bootstrap_method_attr,
+            ..
+        }
+        | CpInfo::InvokeDynamic {
+            bootstrap_method_attr,
+            ..
+        } = item
+        {
+            attributes.bootstrap_method(*bootstrap_method_attr)?;
+        }
+    }
+
+    if let Some(target) = options.max_java_version {
+        let members = fields
+            .iter()
+            .map(|field| &field.attributes)
+            .chain(methods.iter().map(|method| &method.attributes));
+        for attributes in members.chain([&attributes]) {
+            check_attribute_versions(attributes, target)?;
+        }
+    }
 
     Ok(ClassFile {
-        magic: Magic,
-        version: ClassFileVersion {
-            major_version: raw.major_version,
-            minor_version: raw.minor_version,
-        },
+        magic: (!options.omit_magic).then_some(Magic),
+        version,
         constant_pool,
         access_flags,
         this_class,
@@ -1000,6 +3458,7 @@ pub fn wrap<'a>(raw: &'a raw::ClassFile) -> Result<ClassFile<&'a str, &'a [u8]>,
         fields,
         methods,
         attributes,
+        hex_unknown: options.hex_unknown,
     })
 }
 
@@ -1014,7 +3473,7 @@ pub extern "C" fn parse() -> std::ffi::c_int {
         Err(err) => {
             eprintln!("{err}");
             return 1;
-        },
+        }
     };
 
     let data = match wrap(&raw) {
@@ -1022,24 +3481,70 @@ pub extern "C" fn parse() -> std::ffi::c_int {
         Err(err) => {
             eprintln!("{err}");
             return 1;
-        },
+        }
     };
 
     match serde_json::to_writer(&mut stdout, &data) {
-        Ok(..) => {},
+        Ok(..) => {}
         Err(err) => {
             eprintln!("{err}");
             return 1;
-        },
+        }
     };
 
     match stdout.flush() {
-        Ok(..) => {},
+        Ok(..) => {}
         Err(err) => {
             eprintln!("{err}");
             return 1;
-        },
+        }
+    };
+
+    0
+}
+
+/// Like [`parse`], but reads the class file from `in_ptr` and writes the JSON to `out_ptr`.
+///
+/// Returns 0 with the length of the JSON in `out_len_written` on success, 1 when the class file
+/// cannot be parsed, and 2 with the required capacity in `out_len_written` when `out_cap` is too
+/// small, in which case nothing is written.
+///
+/// # Safety
+///
+/// `in_ptr` must be valid for reads of `in_len` bytes, `out_ptr` for writes of `out_cap` bytes,
+/// and `out_len_written` for a write. Either pointer of a buffer may be null if its size is 0.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn parse_to_buffer(
+    in_ptr: *const u8,
+    in_len: usize,
+    out_ptr: *mut u8,
+    out_cap: usize,
+    out_len_written: *mut usize,
+) -> std::ffi::c_int {
+    let input = if in_len == 0 {
+        &[][..]
+    } else {
+        // SAFETY: guaranteed by the caller.
+        unsafe { std::slice::from_raw_parts(in_ptr, in_len) }
+    };
+
+    let to_json = |mut input: &[u8]| -> Result<Vec<u8>, ParseError> {
+        let raw = parse_raw(&mut input)?;
+        Ok(serde_json::to_vec(&wrap(&raw)?)?)
     };
+    let Ok(json) = to_json(input) else {
+        return 1;
+    };
+
+    // SAFETY: guaranteed by the caller.
+    unsafe { out_len_written.write(json.len()) };
+    if json.len() > out_cap {
+        return 2;
+    }
+    if !json.is_empty() {
+        // SAFETY: guaranteed by the caller, and `json` fits in `out_cap` bytes.
+        unsafe { std::ptr::copy_nonoverlapping(json.as_ptr(), out_ptr, json.len()) };
+    }
 
-    return 0;
+    0
 }
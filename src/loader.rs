@@ -0,0 +1,130 @@
+/// Resolves classes referenced by name against a class path.
+use std::collections::{HashSet, VecDeque};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use serde::Serialize;
+
+use crate::MethodAccessFlags;
+use crate::raw::{self, ParseError};
+
+/// Whether a method referred to by an instruction exists on the class path.
+#[derive(Debug, Clone, Serialize)]
+pub enum Linkage {
+    /// Declared by `class`, the referenced class or one of its supertypes.
+    Found {
+        class: String,
+        access_flags: Vec<MethodAccessFlags>,
+    },
+    /// Declared by neither the referenced class nor any of its supertypes.
+    NotFound,
+    /// `class`, the referenced class or one of its supertypes, is not on the class path.
+    Unknown { class: String },
+}
+
+/// Looks classes up in a list of class path directories, in order.
+#[derive(Debug, Clone)]
+pub struct ClassLoader {
+    roots: Vec<PathBuf>,
+}
+
+impl ClassLoader {
+    pub fn new<I: IntoIterator<Item = P>, P: Into<PathBuf>>(roots: I) -> Self {
+        Self {
+            roots: roots.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    pub fn roots(&self) -> &[PathBuf] {
+        &self.roots
+    }
+
+    /// The class file of the class with internal name `name`, e.g. `com/example/Util`.
+    ///
+    /// Names with empty, `.` or `..` segments are never found, so that a lookup cannot escape
+    /// the class path.
+    pub fn find(&self, name: &str) -> Option<PathBuf> {
+        if name
+            .split('/')
+            .any(|segment| matches!(segment, "" | "." | ".."))
+        {
+            return None;
+        }
+
+        let relative = PathBuf::from(format!("{name}.class"));
+        self.roots
+            .iter()
+            .map(|root| root.join(&relative))
+            .find(|path| path.is_file())
+    }
+
+    /// Parse the class with internal name `name`, or `None` when it is not on the class path.
+    ///
+    /// The result is resolved with [`crate::wrap`] like the one of [`crate::parse_raw`].
+    pub fn load(&self, name: &str) -> Result<Option<raw::ClassFile>, ParseError> {
+        let Some(path) = self.find(name) else {
+            return Ok(None);
+        };
+        let mut input = io::BufReader::new(fs::File::open(path)?);
+        Ok(Some(raw::parse(&mut input)?))
+    }
+
+    /// Look method `name` with `descriptor` up in `class` and its supertypes.
+    ///
+    /// Superclasses are searched before superinterfaces. Array classes resolve against
+    /// `java/lang/Object`. A class extending one of its own subclasses, which no JVM would load,
+    /// ends the search with [`Linkage::NotFound`].
+    pub fn resolve_method(
+        &self,
+        class: &str,
+        name: &str,
+        descriptor: &str,
+    ) -> Result<Linkage, ParseError> {
+        let class = if class.starts_with('[') {
+            "java/lang/Object"
+        } else {
+            class
+        };
+
+        let mut interfaces = VecDeque::new();
+        let mut seen = HashSet::new();
+        let mut next = Some(class.to_string());
+        while let Some(class) = next.take() {
+            if !seen.insert(class.clone()) {
+                return Ok(Linkage::NotFound);
+            }
+            let Some(raw) = self.load(&class)? else {
+                return Ok(Linkage::Unknown { class });
+            };
+            let data = crate::wrap(&raw)?;
+            if let Some(method) = data.find_method(name, Some(descriptor)) {
+                return Ok(Linkage::Found {
+                    class,
+                    access_flags: method.access_flags.clone(),
+                });
+            }
+            interfaces.extend(data.interfaces.iter().map(|i| i.to_string()));
+            next = data.super_class.map(|super_class| super_class.to_string());
+        }
+
+        while let Some(class) = interfaces.pop_front() {
+            if !seen.insert(class.clone()) {
+                continue;
+            }
+            let Some(raw) = self.load(&class)? else {
+                return Ok(Linkage::Unknown { class });
+            };
+            let data = crate::wrap(&raw)?;
+            if let Some(method) = data.find_method(name, Some(descriptor)) {
+                return Ok(Linkage::Found {
+                    class,
+                    access_flags: method.access_flags.clone(),
+                });
+            }
+            interfaces.extend(data.interfaces.iter().map(|i| i.to_string()));
+        }
+
+        Ok(Linkage::NotFound)
+    }
+}
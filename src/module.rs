@@ -0,0 +1,434 @@
+/// https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-4.html#jvms-4.7.25
+use serde::Serialize;
+
+use crate::CpInfo;
+use crate::owned::{IntoOwned, owned_str, owned_strs};
+use crate::raw::{AttributeContext, ParseError};
+
+/// The `Module` attribute of `module-info.class`.
+#[derive(Debug, Serialize)]
+pub struct ModuleAttribute<S: AsRef<str>> {
+    name: S,
+    flags: Vec<ModuleAccessFlags>,
+    version: Option<S>,
+    requires: Vec<ModuleRequires<S>>,
+    exports: Vec<ModuleExports<S>>,
+    opens: Vec<ModuleOpens<S>>,
+    /// Internal names of the service interfaces.
+    uses: Vec<S>,
+    provides: Vec<ModuleProvides<S>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ModuleRequires<S: AsRef<str>> {
+    module: S,
+    flags: Vec<RequiresAccessFlags>,
+    /// Version of the module at compile time.
+    version: Option<S>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ModuleExports<S: AsRef<str>> {
+    /// Internal name of the package, e.g. `com/example`.
+    package: S,
+    flags: Vec<ExportsAccessFlags>,
+    /// Modules the package is exported to, all of them when empty.
+    to: Vec<S>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ModuleOpens<S: AsRef<str>> {
+    /// Internal name of the package, e.g. `com/example`.
+    package: S,
+    flags: Vec<ExportsAccessFlags>,
+    /// Modules the package is opened to, all of them when empty.
+    to: Vec<S>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ModuleProvides<S: AsRef<str>> {
+    /// Internal name of the service interface.
+    service: S,
+    /// Internal names of the implementations, in file order.
+    with: Vec<S>,
+}
+
+#[repr(u16)]
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+pub enum ModuleAccessFlags {
+    AccOpen = 0x0020,
+    AccSynthetic = 0x1000,
+    AccMandated = 0x8000,
+}
+
+impl ModuleAccessFlags {
+    const VALUES: [Self; 3] = [Self::AccOpen, Self::AccSynthetic, Self::AccMandated];
+}
+
+/// `AccStaticPhase` marks a dependence mandatory at compile time only, `requires static`.
+#[repr(u16)]
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+pub enum RequiresAccessFlags {
+    AccTransitive = 0x0020,
+    AccStaticPhase = 0x0040,
+    AccSynthetic = 0x1000,
+    AccMandated = 0x8000,
+}
+
+impl RequiresAccessFlags {
+    const VALUES: [Self; 4] = [
+        Self::AccTransitive,
+        Self::AccStaticPhase,
+        Self::AccSynthetic,
+        Self::AccMandated,
+    ];
+}
+
+/// Flags of both `exports` and `opens`.
+#[repr(u16)]
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+pub enum ExportsAccessFlags {
+    AccSynthetic = 0x1000,
+    AccMandated = 0x8000,
+}
+
+impl ExportsAccessFlags {
+    const VALUES: [Self; 2] = [Self::AccSynthetic, Self::AccMandated];
+}
+
+impl<S: AsRef<str>> ModuleAttribute<S> {
+    /// The module name, e.g. `java.base`.
+    pub fn name(&self) -> &str {
+        self.name.as_ref()
+    }
+
+    pub fn flags(&self) -> &[ModuleAccessFlags] {
+        &self.flags
+    }
+
+    pub fn version(&self) -> Option<&str> {
+        self.version.as_ref().map(AsRef::as_ref)
+    }
+
+    pub fn requires(&self) -> &[ModuleRequires<S>] {
+        &self.requires
+    }
+
+    pub fn exports(&self) -> &[ModuleExports<S>] {
+        &self.exports
+    }
+
+    pub fn opens(&self) -> &[ModuleOpens<S>] {
+        &self.opens
+    }
+
+    pub fn uses(&self) -> &[S] {
+        &self.uses
+    }
+
+    pub fn provides(&self) -> &[ModuleProvides<S>] {
+        &self.provides
+    }
+}
+
+impl<S: AsRef<str>> ModuleRequires<S> {
+    pub fn module(&self) -> &str {
+        self.module.as_ref()
+    }
+
+    pub fn flags(&self) -> &[RequiresAccessFlags] {
+        &self.flags
+    }
+
+    pub fn version(&self) -> Option<&str> {
+        self.version.as_ref().map(AsRef::as_ref)
+    }
+}
+
+impl<S: AsRef<str>> ModuleExports<S> {
+    pub fn package(&self) -> &str {
+        self.package.as_ref()
+    }
+
+    pub fn flags(&self) -> &[ExportsAccessFlags] {
+        &self.flags
+    }
+
+    pub fn to(&self) -> &[S] {
+        &self.to
+    }
+}
+
+impl<S: AsRef<str>> ModuleOpens<S> {
+    pub fn package(&self) -> &str {
+        self.package.as_ref()
+    }
+
+    pub fn flags(&self) -> &[ExportsAccessFlags] {
+        &self.flags
+    }
+
+    pub fn to(&self) -> &[S] {
+        &self.to
+    }
+}
+
+impl<S: AsRef<str>> ModuleProvides<S> {
+    pub fn service(&self) -> &str {
+        self.service.as_ref()
+    }
+
+    pub fn with(&self) -> &[S] {
+        &self.with
+    }
+}
+
+fn parse_flags<F: Copy>(
+    flags: u16,
+    values: &[F],
+    bit: fn(F) -> u16,
+    context: &'static str,
+) -> Result<Vec<F>, ParseError> {
+    let found = values
+        .iter()
+        .copied()
+        .filter(|value| flags & bit(*value) != 0)
+        .collect::<Vec<_>>();
+    if found.iter().fold(0, |wants, value| wants | bit(*value)) != flags {
+        return Err(crate::raw::malformed(
+            "Module",
+            format!("{context} {flags:#06x}"),
+        ));
+    }
+    Ok(found)
+}
+
+/// Reads constant pool indexes of the `info` of a `Module` attribute, resolving them with
+/// `resolve`, which gives `None` for index 0 and the unusable slots.
+struct Reader<'a, R> {
+    cx: AttributeContext<'a>,
+    input: &'a [u8],
+    resolve: R,
+}
+
+impl<S: AsRef<str>, R: FnMut(u16) -> Result<Option<CpInfo<S>>, ParseError>> Reader<'_, R> {
+    fn u2(&mut self, context: &'static str) -> Result<u16, ParseError> {
+        self.cx.u2(&mut self.input, context)
+    }
+
+    /// Items preceded by their `u2` count.
+    fn items<T>(
+        &mut self,
+        context: &'static str,
+        mut item: impl FnMut(&mut Self) -> Result<T, ParseError>,
+    ) -> Result<Vec<T>, ParseError> {
+        let count = self.u2(context)?;
+        let mut items = Vec::with_capacity((count as usize).min(self.input.len() / 2));
+        for _ in 0..count {
+            items.push(item(self)?);
+        }
+        Ok(items)
+    }
+
+    fn module(&mut self, context: &'static str) -> Result<S, ParseError> {
+        let index = self.u2(context)?;
+        match (self.resolve)(index)? {
+            Some(CpInfo::Module { name }) => Ok(name),
+            _ => Err(ParseError::UnexpectedCpType {
+                index,
+                expected: "CONSTANT_Module",
+            }),
+        }
+    }
+
+    fn package(&mut self, context: &'static str) -> Result<S, ParseError> {
+        let index = self.u2(context)?;
+        match (self.resolve)(index)? {
+            Some(CpInfo::Package { name }) => Ok(name),
+            _ => Err(ParseError::UnexpectedCpType {
+                index,
+                expected: "CONSTANT_Package",
+            }),
+        }
+    }
+
+    fn class(&mut self, context: &'static str) -> Result<S, ParseError> {
+        let index = self.u2(context)?;
+        match (self.resolve)(index)? {
+            Some(CpInfo::Class { name }) => Ok(name),
+            _ => Err(ParseError::UnexpectedCpType {
+                index,
+                expected: "CONSTANT_Class",
+            }),
+        }
+    }
+
+    /// A `CONSTANT_Utf8`, or `None` for index 0.
+    fn version(&mut self, context: &'static str) -> Result<Option<S>, ParseError> {
+        let index = self.u2(context)?;
+        if index == 0 {
+            return Ok(None);
+        }
+        match (self.resolve)(index)? {
+            Some(CpInfo::Utf8(version)) => Ok(Some(version)),
+            _ => Err(ParseError::UnexpectedCpType {
+                index,
+                expected: "CONSTANT_Utf8",
+            }),
+        }
+    }
+}
+
+/// Decode the `info` of a `Module` attribute.
+pub(crate) fn parse_module<S: AsRef<str>>(
+    cx: AttributeContext<'_>,
+    resolve: impl FnMut(u16) -> Result<Option<CpInfo<S>>, ParseError>,
+) -> Result<ModuleAttribute<S>, ParseError> {
+    let mut input = Reader {
+        cx,
+        input: cx.info,
+        resolve,
+    };
+    let name = input.module("Module module_name_index")?;
+    let flags = parse_flags(
+        input.u2("Module module_flags")?,
+        &ModuleAccessFlags::VALUES,
+        |flag| flag as u16,
+        "module_flags",
+    )?;
+    let version = input.version("Module module_version_index")?;
+
+    let requires = input.items("Module requires_count", |input| {
+        Ok(ModuleRequires {
+            module: input.module("Module requires_index")?,
+            flags: parse_flags(
+                input.u2("Module requires_flags")?,
+                &RequiresAccessFlags::VALUES,
+                |flag| flag as u16,
+                "requires_flags",
+            )?,
+            version: input.version("Module requires_version_index")?,
+        })
+    })?;
+
+    let exports = input.items("Module exports_count", |input| {
+        Ok(ModuleExports {
+            package: input.package("Module exports_index")?,
+            flags: parse_flags(
+                input.u2("Module exports_flags")?,
+                &ExportsAccessFlags::VALUES,
+                |flag| flag as u16,
+                "exports_flags",
+            )?,
+            to: input.items("Module exports_to_count", |input| {
+                input.module("Module exports_to_index")
+            })?,
+        })
+    })?;
+
+    let opens = input.items("Module opens_count", |input| {
+        Ok(ModuleOpens {
+            package: input.package("Module opens_index")?,
+            flags: parse_flags(
+                input.u2("Module opens_flags")?,
+                &ExportsAccessFlags::VALUES,
+                |flag| flag as u16,
+                "opens_flags",
+            )?,
+            to: input.items("Module opens_to_count", |input| {
+                input.module("Module opens_to_index")
+            })?,
+        })
+    })?;
+
+    let uses = input.items("Module uses_count", |input| {
+        input.class("Module uses_index")
+    })?;
+
+    let provides = input.items("Module provides_count", |input| {
+        Ok(ModuleProvides {
+            service: input.class("Module provides_index")?,
+            with: input.items("Module provides_with_count", |input| {
+                input.class("Module provides_with_index")
+            })?,
+        })
+    })?;
+
+    if !input.input.is_empty() {
+        return Err(cx.length_mismatch("Module attribute_length"));
+    }
+    Ok(ModuleAttribute {
+        name,
+        flags,
+        version,
+        requires,
+        exports,
+        opens,
+        uses,
+        provides,
+    })
+}
+
+impl<S: AsRef<str>> IntoOwned for ModuleAttribute<S> {
+    type Owned = ModuleAttribute<String>;
+
+    fn into_owned(self) -> Self::Owned {
+        ModuleAttribute {
+            name: owned_str(self.name),
+            flags: self.flags,
+            version: self.version.map(owned_str),
+            requires: self.requires.into_owned(),
+            exports: self.exports.into_owned(),
+            opens: self.opens.into_owned(),
+            uses: owned_strs(self.uses),
+            provides: self.provides.into_owned(),
+        }
+    }
+}
+
+impl<S: AsRef<str>> IntoOwned for ModuleRequires<S> {
+    type Owned = ModuleRequires<String>;
+
+    fn into_owned(self) -> Self::Owned {
+        ModuleRequires {
+            module: owned_str(self.module),
+            flags: self.flags,
+            version: self.version.map(owned_str),
+        }
+    }
+}
+
+impl<S: AsRef<str>> IntoOwned for ModuleExports<S> {
+    type Owned = ModuleExports<String>;
+
+    fn into_owned(self) -> Self::Owned {
+        ModuleExports {
+            package: owned_str(self.package),
+            flags: self.flags,
+            to: owned_strs(self.to),
+        }
+    }
+}
+
+impl<S: AsRef<str>> IntoOwned for ModuleOpens<S> {
+    type Owned = ModuleOpens<String>;
+
+    fn into_owned(self) -> Self::Owned {
+        ModuleOpens {
+            package: owned_str(self.package),
+            flags: self.flags,
+            to: owned_strs(self.to),
+        }
+    }
+}
+
+impl<S: AsRef<str>> IntoOwned for ModuleProvides<S> {
+    type Owned = ModuleProvides<String>;
+
+    fn into_owned(self) -> Self::Owned {
+        ModuleProvides {
+            service: owned_str(self.service),
+            with: owned_strs(self.with),
+        }
+    }
+}
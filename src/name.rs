@@ -0,0 +1,32 @@
+/// https://docs.oracle.com/javase/specs/jls/se25/html/jls-13.html#jls-13.1
+///
+/// Split the internal name of a nested class, e.g. `com/example/Outer$Inner$Leaf`, into the
+/// internal name of its enclosing class, `com/example/Outer$Inner`, and its simple name, `Leaf`.
+///
+/// The binary name of a nested class is the one of its enclosing class, `$` and its simple
+/// name, but `$` is also legal within identifiers. A `$` at either end of the class name, or
+/// next to another `$`, as in `$Proxy1` or `Outer$$Lambda`, is taken as part of an identifier.
+/// Classes named with `$` otherwise, e.g. a top-level `My$Class`, cannot be told apart from
+/// nested ones by the name alone; the `InnerClasses` attribute has the actual nesting.
+///
+/// Anonymous classes keep their number as simple name, e.g. `1` for `Outer$1`, while the number
+/// in front of the name of local classes is left out, e.g. `Local` for `Outer$1Local`.
+///
+/// The enclosing class is `None` for top-level classes, the simple name being the class name
+/// without the package.
+pub fn split_nested_name(internal: &str) -> (Option<&str>, &str) {
+    let start = internal.rfind('/').map_or(0, |slash| slash + 1);
+    let name = &internal.as_bytes()[start..];
+    let separator = (1..name.len().saturating_sub(1))
+        .rev()
+        .find(|&i| name[i] == b'$' && name[i - 1] != b'$' && name[i + 1] != b'$');
+    let Some(separator) = separator else {
+        return (None, &internal[start..]);
+    };
+
+    let enclosing = &internal[..start + separator];
+    let simple = &internal[start + separator + 1..];
+    let local = simple.trim_start_matches(|c: char| c.is_ascii_digit());
+    let simple = if local.is_empty() { simple } else { local };
+    (Some(enclosing), simple)
+}
@@ -0,0 +1,369 @@
+/// Conversion of a class file borrowing from its `raw::ClassFile` into one owning its data.
+use crate::{
+    AttributeInfo, Attributes, BootstrapMethod, ClassFile, CodeAttribute, ConstantPool,
+    ConstantValueAttribute, CpInfo, ExceptionHandler, FieldInfo, InnerClass, LocalVariable,
+    MethodInfo, MethodParameter, RecordComponent,
+};
+
+/// The same value with `String` in place of every string and `Vec<u8>` of every byte slice.
+pub(crate) trait IntoOwned {
+    type Owned;
+
+    fn into_owned(self) -> Self::Owned;
+}
+
+pub(crate) fn owned_str<S: AsRef<str>>(s: S) -> String {
+    s.as_ref().to_owned()
+}
+
+pub(crate) fn owned_strs<S: AsRef<str>>(strs: Vec<S>) -> Vec<String> {
+    strs.into_iter().map(owned_str).collect()
+}
+
+impl<T: IntoOwned> IntoOwned for Vec<T> {
+    type Owned = Vec<T::Owned>;
+
+    fn into_owned(self) -> Self::Owned {
+        self.into_iter().map(IntoOwned::into_owned).collect()
+    }
+}
+
+impl<T: IntoOwned> IntoOwned for Option<T> {
+    type Owned = Option<T::Owned>;
+
+    fn into_owned(self) -> Self::Owned {
+        self.map(IntoOwned::into_owned)
+    }
+}
+
+impl<T: IntoOwned> IntoOwned for Box<T> {
+    type Owned = Box<T::Owned>;
+
+    fn into_owned(self) -> Self::Owned {
+        Box::new((*self).into_owned())
+    }
+}
+
+impl<S: AsRef<str>> IntoOwned for CpInfo<S> {
+    type Owned = CpInfo<String>;
+
+    fn into_owned(self) -> Self::Owned {
+        match self {
+            Self::Utf8(value) => CpInfo::Utf8(owned_str(value)),
+            Self::Integer(value) => CpInfo::Integer(value),
+            Self::Float(value) => CpInfo::Float(value),
+            Self::Long(value) => CpInfo::Long(value),
+            Self::Double(value) => CpInfo::Double(value),
+            Self::Class { name } => CpInfo::Class {
+                name: owned_str(name),
+            },
+            Self::String { string } => CpInfo::String {
+                string: owned_str(string),
+            },
+            Self::Fieldref {
+                class,
+                name,
+                descriptor,
+            } => CpInfo::Fieldref {
+                class: owned_str(class),
+                name: owned_str(name),
+                descriptor: owned_str(descriptor),
+            },
+            Self::Methodref {
+                class,
+                name,
+                descriptor,
+            } => CpInfo::Methodref {
+                class: owned_str(class),
+                name: owned_str(name),
+                descriptor: owned_str(descriptor),
+            },
+            Self::InterfaceMethodref {
+                class,
+                name,
+                descriptor,
+            } => CpInfo::InterfaceMethodref {
+                class: owned_str(class),
+                name: owned_str(name),
+                descriptor: owned_str(descriptor),
+            },
+            Self::NameAndType { name, descriptor } => CpInfo::NameAndType {
+                name: owned_str(name),
+                descriptor: owned_str(descriptor),
+            },
+            Self::MethodHandle {
+                reference_kind,
+                class,
+                name,
+                descriptor,
+                reference_index,
+            } => CpInfo::MethodHandle {
+                reference_kind,
+                class: owned_str(class),
+                name: owned_str(name),
+                descriptor: owned_str(descriptor),
+                reference_index,
+            },
+            Self::MethodType { descriptor } => CpInfo::MethodType {
+                descriptor: owned_str(descriptor),
+            },
+            Self::Dynamic {
+                bootstrap_method_attr,
+                name,
+                descriptor,
+            } => CpInfo::Dynamic {
+                bootstrap_method_attr,
+                name: owned_str(name),
+                descriptor: owned_str(descriptor),
+            },
+            Self::InvokeDynamic {
+                bootstrap_method_attr,
+                name,
+                descriptor,
+            } => CpInfo::InvokeDynamic {
+                bootstrap_method_attr,
+                name: owned_str(name),
+                descriptor: owned_str(descriptor),
+            },
+            Self::Module { name } => CpInfo::Module {
+                name: owned_str(name),
+            },
+            Self::Package { name } => CpInfo::Package {
+                name: owned_str(name),
+            },
+        }
+    }
+}
+
+impl<S: AsRef<str>> IntoOwned for BootstrapMethod<S> {
+    type Owned = BootstrapMethod<String>;
+
+    fn into_owned(self) -> Self::Owned {
+        BootstrapMethod {
+            reference_kind: self.reference_kind,
+            class: owned_str(self.class),
+            name: owned_str(self.name),
+            descriptor: owned_str(self.descriptor),
+            bootstrap_arguments: self.bootstrap_arguments.into_owned(),
+        }
+    }
+}
+
+impl<S: AsRef<str>> IntoOwned for ConstantValueAttribute<S> {
+    type Owned = ConstantValueAttribute<String>;
+
+    fn into_owned(self) -> Self::Owned {
+        match self {
+            Self::Integer(value) => ConstantValueAttribute::Integer(value),
+            Self::Float(value) => ConstantValueAttribute::Float(value),
+            Self::Long(value) => ConstantValueAttribute::Long(value),
+            Self::Double(value) => ConstantValueAttribute::Double(value),
+            Self::String(value) => ConstantValueAttribute::String(owned_str(value)),
+        }
+    }
+}
+
+impl<S: AsRef<str>> IntoOwned for InnerClass<S> {
+    type Owned = InnerClass<String>;
+
+    fn into_owned(self) -> Self::Owned {
+        InnerClass {
+            inner_class_info: owned_str(self.inner_class_info),
+            outer_class_info: self.outer_class_info.map(owned_str),
+            inner_name: self.inner_name.map(owned_str),
+            inner_class_access_flags: self.inner_class_access_flags,
+        }
+    }
+}
+
+impl<S: AsRef<str>> IntoOwned for MethodParameter<S> {
+    type Owned = MethodParameter<String>;
+
+    fn into_owned(self) -> Self::Owned {
+        MethodParameter {
+            name: self.name.map(owned_str),
+            access_flags: self.access_flags,
+        }
+    }
+}
+
+impl<S: AsRef<str>> IntoOwned for ExceptionHandler<S> {
+    type Owned = ExceptionHandler<String>;
+
+    fn into_owned(self) -> Self::Owned {
+        ExceptionHandler {
+            start_pc: self.start_pc,
+            end_pc: self.end_pc,
+            handler_pc: self.handler_pc,
+            catch_type: self.catch_type.map(owned_str),
+        }
+    }
+}
+
+impl<S: AsRef<str>, B: AsRef<[u8]>> IntoOwned for CodeAttribute<S, B> {
+    type Owned = CodeAttribute<String, Vec<u8>>;
+
+    fn into_owned(self) -> Self::Owned {
+        CodeAttribute {
+            max_stack: self.max_stack,
+            max_locals: self.max_locals,
+            bytecode: self.bytecode.as_ref().to_vec(),
+            exception_table: self.exception_table.into_owned(),
+            attributes: self.attributes.into_owned(),
+        }
+    }
+}
+
+impl<S: AsRef<str>> IntoOwned for LocalVariable<S> {
+    type Owned = LocalVariable<String>;
+
+    fn into_owned(self) -> Self::Owned {
+        LocalVariable {
+            start_pc: self.start_pc,
+            length: self.length,
+            name: owned_str(self.name),
+            descriptor: owned_str(self.descriptor),
+            slot: self.slot,
+        }
+    }
+}
+
+impl<S: AsRef<str>, B: AsRef<[u8]>> IntoOwned for RecordComponent<S, B> {
+    type Owned = RecordComponent<String, Vec<u8>>;
+
+    fn into_owned(self) -> Self::Owned {
+        RecordComponent {
+            name: owned_str(self.name),
+            descriptor: owned_str(self.descriptor),
+            attributes: self.attributes.into_owned(),
+        }
+    }
+}
+
+impl<S: AsRef<str>, B: AsRef<[u8]>> IntoOwned for AttributeInfo<S, B> {
+    type Owned = AttributeInfo<String, Vec<u8>>;
+
+    fn into_owned(self) -> Self::Owned {
+        match self {
+            Self::ConstantValue(value) => AttributeInfo::ConstantValue(value.into_owned()),
+            Self::Code(code) => AttributeInfo::Code(code.into_owned()),
+            Self::Exceptions(classes) => AttributeInfo::Exceptions(owned_strs(classes)),
+            Self::SourceFile(name) => AttributeInfo::SourceFile(owned_str(name)),
+            Self::Signature(signature) => AttributeInfo::Signature(owned_str(signature)),
+            Self::BootstrapMethods(methods) => {
+                AttributeInfo::BootstrapMethods(methods.into_owned())
+            }
+            Self::InnerClasses(classes) => AttributeInfo::InnerClasses(classes.into_owned()),
+            Self::LineNumberTable(entries) => AttributeInfo::LineNumberTable(entries),
+            Self::LocalVariableTable(entries) => {
+                AttributeInfo::LocalVariableTable(entries.into_owned())
+            }
+            Self::StackMapTable(frames) => AttributeInfo::StackMapTable(frames.into_owned()),
+            Self::RuntimeVisibleAnnotations(annotations) => {
+                AttributeInfo::RuntimeVisibleAnnotations(annotations.into_owned())
+            }
+            Self::RuntimeInvisibleAnnotations(annotations) => {
+                AttributeInfo::RuntimeInvisibleAnnotations(annotations.into_owned())
+            }
+            Self::RuntimeVisibleTypeAnnotations(annotations) => {
+                AttributeInfo::RuntimeVisibleTypeAnnotations(annotations.into_owned())
+            }
+            Self::RuntimeInvisibleTypeAnnotations(annotations) => {
+                AttributeInfo::RuntimeInvisibleTypeAnnotations(annotations.into_owned())
+            }
+            Self::AnnotationDefault(value) => AttributeInfo::AnnotationDefault(value.into_owned()),
+            Self::MethodParameters(parameters) => {
+                AttributeInfo::MethodParameters(parameters.into_owned())
+            }
+            Self::EnclosingMethod { class, method } => AttributeInfo::EnclosingMethod {
+                class: owned_str(class),
+                method: method.map(|(name, descriptor)| (owned_str(name), owned_str(descriptor))),
+            },
+            Self::Module(module) => AttributeInfo::Module(module.into_owned()),
+            Self::Record(components) => AttributeInfo::Record(components.into_owned()),
+            Self::NestHost(class) => AttributeInfo::NestHost(owned_str(class)),
+            Self::NestMembers(classes) => AttributeInfo::NestMembers(owned_strs(classes)),
+            Self::PermittedSubclasses(classes) => {
+                AttributeInfo::PermittedSubclasses(owned_strs(classes))
+            }
+            Self::Deprecated => AttributeInfo::Deprecated,
+            Self::Synthetic => AttributeInfo::Synthetic,
+            Self::Unknown {
+                name,
+                length,
+                offset,
+                info,
+            } => AttributeInfo::Unknown {
+                name: owned_str(name),
+                length,
+                offset,
+                info: info.as_ref().to_vec(),
+            },
+        }
+    }
+}
+
+impl<S: AsRef<str>, B: AsRef<[u8]>> IntoOwned for Attributes<S, B> {
+    type Owned = Attributes<String, Vec<u8>>;
+
+    fn into_owned(self) -> Self::Owned {
+        Attributes(self.0.into_owned())
+    }
+}
+
+impl<S: AsRef<str>, B: AsRef<[u8]>> IntoOwned for FieldInfo<S, B> {
+    type Owned = FieldInfo<String, Vec<u8>>;
+
+    fn into_owned(self) -> Self::Owned {
+        FieldInfo {
+            access_flags: self.access_flags,
+            name: owned_str(self.name),
+            descriptor: owned_str(self.descriptor),
+            attributes: self.attributes.into_owned(),
+        }
+    }
+}
+
+impl<S: AsRef<str>, B: AsRef<[u8]>> IntoOwned for MethodInfo<S, B> {
+    type Owned = MethodInfo<String, Vec<u8>>;
+
+    fn into_owned(self) -> Self::Owned {
+        MethodInfo {
+            access_flags: self.access_flags,
+            name: owned_str(self.name),
+            descriptor: owned_str(self.descriptor),
+            attributes: self.attributes.into_owned(),
+        }
+    }
+}
+
+impl<S: AsRef<str>> IntoOwned for ConstantPool<S> {
+    type Owned = ConstantPool<String>;
+
+    fn into_owned(self) -> Self::Owned {
+        ConstantPool {
+            entries: self.entries.into_owned(),
+            parse_descriptors: self.parse_descriptors,
+        }
+    }
+}
+
+impl<S: AsRef<str>, B: AsRef<[u8]>> IntoOwned for ClassFile<S, B> {
+    type Owned = ClassFile<String, Vec<u8>>;
+
+    fn into_owned(self) -> Self::Owned {
+        ClassFile {
+            magic: self.magic,
+            version: self.version,
+            constant_pool: self.constant_pool.into_owned(),
+            access_flags: self.access_flags,
+            this_class: owned_str(self.this_class),
+            super_class: self.super_class.map(owned_str),
+            interfaces: owned_strs(self.interfaces),
+            fields: self.fields.into_owned(),
+            methods: self.methods.into_owned(),
+            attributes: self.attributes.into_owned(),
+            hex_unknown: self.hex_unknown,
+        }
+    }
+}
@@ -0,0 +1,376 @@
+/// Constant pool utilization, for shrinking class files, compaction and canonical ordering.
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::layout::{Reference, Visit, walk_attribute};
+use crate::raw::{self, ParseError};
+
+/// Constants a class file could do without, as found by [`raw::ClassFile::pool_analysis`].
+#[derive(Debug, Default, Serialize)]
+pub struct PoolAnalysis {
+    unreferenced: Vec<u16>,
+    duplicates: Vec<Vec<u16>>,
+}
+
+impl PoolAnalysis {
+    /// Indexes of the constants nothing refers to, directly or through other constants.
+    pub fn unreferenced(&self) -> &[u16] {
+        &self.unreferenced
+    }
+
+    /// Indexes of referenced constants equal to each other once resolved, e.g. two
+    /// `CONSTANT_Utf8` with the same content, or two `CONSTANT_NameAndType` with the same name
+    /// and descriptor, in groups of two or more in pool order.
+    ///
+    /// All the references to a group could go to its first constant.
+    pub fn duplicates(&self) -> &[Vec<u16>] {
+        &self.duplicates
+    }
+
+    /// The number of constants which could be removed: the unreferenced ones, and all but the
+    /// first of each group of duplicates.
+    pub fn removable_count(&self) -> usize {
+        self.unreferenced.len()
+            + self
+                .duplicates
+                .iter()
+                .map(|group| group.len() - 1)
+                .sum::<usize>()
+    }
+}
+
+impl raw::ClassFile {
+    /// Find the unreferenced and duplicate constants.
+    ///
+    /// Constants are compared resolved, so two `CONSTANT_Class` naming the same class through
+    /// two equal `CONSTANT_Utf8` are duplicates too. Fails for malformed constants and
+    /// attributes, as [`crate::wrap`] does.
+    pub fn pool_analysis(&self) -> Result<PoolAnalysis, ParseError> {
+        let referenced = self.referenced_constants()?;
+
+        let mut analysis = PoolAnalysis::default();
+        let mut groups = HashMap::new();
+        for (index, resolved) in self.resolved_constants() {
+            let resolved = resolved?;
+            if !referenced[index as usize] {
+                analysis.unreferenced.push(index);
+                continue;
+            }
+            let key = crate::equality_key(&resolved)?;
+            let group = *groups.entry(key).or_insert_with(|| {
+                analysis.duplicates.push(vec![]);
+                analysis.duplicates.len() - 1
+            });
+            analysis.duplicates[group].push(index);
+        }
+        analysis.duplicates.retain(|group| group.len() > 1);
+        Ok(analysis)
+    }
+}
+
+/// Position of a kind of constant in the canonical order of [`raw::ClassFile::canonicalize_pool`].
+///
+/// The kinds `ldc` can load come first, so that they are the most likely to keep an index
+/// under 256.
+fn canonical_rank(entry: &raw::CpInfo) -> u8 {
+    match entry {
+        raw::CpInfo::Integer(..) => 0,
+        raw::CpInfo::Float(..) => 1,
+        raw::CpInfo::String { .. } => 2,
+        raw::CpInfo::Class { .. } => 3,
+        raw::CpInfo::MethodType { .. } => 4,
+        raw::CpInfo::MethodHandle { .. } => 5,
+        raw::CpInfo::Dynamic { .. } => 6,
+        raw::CpInfo::Long(..) => 7,
+        raw::CpInfo::Double(..) => 8,
+        raw::CpInfo::Utf8(..) => 9,
+        raw::CpInfo::NameAndType { .. } => 10,
+        raw::CpInfo::Fieldref { .. } => 11,
+        raw::CpInfo::Methodref { .. } => 12,
+        raw::CpInfo::InterfaceMethodref { .. } => 13,
+        raw::CpInfo::InvokeDynamic { .. } => 14,
+        raw::CpInfo::Module { .. } => 15,
+        raw::CpInfo::Package { .. } => 16,
+    }
+}
+
+impl raw::ClassFile {
+    /// Reorder the constant pool by kind, then by resolved value, rewriting every index to it,
+    /// so that classes differing only in the order of their constants encode the same.
+    ///
+    /// Duplicates are kept, next to each other. Fails for malformed constants and attributes,
+    /// for attributes whose layout is not known, as their indexes cannot be rewritten, and
+    /// when an `ldc` operand would move past index 255.
+    pub fn canonicalize_pool(&mut self) -> Result<(), ParseError> {
+        let mut order = self
+            .resolved_constants()
+            .map(|(index, resolved)| {
+                let Some(Some(entry)) = self.constant_pool.get(index as usize) else {
+                    unreachable!()
+                };
+                Ok((
+                    canonical_rank(entry),
+                    crate::equality_key(&resolved?)?,
+                    index,
+                ))
+            })
+            .collect::<Result<Vec<_>, ParseError>>()?;
+        order.sort();
+
+        let mut indexes = vec![0; self.constant_pool.len()];
+        let mut next = 1;
+        let order = order
+            .into_iter()
+            .map(|(_, _, index)| {
+                indexes[index as usize] = next;
+                next += self.constant_pool[index as usize].as_ref().map_or(1, width);
+                index
+            })
+            .collect::<Vec<_>>();
+        self.renumber(&order, &indexes)
+    }
+
+    /// Drop the constants [`Self::pool_analysis`] finds removable, pointing the references to
+    /// each group of duplicates to its first constant, and close the gaps, rewriting every
+    /// index to the new positions.
+    ///
+    /// The constants left keep their order, so indexes only decrease and `ldc` operands stay
+    /// under 256. Fails as [`Self::canonicalize_pool`] does.
+    pub fn compact_pool(&mut self) -> Result<(), ParseError> {
+        let analysis = self.pool_analysis()?;
+        let mut kept = self
+            .constant_pool
+            .iter()
+            .map(Option::is_some)
+            .collect::<Vec<_>>();
+        for &index in analysis.unreferenced() {
+            kept[index as usize] = false;
+        }
+        for group in analysis.duplicates() {
+            for &index in &group[1..] {
+                kept[index as usize] = false;
+            }
+        }
+
+        let mut indexes = vec![0; self.constant_pool.len()];
+        let mut next = 1;
+        let mut order = vec![];
+        for (index, entry) in self.constant_pool.iter().enumerate() {
+            if let Some(entry) = entry
+                && kept[index]
+            {
+                indexes[index] = next;
+                next += width(entry);
+                order.push(index as u16);
+            }
+        }
+        for group in analysis.duplicates() {
+            for &index in &group[1..] {
+                indexes[index as usize] = indexes[group[0] as usize];
+            }
+        }
+        self.renumber(&order, &indexes)
+    }
+
+    /// Rebuild the constant pool from the constants at `order`, rewriting every index through
+    /// `indexes`, where 0 stands for a constant nothing may refer to any more.
+    fn renumber(&mut self, order: &[u16], indexes: &[u16]) -> Result<(), ParseError> {
+        let map = |index: u16| match index {
+            0 => Ok(0),
+            index => match indexes.get(index as usize) {
+                Some(&mapped) if mapped != 0 => Ok(mapped),
+                Some(_) => Err(ParseError::UnexpectedCpType {
+                    index,
+                    expected: "a constant",
+                }),
+                None => Err(ParseError::InvalidCpIndex {
+                    index,
+                    pool_size: indexes.len(),
+                }),
+            },
+        };
+
+        // Attributes are dispatched on their names in the original constant pool.
+        let pool = &self.constant_pool;
+        let attributes = self
+            .attributes
+            .iter_mut()
+            .chain(
+                self.fields
+                    .iter_mut()
+                    .flat_map(|field| &mut field.attributes),
+            )
+            .chain(
+                self.methods
+                    .iter_mut()
+                    .flat_map(|method| &mut method.attributes),
+            );
+        for attribute in attributes {
+            remap_attribute(
+                pool,
+                attribute.attribute_name_index,
+                &mut attribute.info,
+                &map,
+            )?;
+            attribute.attribute_name_index = map(attribute.attribute_name_index)?;
+        }
+
+        self.this_class = map(self.this_class)?;
+        self.super_class = map(self.super_class)?;
+        for interface in &mut self.interfaces {
+            *interface = map(*interface)?;
+        }
+        let members = self
+            .fields
+            .iter_mut()
+            .map(|f| (&mut f.name_index, &mut f.descriptor_index))
+            .chain(
+                self.methods
+                    .iter_mut()
+                    .map(|m| (&mut m.name_index, &mut m.descriptor_index)),
+            );
+        for (name_index, descriptor_index) in members {
+            *name_index = map(*name_index)?;
+            *descriptor_index = map(*descriptor_index)?;
+        }
+
+        let mut old = std::mem::take(&mut self.constant_pool);
+        self.constant_pool.push(None);
+        for &index in order {
+            let Some(mut entry) = old[index as usize].take() else {
+                unreachable!()
+            };
+            entry.remap_references(&map)?;
+            let width = width(&entry);
+            self.constant_pool.push(Some(entry));
+            if width == 2 {
+                self.constant_pool.push(None);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The number of constant pool slots `entry` takes.
+fn width(entry: &raw::CpInfo) -> u16 {
+    match entry {
+        raw::CpInfo::Long(..) | raw::CpInfo::Double(..) => 2,
+        _ => 1,
+    }
+}
+
+impl raw::CpInfo {
+    /// Point the indexes this entry refers to through `map`.
+    fn remap_references(
+        &mut self,
+        map: &impl Fn(u16) -> Result<u16, ParseError>,
+    ) -> Result<(), ParseError> {
+        match self {
+            raw::CpInfo::Utf8(..)
+            | raw::CpInfo::Integer(..)
+            | raw::CpInfo::Float(..)
+            | raw::CpInfo::Long(..)
+            | raw::CpInfo::Double(..) => {}
+            raw::CpInfo::Class { name_index: index }
+            | raw::CpInfo::Module { name_index: index }
+            | raw::CpInfo::Package { name_index: index }
+            | raw::CpInfo::String {
+                string_index: index,
+            }
+            | raw::CpInfo::MethodHandle {
+                reference_index: index,
+                ..
+            }
+            | raw::CpInfo::MethodType {
+                descriptor_index: index,
+            }
+            | raw::CpInfo::Dynamic {
+                name_and_type_index: index,
+                ..
+            }
+            | raw::CpInfo::InvokeDynamic {
+                name_and_type_index: index,
+                ..
+            } => *index = map(*index)?,
+            raw::CpInfo::Fieldref {
+                class_index: first,
+                name_and_type_index: second,
+            }
+            | raw::CpInfo::Methodref {
+                class_index: first,
+                name_and_type_index: second,
+            }
+            | raw::CpInfo::InterfaceMethodref {
+                class_index: first,
+                name_and_type_index: second,
+            }
+            | raw::CpInfo::NameAndType {
+                name_index: first,
+                descriptor_index: second,
+            } => {
+                *first = map(*first)?;
+                *second = map(*second)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Collects the references within an attribute, failing for nested attributes of unknown
+/// layout, as their indexes cannot be rewritten.
+#[derive(Default)]
+struct References(Vec<Reference>);
+
+impl Visit for References {
+    fn reference(&mut self, reference: Reference) -> Result<(), ParseError> {
+        self.0.push(reference);
+        Ok(())
+    }
+
+    fn unknown(&mut self) -> Result<(), ParseError> {
+        Err(ParseError::Unsupported {
+            context: "canonicalize_pool attribute of unknown layout",
+        })
+    }
+}
+
+/// Rewrite the indexes within `info` of the attribute named by `attribute_name_index` in
+/// `pool`, excluding `attribute_name_index` itself.
+fn remap_attribute<F: Fn(u16) -> Result<u16, ParseError>>(
+    pool: &[Option<raw::CpInfo>],
+    attribute_name_index: u16,
+    info: &mut [u8],
+    map: &F,
+) -> Result<(), ParseError> {
+    let Some(Some(raw::CpInfo::Utf8(name))) = pool.get(attribute_name_index as usize) else {
+        return Err(ParseError::IncorrectAttributeNameIndex);
+    };
+    let mut references = References::default();
+    match walk_attribute(pool, name, info, raw::MAX_NESTING_DEPTH, &mut references)? {
+        Some(length) if length == info.len() => {}
+        Some(length) => {
+            return Err(raw::malformed(
+                name,
+                format!("{} bytes past the contents", info.len() - length),
+            ));
+        }
+        None => references.unknown()?,
+    }
+
+    for reference in references.0 {
+        let at = reference.position;
+        let index = map(reference.index)?;
+        if reference.narrow {
+            let Ok(index) = u8::try_from(index) else {
+                return Err(ParseError::Unsupported {
+                    context: "ldc index over 255",
+                });
+            };
+            info[at] = index;
+        } else {
+            info[at..at + 2].copy_from_slice(&index.to_be_bytes());
+        }
+    }
+    Ok(())
+}
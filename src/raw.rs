@@ -5,22 +5,180 @@ use base64::Engine as _;
 use serde::Serialize;
 use thiserror::Error;
 
+/// Errors may be added in minor releases, so matches need a wildcard arm.
 #[derive(Debug, Error)]
+#[non_exhaustive]
 pub enum ParseError {
     #[error("io error. {0}")]
     Io(#[from] io::Error),
 
+    #[error("unexpected EOF while reading {while_reading} at offset {offset}")]
+    UnexpectedEof {
+        while_reading: &'static str,
+        offset: u64,
+    },
+
     #[error("bad magic number")]
     BadMagicNumber,
 
+    /// The header has the magic of a class file but not much else, as with Mach-O universal
+    /// binaries, which share the magic.
+    #[error("implausible {field} {value}, not a class file")]
+    ImplausibleHeader { field: &'static str, value: u16 },
+
     #[error("from utf8 error. {0}")]
     FromUtf8(#[from] std::string::FromUtf8Error),
 
+    #[error("base64 decode error. {0}")]
+    Base64Decode(#[from] base64::DecodeError),
+
     #[error("serialize error. {0}")]
     Serialize(#[from] serde_json::Error),
 
     #[error("incorrect attribute_name_index")]
     IncorrectAttributeNameIndex,
+
+    #[error("class file depends on preview features")]
+    PreviewFeaturesNotAllowed,
+
+    #[error("ConstantValue refers to constant pool index 0")]
+    InvalidConstantValueIndex,
+
+    /// `found` is `index 0` when `this_class` is zero.
+    #[error("this_class refers to {found} instead of a CONSTANT_Class")]
+    InvalidThisClass { found: &'static str },
+
+    #[error("this_class of a module is {name} instead of module-info")]
+    InvalidModuleThisClass { name: String },
+
+    /// A field or method descriptor which does not follow the grammar, failing at byte
+    /// `position`.
+    #[error("invalid descriptor {value:?} at position {position}")]
+    InvalidDescriptor { value: String, position: usize },
+
+    /// `constant_pool_count` does not account for the second slot taken by `CONSTANT_Long` and
+    /// `CONSTANT_Double`, as with some broken generators, so that the constants after `index`,
+    /// the first of them, are off by one. Detected when the last constant needs a slot past the
+    /// count, or by [`crate::parse_raw_bytes`] when the class file only parses with the second
+    /// slots left out.
+    #[error(
+        "constant_pool_count does not account for the second slot of CONSTANT_Long and \
+         CONSTANT_Double, from the one at index {index}"
+    )]
+    ConstantPoolSlotMisalignment { index: u16 },
+
+    #[error("super_class refers to {found} instead of a CONSTANT_Class")]
+    InvalidSuperClass { found: &'static str },
+
+    #[error("illegal opcode {opcode:#04x} at offset {offset}")]
+    IllegalOpcode { opcode: u8, offset: u32 },
+
+    #[error("constant pool refers to bootstrap methods but there is no BootstrapMethods attribute")]
+    MissingBootstrapMethods,
+
+    #[error("bootstrap method {index} out of range of {len} bootstrap methods")]
+    BootstrapMethodIndexOutOfRange { index: u16, len: usize },
+
+    #[error("CONSTANT_Utf8 at index {index} is {length} bytes long, over the limit of 65535")]
+    Utf8TooLong { index: u16, length: usize },
+
+    /// Only detected for references to the class being parsed, as whether another class is an
+    /// interface is not known without loading it.
+    #[error(
+        "{} at constant pool index {index} refers to {}",
+        if *interface_method_ref { "CONSTANT_InterfaceMethodref" } else { "CONSTANT_Methodref" },
+        if *interface_method_ref { "a class" } else { "an interface" },
+    )]
+    MethodRefInterfaceMismatch {
+        index: u16,
+        interface_method_ref: bool,
+    },
+
+    #[error("{name} attribute needs Java {introduced_in}, newer than the target Java {target}")]
+    AttributeNewerThanTarget {
+        name: String,
+        introduced_in: u16,
+        target: u16,
+    },
+
+    #[error("no member {name} with descriptor {descriptor}")]
+    MemberNotFound { name: String, descriptor: String },
+
+    #[error("member {name} with descriptor {descriptor} already exists")]
+    DuplicateMember { name: String, descriptor: String },
+
+    /// Attributes nested within `Code`, or annotations and element values within each other,
+    /// deeper than [`crate::ParseOptions::max_nesting_depth`].
+    #[error("attributes or annotations nested too deeply")]
+    MaxNestingExceeded,
+
+    /// A length or count within an attribute disagrees with its `attribute_length`, as told by
+    /// `context`. `snippet` is up to the first 16 bytes of the attribute's `info`.
+    #[error(
+        "{context} mismatch in {name} attribute at offset {offset}, starting with [{}]",
+        hex_snippet(snippet)
+    )]
+    AttributeLengthMismatch {
+        name: String,
+        offset: u64,
+        context: &'static str,
+        snippet: Vec<u8>,
+    },
+
+    #[error("{limit} of {max} exceeded")]
+    LimitExceeded { limit: &'static str, max: u64 },
+
+    /// A constant pool tag which was reserved but never assigned, such as 2 for the
+    /// `CONSTANT_Unicode` of early JVM drafts, or 13 and 14.
+    #[error("reserved constant pool tag {0}")]
+    ReservedConstantPoolTag(u8),
+
+    /// The constant at `index` has a `field` of a `value` which is not assigned, e.g. a `tag`
+    /// of 21, or a `reference_kind` of 0 for a `CONSTANT_MethodHandle`.
+    #[error("constant pool index {index} has unknown {field} {value}")]
+    InvalidConstant {
+        index: u16,
+        field: &'static str,
+        value: u8,
+    },
+
+    /// `index` is past the end of the constant pool, of `pool_size` slots including index 0.
+    #[error("constant pool index {index} out of range of {pool_size} slots")]
+    InvalidCpIndex { index: u16, pool_size: usize },
+
+    /// The constant at `index` is not of the kind `expected`, e.g. `CONSTANT_Class`, or `index`
+    /// is 0 or the unusable slot after a `CONSTANT_Long` or `CONSTANT_Double`.
+    #[error("constant pool index {index} is not {expected}")]
+    UnexpectedCpType { index: u16, expected: &'static str },
+
+    /// The contents of a predefined attribute break its layout other than by their length,
+    /// e.g. with an unknown tag.
+    #[error("malformed {name} attribute: {detail}")]
+    MalformedAttribute { name: String, detail: String },
+
+    /// The constant pool has no slot at all, not even index 0.
+    #[error("constant pool is empty")]
+    EmptyConstantPool,
+
+    #[error("unsupported input: {context}")]
+    Unsupported { context: &'static str },
+
+    #[cfg(feature = "zip")]
+    #[error("zip error. {0}")]
+    Zip(#[from] zip::result::ZipError),
+
+    #[cfg(feature = "zip")]
+    #[error("no entry {name} in the archive")]
+    EntryNotFound { name: String },
+}
+
+/// Bytes in hex separated by spaces, e.g. `ca fe ba be`.
+fn hex_snippet(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect::<Vec<_>>()
+        .join(" ")
 }
 
 #[derive(Debug, Serialize)]
@@ -75,8 +233,10 @@ pub enum CpInfo {
     },
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Serialize)]
 pub struct AttributeInfo {
+    /// Offset of the attribute from the beginning of the class file.
+    pub offset: u64,
     pub attribute_name_index: u16,
     #[serde(serialize_with = "as_base64")]
     pub info: Vec<u8>,
@@ -120,142 +280,410 @@ fn as_base64<T: AsRef<[u8]>, S: serde::Serializer>(
     serializer.serialize_str(&base64::engine::general_purpose::STANDARD.encode(val.as_ref()))
 }
 
-fn read_u1<I: io::Read>(input: &mut I) -> io::Result<u8> {
+/// Formats bytes for `Debug` as they are when short, or as their length, e.g. `1432 bytes`, so
+/// that dumping a class does not flood the output with bytecode.
+pub(crate) struct DebugBytes<'a>(pub(crate) &'a [u8]);
+
+impl std::fmt::Debug for DebugBytes<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.0.len() <= 16 {
+            self.0.fmt(f)
+        } else {
+            write!(f, "{} bytes", self.0.len())
+        }
+    }
+}
+
+impl std::fmt::Debug for AttributeInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AttributeInfo")
+            .field("offset", &self.offset)
+            .field("attribute_name_index", &self.attribute_name_index)
+            .field("info", &DebugBytes(&self.info))
+            .finish()
+    }
+}
+
+/// The default of [`crate::ParseOptions::max_nesting_depth`], also the one of the attributes
+/// decoded by [`ClassFile`] methods and [`crate::validate`].
+pub(crate) const MAX_NESTING_DEPTH: usize = 64;
+
+/// [`ParseError::MalformedAttribute`] for the attribute `name`.
+pub(crate) fn malformed(name: &str, detail: impl Into<String>) -> ParseError {
+    ParseError::MalformedAttribute {
+        name: name.to_string(),
+        detail: detail.into(),
+    }
+}
+
+/// The predefined attribute being decoded, to tell in its errors.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct AttributeContext<'a> {
+    pub(crate) name: &'a str,
+    /// Offset of the attribute from the beginning of the class file.
+    pub(crate) offset: u64,
+    pub(crate) info: &'a [u8],
+}
+
+impl AttributeContext<'_> {
+    pub(crate) fn malformed(&self, detail: impl Into<String>) -> ParseError {
+        malformed(self.name, detail)
+    }
+
+    /// [`ParseError::AttributeLengthMismatch`] for the length or count told by `context`.
+    pub(crate) fn length_mismatch(&self, context: &'static str) -> ParseError {
+        ParseError::AttributeLengthMismatch {
+            name: self.name.to_string(),
+            offset: self.offset,
+            context,
+            snippet: self.info[..self.info.len().min(16)].to_vec(),
+        }
+    }
+
+    /// Read a `u1` of `input`, whose end is that of `info`, for the field told by `context`.
+    pub(crate) fn u1(&self, input: &mut &[u8], context: &'static str) -> Result<u8, ParseError> {
+        read_u1(input).map_err(|_| self.length_mismatch(context))
+    }
+
+    /// Read a `u2` as in [`Self::u1`].
+    pub(crate) fn u2(&self, input: &mut &[u8], context: &'static str) -> Result<u16, ParseError> {
+        read_u2(input).map_err(|_| self.length_mismatch(context))
+    }
+
+    /// Read a `u4` as in [`Self::u1`].
+    pub(crate) fn u4(&self, input: &mut &[u8], context: &'static str) -> Result<u32, ParseError> {
+        read_u4(input).map_err(|_| self.length_mismatch(context))
+    }
+}
+
+/// One level deeper than `depth` levels left, failing when none is left.
+pub(crate) fn nest(depth: usize) -> Result<usize, ParseError> {
+    depth.checked_sub(1).ok_or(ParseError::MaxNestingExceeded)
+}
+
+pub(crate) fn read_u1<I: io::Read>(input: &mut I) -> io::Result<u8> {
     let mut buf = [0u8; 1];
     input.read_exact(&mut buf)?;
     Ok(u8::from_be_bytes(buf))
 }
 
-fn read_u2<I: io::Read>(input: &mut I) -> io::Result<u16> {
+pub(crate) fn read_u2<I: io::Read>(input: &mut I) -> io::Result<u16> {
     let mut buf = [0u8; 2];
     input.read_exact(&mut buf)?;
     Ok(u16::from_be_bytes(buf))
 }
 
-fn read_u4<I: io::Read>(input: &mut I) -> io::Result<u32> {
+pub(crate) fn read_u4<I: io::Read>(input: &mut I) -> io::Result<u32> {
     let mut buf = [0u8; 4];
     input.read_exact(&mut buf)?;
     Ok(u32::from_be_bytes(buf))
 }
 
-fn read_utf8<I: io::Read>(input: &mut I) -> Result<String, ParseError> {
-    let len = read_u2(input)?;
-    let mut data = vec![0u8; len as usize];
-    input.read_exact(&mut data)?;
+/// Bounds on the size of the class file, for untrusted input.
+///
+/// The defaults are the largest values the class file format allows.
+#[derive(Debug, Clone)]
+pub struct ParseLimits {
+    /// Size of the whole class file in bytes.
+    pub max_input_size: u64,
+    /// `constant_pool_count`.
+    pub max_constant_pool: u16,
+    /// `methods_count`.
+    pub max_methods: u16,
+    /// `fields_count`.
+    pub max_fields: u16,
+    /// `attribute_length` of the attributes of the class, its fields and methods.
+    pub max_attribute_length: u32,
+}
+
+impl Default for ParseLimits {
+    fn default() -> Self {
+        Self {
+            max_input_size: u64::MAX,
+            max_constant_pool: u16::MAX,
+            max_methods: u16::MAX,
+            max_fields: u16::MAX,
+            max_attribute_length: u32::MAX,
+        }
+    }
+}
+
+fn check_limit<T: Into<u64>>(value: T, max: T, limit: &'static str) -> Result<(), ParseError> {
+    let (value, max) = (value.into(), max.into());
+    if value > max {
+        return Err(ParseError::LimitExceeded { limit, max });
+    }
+    Ok(())
+}
+
+/// Keeps track of the offset read so far, to report where the input ended prematurely.
+struct Reader<I> {
+    input: I,
+    offset: u64,
+    limits: ParseLimits,
+    /// Skip past the attributes instead of reading them, see [`parse_skipping_attributes`].
+    skip_attributes: bool,
+}
+
+impl<I: io::Read> Reader<I> {
+    fn new(input: I, limits: ParseLimits) -> Self {
+        Self {
+            input,
+            offset: 0,
+            limits,
+            skip_attributes: false,
+        }
+    }
+
+    /// A reader of `input` from its beginning, with the same settings.
+    fn with_input<J: io::Read>(&self, input: J) -> Reader<J> {
+        Reader {
+            input,
+            offset: 0,
+            limits: self.limits.clone(),
+            skip_attributes: self.skip_attributes,
+        }
+    }
+
+    /// Fail before reading, or allocating, past `max_input_size`.
+    fn check_input_size(&self, len: usize) -> Result<(), ParseError> {
+        check_limit(
+            self.offset.saturating_add(len as u64),
+            self.limits.max_input_size,
+            "max_input_size",
+        )
+    }
+
+    fn read_exact(
+        &mut self,
+        buf: &mut [u8],
+        while_reading: &'static str,
+    ) -> Result<(), ParseError> {
+        self.check_input_size(buf.len())?;
+        match self.input.read_exact(buf) {
+            Ok(()) => {
+                self.offset += buf.len() as u64;
+                Ok(())
+            }
+            Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => {
+                Err(ParseError::UnexpectedEof {
+                    while_reading,
+                    offset: self.offset,
+                })
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    fn read_u1(&mut self, while_reading: &'static str) -> Result<u8, ParseError> {
+        let mut buf = [0u8; 1];
+        self.read_exact(&mut buf, while_reading)?;
+        Ok(u8::from_be_bytes(buf))
+    }
+
+    fn read_u2(&mut self, while_reading: &'static str) -> Result<u16, ParseError> {
+        let mut buf = [0u8; 2];
+        self.read_exact(&mut buf, while_reading)?;
+        Ok(u16::from_be_bytes(buf))
+    }
+
+    fn read_u4(&mut self, while_reading: &'static str) -> Result<u32, ParseError> {
+        let mut buf = [0u8; 4];
+        self.read_exact(&mut buf, while_reading)?;
+        Ok(u32::from_be_bytes(buf))
+    }
+
+    fn read_bytes(
+        &mut self,
+        len: usize,
+        while_reading: &'static str,
+    ) -> Result<Vec<u8>, ParseError> {
+        self.check_input_size(len)?;
+        let mut buf = vec![0u8; len];
+        self.read_exact(&mut buf, while_reading)?;
+        Ok(buf)
+    }
+
+    /// Skip `len` bytes without keeping them.
+    fn skip(&mut self, len: u64, while_reading: &'static str) -> Result<(), ParseError> {
+        check_limit(
+            self.offset.saturating_add(len),
+            self.limits.max_input_size,
+            "max_input_size",
+        )?;
+        let skipped = io::copy(&mut io::Read::take(&mut self.input, len), &mut io::sink())?;
+        self.offset += skipped;
+        if skipped < len {
+            return Err(ParseError::UnexpectedEof {
+                while_reading,
+                offset: self.offset,
+            });
+        }
+        Ok(())
+    }
+
+    fn is_eof(&mut self) -> Result<bool, ParseError> {
+        if self.input.read(&mut [0])? == 0 {
+            return Ok(true);
+        }
+        self.check_input_size(1)?;
+        Ok(false)
+    }
+}
+
+fn read_utf8<I: io::Read>(input: &mut Reader<I>) -> Result<String, ParseError> {
+    let len = input.read_u2("CONSTANT_Utf8 length")?;
+    let data = input.read_bytes(len as usize, "CONSTANT_Utf8 bytes")?;
     Ok(String::from_utf8(data)?)
 }
 
-fn read_cp_info<I: io::Read>(input: &mut I) -> Result<CpInfo, ParseError> {
-    let tag = read_u1(input)?;
+fn read_cp_info<I: io::Read>(input: &mut Reader<I>, index: u16) -> Result<CpInfo, ParseError> {
+    let tag = input.read_u1("cp_info tag")?;
     match tag {
         // CONSTANT_Utf8
         1 => Ok(CpInfo::Utf8(read_utf8(input)?)),
 
         // CONSTANT_Integer
-        3 => Ok(CpInfo::Integer(read_u4(input)?)),
+        3 => Ok(CpInfo::Integer(input.read_u4("CONSTANT_Integer")?)),
 
         // CONSTANT_Float
-        4 => Ok(CpInfo::Float(read_u4(input)?)),
+        4 => Ok(CpInfo::Float(input.read_u4("CONSTANT_Float")?)),
 
         // CONSTANT_Long
-        5 => Ok(CpInfo::Long(read_u4(input)?, read_u4(input)?)),
+        5 => Ok(CpInfo::Long(
+            input.read_u4("CONSTANT_Long")?,
+            input.read_u4("CONSTANT_Long")?,
+        )),
 
         // CONSTANT_Double
-        6 => Ok(CpInfo::Double(read_u4(input)?, read_u4(input)?)),
+        6 => Ok(CpInfo::Double(
+            input.read_u4("CONSTANT_Double")?,
+            input.read_u4("CONSTANT_Double")?,
+        )),
 
         // CONSTANT_Class
         7 => Ok(CpInfo::Class {
-            name_index: read_u2(input)?,
+            name_index: input.read_u2("CONSTANT_Class")?,
         }),
 
         // CONSTANT_String
         8 => Ok(CpInfo::String {
-            string_index: read_u2(input)?,
+            string_index: input.read_u2("CONSTANT_String")?,
         }),
 
         // CONSTANT_Fieldref
         9 => Ok(CpInfo::Fieldref {
-            class_index: read_u2(input)?,
-            name_and_type_index: read_u2(input)?,
+            class_index: input.read_u2("CONSTANT_Fieldref")?,
+            name_and_type_index: input.read_u2("CONSTANT_Fieldref")?,
         }),
 
         // CONSTANT_Methodref
         10 => Ok(CpInfo::Methodref {
-            class_index: read_u2(input)?,
-            name_and_type_index: read_u2(input)?,
+            class_index: input.read_u2("CONSTANT_Methodref")?,
+            name_and_type_index: input.read_u2("CONSTANT_Methodref")?,
         }),
 
         // CONSTANT_InterfaceMethodref
         11 => Ok(CpInfo::InterfaceMethodref {
-            class_index: read_u2(input)?,
-            name_and_type_index: read_u2(input)?,
+            class_index: input.read_u2("CONSTANT_InterfaceMethodref")?,
+            name_and_type_index: input.read_u2("CONSTANT_InterfaceMethodref")?,
         }),
 
         // CONSTANT_NameAndType
         12 => Ok(CpInfo::NameAndType {
-            name_index: read_u2(input)?,
-            descriptor_index: read_u2(input)?,
+            name_index: input.read_u2("CONSTANT_NameAndType")?,
+            descriptor_index: input.read_u2("CONSTANT_NameAndType")?,
         }),
 
         // CONSTANT_MethodHandle
         15 => Ok(CpInfo::MethodHandle {
-            reference_kind: read_u1(input)?,
-            reference_index: read_u2(input)?,
+            reference_kind: input.read_u1("CONSTANT_MethodHandle")?,
+            reference_index: input.read_u2("CONSTANT_MethodHandle")?,
         }),
 
         // CONSTANT_MethodType
         16 => Ok(CpInfo::MethodType {
-            descriptor_index: read_u2(input)?,
+            descriptor_index: input.read_u2("CONSTANT_MethodType")?,
         }),
 
         // CONSTANT_Dynamic
         // TODO Not tested.
         17 => Ok(CpInfo::Dynamic {
-            bootstrap_method_attr_index: read_u2(input)?,
-            name_and_type_index: read_u2(input)?,
+            bootstrap_method_attr_index: input.read_u2("CONSTANT_Dynamic")?,
+            name_and_type_index: input.read_u2("CONSTANT_Dynamic")?,
         }),
 
         // CONSTANT_InvokeDynamic
         18 => Ok(CpInfo::InvokeDynamic {
-            bootstrap_method_attr_index: read_u2(input)?,
-            name_and_type_index: read_u2(input)?,
+            bootstrap_method_attr_index: input.read_u2("CONSTANT_InvokeDynamic")?,
+            name_and_type_index: input.read_u2("CONSTANT_InvokeDynamic")?,
         }),
 
         // CONSTANT_Module
         19 => Ok(CpInfo::Module {
-            name_index: read_u2(input)?,
+            name_index: input.read_u2("CONSTANT_Module")?,
         }),
 
         // CONSTANT_Package
         20 => Ok(CpInfo::Package {
-            name_index: read_u2(input)?,
+            name_index: input.read_u2("CONSTANT_Package")?,
         }),
 
-        _ => todo!("Unknown tag {tag}"),
+        2 | 13 | 14 => Err(ParseError::ReservedConstantPoolTag(tag)),
+
+        _ => Err(ParseError::InvalidConstant {
+            index,
+            field: "tag",
+            value: tag,
+        }),
     }
 }
 
-fn read_attribute_info<I: io::Read>(input: &mut I) -> Result<AttributeInfo, ParseError> {
-    let attribute_name_index = read_u2(input)?;
-    let attribute_length = read_u4(input)? as usize;
-    let mut info = vec![0u8; attribute_length];
-    input.read_exact(&mut info)?;
+fn read_attribute_info<I: io::Read>(input: &mut Reader<I>) -> Result<AttributeInfo, ParseError> {
+    let offset = input.offset;
+    let attribute_name_index = input.read_u2("attribute_name_index")?;
+    let attribute_length = input.read_u4("attribute_length")?;
+    check_limit(
+        attribute_length,
+        input.limits.max_attribute_length,
+        "max_attribute_length",
+    )?;
+    let info = input.read_bytes(attribute_length as usize, "attribute info")?;
 
-    return Ok(AttributeInfo {
+    Ok(AttributeInfo {
+        offset,
         attribute_name_index,
         info,
-    });
+    })
 }
 
-fn read_field_info<I: io::Read>(input: &mut I) -> Result<FieldInfo, ParseError> {
-    let access_flags = read_u2(input)?;
-    let name_index = read_u2(input)?;
-    let descriptor_index = read_u2(input)?;
-    let attributes_count = read_u2(input)? as usize;
+/// Read `attributes_count` attributes, or skip past them leaving none when `skip_attributes`
+/// is set.
+fn read_attributes<I: io::Read>(
+    input: &mut Reader<I>,
+    attributes_count: usize,
+) -> Result<Vec<AttributeInfo>, ParseError> {
+    if input.skip_attributes {
+        for _ in 0..attributes_count {
+            let _attribute_name_index = input.read_u2("attribute_name_index")?;
+            let attribute_length = input.read_u4("attribute_length")?;
+            input.skip(attribute_length as u64, "attribute info")?;
+        }
+        return Ok(vec![]);
+    }
     let mut attributes = Vec::with_capacity(attributes_count);
     for _ in 0..attributes_count {
         attributes.push(read_attribute_info(input)?);
     }
+    Ok(attributes)
+}
+
+fn read_field_info<I: io::Read>(input: &mut Reader<I>) -> Result<FieldInfo, ParseError> {
+    let access_flags = input.read_u2("field_info access_flags")?;
+    let name_index = input.read_u2("field_info name_index")?;
+    let descriptor_index = input.read_u2("field_info descriptor_index")?;
+    let attributes_count = input.read_u2("field_info attributes_count")? as usize;
+    let attributes = read_attributes(input, attributes_count)?;
 
     Ok(FieldInfo {
         access_flags,
@@ -265,15 +693,12 @@ fn read_field_info<I: io::Read>(input: &mut I) -> Result<FieldInfo, ParseError>
     })
 }
 
-fn read_method_info<I: io::Read>(input: &mut I) -> Result<MethodInfo, ParseError> {
-    let access_flags = read_u2(input)?;
-    let name_index = read_u2(input)?;
-    let descriptor_index = read_u2(input)?;
-    let attributes_count = read_u2(input)? as usize;
-    let mut attributes = Vec::with_capacity(attributes_count);
-    for _ in 0..attributes_count {
-        attributes.push(read_attribute_info(input)?);
-    }
+fn read_method_info<I: io::Read>(input: &mut Reader<I>) -> Result<MethodInfo, ParseError> {
+    let access_flags = input.read_u2("method_info access_flags")?;
+    let name_index = input.read_u2("method_info name_index")?;
+    let descriptor_index = input.read_u2("method_info descriptor_index")?;
+    let attributes_count = input.read_u2("method_info attributes_count")? as usize;
+    let attributes = read_attributes(input, attributes_count)?;
 
     Ok(MethodInfo {
         access_flags,
@@ -284,19 +709,72 @@ fn read_method_info<I: io::Read>(input: &mut I) -> Result<MethodInfo, ParseError
 }
 
 pub fn parse<I: io::Read>(input: &mut I) -> Result<ClassFile, ParseError> {
-    let magic = read_u4(input)?;
+    parse_with_limits(input, &ParseLimits::default())
+}
+
+/// Reject input which is not a class file despite the magic, before reading any further.
+fn check_header(
+    minor_version: u16,
+    major_version: u16,
+    constant_pool_count: u16,
+) -> Result<(), ParseError> {
+    let implausible = |field, value| Err(ParseError::ImplausibleHeader { field, value });
+    // JDK 1.0.2 is 45, and releases come at a rate of two a year.
+    if !(45..=100).contains(&major_version) {
+        return implausible("major_version", major_version);
+    }
+    if major_version >= 56 && !matches!(minor_version, 0 | 0xffff) {
+        return implausible("minor_version", minor_version);
+    }
+    // At least index 0, and this_class with its name.
+    if constant_pool_count < 3 {
+        return implausible("constant_pool_count", constant_pool_count);
+    }
+    Ok(())
+}
+
+pub fn parse_with_limits<I: io::Read>(
+    input: &mut I,
+    limits: &ParseLimits,
+) -> Result<ClassFile, ParseError> {
+    read_class_file(&mut Reader::new(input, limits.clone()))
+}
+
+/// Like [`parse`], skipping past every attribute without allocating it, so that the class
+/// file has no attributes, nor do its fields and methods.
+pub(crate) fn parse_skipping_attributes<I: io::Read>(
+    input: &mut I,
+) -> Result<ClassFile, ParseError> {
+    let mut input = Reader::new(input, ParseLimits::default());
+    input.skip_attributes = true;
+    read_class_file(&mut input)
+}
+
+/// Read up to the end of the constant pool, giving the index of the first `Long` or `Double`
+/// constant if any.
+fn read_constant_pool<I: io::Read>(
+    input: &mut Reader<I>,
+) -> Result<(ClassFile, Option<usize>), ParseError> {
+    let magic = input.read_u4("magic")?;
     if magic != 0xcafebabe {
         return Err(ParseError::BadMagicNumber);
     }
 
-    let minor_version = read_u2(input)?;
-    let major_version = read_u2(input)?;
+    let minor_version = input.read_u2("minor_version")?;
+    let major_version = input.read_u2("major_version")?;
+    let constant_pool_count = input.read_u2("constant_pool_count")?;
+    check_header(minor_version, major_version, constant_pool_count)?;
 
-    let constant_pool_count = read_u2(input)? as usize;
+    check_limit(
+        constant_pool_count,
+        input.limits.max_constant_pool,
+        "max_constant_pool",
+    )?;
+    let constant_pool_count = constant_pool_count as usize;
     let mut constant_pool = Vec::with_capacity(constant_pool_count);
     constant_pool.push(None);
     while constant_pool.len() < constant_pool_count {
-        let entry = read_cp_info(input)?;
+        let entry = read_cp_info(input, constant_pool.len() as u16)?;
         match &entry {
             CpInfo::Long(..) | CpInfo::Double(..) => {
                 constant_pool.push(Some(entry));
@@ -308,52 +786,918 @@ pub fn parse<I: io::Read>(input: &mut I) -> Result<ClassFile, ParseError> {
         };
     }
 
-    let access_flags = read_u2(input)?;
-    let this_class = read_u2(input)?;
-    let super_class = read_u2(input)?;
-    let interfaces_count = read_u2(input)? as usize;
+    let wide = constant_pool
+        .iter()
+        .position(|entry| matches!(entry, Some(CpInfo::Long(..) | CpInfo::Double(..))));
+    if constant_pool.len() > constant_pool_count {
+        return Err(ParseError::ConstantPoolSlotMisalignment {
+            index: wide.unwrap_or_default() as u16,
+        });
+    }
+
+    let classfile =
+        ClassFile::with_constant_pool(magic, minor_version, major_version, constant_pool);
+    Ok((classfile, wide))
+}
+
+fn read_class_file<I: io::Read>(input: &mut Reader<I>) -> Result<ClassFile, ParseError> {
+    let (mut classfile, _) = read_constant_pool(input)?;
+    read_after_constant_pool(input, &mut classfile)?;
+    Ok(classfile)
+}
+
+/// Like [`parse_with_limits`], reading from `bytes`, which are kept to tell a failure apart
+/// as due to misaligned constants once it happens, see
+/// [`ParseError::ConstantPoolSlotMisalignment`].
+pub fn parse_bytes_with_limits(
+    bytes: &[u8],
+    limits: &ParseLimits,
+) -> Result<ClassFile, ParseError> {
+    let mut input = Reader::new(bytes, limits.clone());
+    let (mut classfile, wide) = read_constant_pool(&mut input)?;
+    let rest = input.input;
+    let Err(err) = read_after_constant_pool(&mut input, &mut classfile) else {
+        return Ok(classfile);
+    };
+    let Some(wide) = wide else {
+        return Err(err);
+    };
+    let wide_count = classfile
+        .constant_pool
+        .iter()
+        .filter(|entry| matches!(entry, Some(CpInfo::Long(..) | CpInfo::Double(..))))
+        .count();
+    if is_misaligned(&mut input.with_input(rest), wide_count) {
+        return Err(ParseError::ConstantPoolSlotMisalignment { index: wide as u16 });
+    }
+    Err(err)
+}
+
+impl ClassFile {
+    /// A class file with nothing after the constant pool yet.
+    fn with_constant_pool(
+        magic: u32,
+        minor_version: u16,
+        major_version: u16,
+        constant_pool: Vec<Option<CpInfo>>,
+    ) -> Self {
+        Self {
+            magic,
+            minor_version,
+            major_version,
+            constant_pool,
+            access_flags: 0,
+            this_class: 0,
+            super_class: 0,
+            interfaces: vec![],
+            fields: vec![],
+            methods: vec![],
+            attributes: vec![],
+        }
+    }
+}
+
+/// Read what follows the constant pool into `classfile`, up to the end of the input.
+fn read_after_constant_pool<I: io::Read>(
+    input: &mut Reader<I>,
+    classfile: &mut ClassFile,
+) -> Result<(), ParseError> {
+    read_class_header(input, classfile)?;
+    let mut members = Members::new(input)?;
+    while let Some(member) = members.next(input)? {
+        match member {
+            Member::Field(field) => classfile.fields.push(field),
+            Member::Method(method) => classfile.methods.push(method),
+            Member::Attribute(attribute) => classfile.attributes.push(attribute),
+        }
+    }
+    Ok(())
+}
+
+/// Read from `access_flags` up to the interfaces into `classfile`.
+fn read_class_header<I: io::Read>(
+    input: &mut Reader<I>,
+    classfile: &mut ClassFile,
+) -> Result<(), ParseError> {
+    classfile.access_flags = input.read_u2("access_flags")?;
+    classfile.this_class = input.read_u2("this_class")?;
+    classfile.super_class = input.read_u2("super_class")?;
+    let interfaces_count = input.read_u2("interfaces_count")? as usize;
     let mut interfaces = Vec::with_capacity(interfaces_count);
     for _ in 0..interfaces_count {
-        interfaces.push(read_u2(input)?);
+        interfaces.push(input.read_u2("interfaces")?);
     }
+    classfile.interfaces = interfaces;
+    Ok(())
+}
+
+/// A field, method or class attribute, as read by [`MemberReader`].
+pub(crate) enum Member {
+    Field(FieldInfo),
+    Method(MethodInfo),
+    Attribute(AttributeInfo),
+}
+
+/// How many of the fields, methods or class attributes are left to read.
+enum Stage {
+    Fields(u16),
+    Methods(u16),
+    Attributes(u16),
+    Done,
+}
 
-    let fields_count = read_u2(input)? as usize;
-    let mut fields = Vec::with_capacity(fields_count);
-    for _ in 0..fields_count {
-        fields.push(read_field_info(input)?);
+/// Reads the fields, methods and class attributes one at a time, in file order.
+struct Members {
+    stage: Stage,
+}
+
+impl Members {
+    /// Start right after the interfaces.
+    fn new<I: io::Read>(input: &mut Reader<I>) -> Result<Self, ParseError> {
+        let fields_count = input.read_u2("fields_count")?;
+        check_limit(fields_count, input.limits.max_fields, "max_fields")?;
+        Ok(Self {
+            stage: Stage::Fields(fields_count),
+        })
     }
 
-    let method_count = read_u2(input)? as usize;
-    let mut methods = Vec::with_capacity(method_count);
-    for _ in 0..method_count {
-        methods.push(read_method_info(input)?);
+    /// The next member, or `None` once the input has ended after the class attributes.
+    fn next<I: io::Read>(&mut self, input: &mut Reader<I>) -> Result<Option<Member>, ParseError> {
+        loop {
+            match self.stage {
+                Stage::Fields(0) => {
+                    let method_count = input.read_u2("methods_count")?;
+                    check_limit(method_count, input.limits.max_methods, "max_methods")?;
+                    self.stage = Stage::Methods(method_count);
+                }
+                Stage::Fields(left) => {
+                    self.stage = Stage::Fields(left - 1);
+                    return Ok(Some(Member::Field(read_field_info(input)?)));
+                }
+                Stage::Methods(0) => {
+                    let attributes_count = input.read_u2("attributes_count")?;
+                    self.stage = Stage::Attributes(attributes_count);
+                }
+                Stage::Methods(left) => {
+                    self.stage = Stage::Methods(left - 1);
+                    return Ok(Some(Member::Method(read_method_info(input)?)));
+                }
+                Stage::Attributes(left) if left > 0 && input.skip_attributes => {
+                    read_attributes(input, left as usize)?;
+                    self.stage = Stage::Attributes(0);
+                }
+                Stage::Attributes(0) => {
+                    // check EOF
+                    if !input.is_eof()? {
+                        return Err(ParseError::Unsupported {
+                            context: "trailing bytes after attributes",
+                        });
+                    }
+                    self.stage = Stage::Done;
+                }
+                Stage::Attributes(left) => {
+                    self.stage = Stage::Attributes(left - 1);
+                    return Ok(Some(Member::Attribute(read_attribute_info(input)?)));
+                }
+                Stage::Done => return Ok(None),
+            }
+        }
     }
+}
 
-    let attributes_count = read_u2(input)? as usize;
-    let mut attributes = Vec::with_capacity(attributes_count);
-    for _ in 0..attributes_count {
-        attributes.push(read_attribute_info(input)?);
+/// Hands out the fields, methods and class attributes of a class file as they are read, see
+/// [`parse_members`].
+pub(crate) struct MemberReader<I> {
+    input: Reader<I>,
+    members: Members,
+}
+
+impl<I: io::Read> MemberReader<I> {
+    pub(crate) fn next(&mut self) -> Result<Option<Member>, ParseError> {
+        self.members.next(&mut self.input)
     }
+}
+
+/// Read up to the interfaces, leaving the members to be read one at a time with the returned
+/// reader.
+pub(crate) fn parse_members<I: io::Read>(
+    input: I,
+) -> Result<(ClassFile, MemberReader<I>), ParseError> {
+    let mut input = Reader::new(input, ParseLimits::default());
+    let (mut classfile, _) = read_constant_pool(&mut input)?;
+    read_class_header(&mut input, &mut classfile)?;
+    let members = Members::new(&mut input)?;
+    Ok((classfile, MemberReader { input, members }))
+}
 
-    // check EOF
-    let n = input.read(&mut [0])?;
-    if n != 0 {
-        todo!();
+/// Whether `input`, what follows the constant pool, parses once the constants left unread,
+/// one for each of the `wide_count` second slots which were not accounted for, are skipped.
+fn is_misaligned(input: &mut Reader<&[u8]>, mut wide_count: usize) -> bool {
+    while wide_count > 0 {
+        // Constants past the count have no index, and errors are not reported.
+        match read_cp_info(input, 0) {
+            Ok(CpInfo::Long(..) | CpInfo::Double(..)) => {}
+            Ok(_) => wide_count -= 1,
+            Err(_) => return false,
+        }
+        let mut attempt = input.with_input(input.input);
+        let mut classfile = ClassFile::with_constant_pool(0, 0, 0, vec![]);
+        if read_after_constant_pool(&mut attempt, &mut classfile).is_ok() {
+            return true;
+        }
     }
+    false
+}
 
-    let classfile = ClassFile {
-        magic,
-        minor_version,
-        major_version,
-        constant_pool,
-        access_flags,
-        this_class,
-        super_class,
-        interfaces,
-        fields,
-        methods,
-        attributes,
+fn write_count<O: io::Write>(
+    output: &mut O,
+    count: usize,
+    context: &'static str,
+) -> Result<(), ParseError> {
+    let Ok(count) = u16::try_from(count) else {
+        return Err(ParseError::Unsupported { context });
     };
+    output.write_all(&count.to_be_bytes())?;
+    Ok(())
+}
 
-    Ok(classfile)
+fn write_cp_info<O: io::Write>(
+    output: &mut O,
+    index: u16,
+    entry: &CpInfo,
+) -> Result<(), ParseError> {
+    let u1 = |output: &mut O, value: u8| output.write_all(&[value]);
+    let u2 = |output: &mut O, value: u16| output.write_all(&value.to_be_bytes());
+    let u4 = |output: &mut O, value: u32| output.write_all(&value.to_be_bytes());
+    match entry {
+        CpInfo::Utf8(value) => {
+            let Ok(length) = u16::try_from(value.len()) else {
+                return Err(ParseError::Utf8TooLong {
+                    index,
+                    length: value.len(),
+                });
+            };
+            u1(output, 1)?;
+            u2(output, length)?;
+            output.write_all(value.as_bytes())?;
+        }
+        CpInfo::Integer(value) => {
+            u1(output, 3)?;
+            u4(output, *value)?;
+        }
+        CpInfo::Float(value) => {
+            u1(output, 4)?;
+            u4(output, *value)?;
+        }
+        CpInfo::Long(high, low) => {
+            u1(output, 5)?;
+            u4(output, *high)?;
+            u4(output, *low)?;
+        }
+        CpInfo::Double(high, low) => {
+            u1(output, 6)?;
+            u4(output, *high)?;
+            u4(output, *low)?;
+        }
+        CpInfo::Class { name_index } => {
+            u1(output, 7)?;
+            u2(output, *name_index)?;
+        }
+        CpInfo::String { string_index } => {
+            u1(output, 8)?;
+            u2(output, *string_index)?;
+        }
+        CpInfo::Fieldref {
+            class_index,
+            name_and_type_index,
+        } => {
+            u1(output, 9)?;
+            u2(output, *class_index)?;
+            u2(output, *name_and_type_index)?;
+        }
+        CpInfo::Methodref {
+            class_index,
+            name_and_type_index,
+        } => {
+            u1(output, 10)?;
+            u2(output, *class_index)?;
+            u2(output, *name_and_type_index)?;
+        }
+        CpInfo::InterfaceMethodref {
+            class_index,
+            name_and_type_index,
+        } => {
+            u1(output, 11)?;
+            u2(output, *class_index)?;
+            u2(output, *name_and_type_index)?;
+        }
+        CpInfo::NameAndType {
+            name_index,
+            descriptor_index,
+        } => {
+            u1(output, 12)?;
+            u2(output, *name_index)?;
+            u2(output, *descriptor_index)?;
+        }
+        CpInfo::MethodHandle {
+            reference_kind,
+            reference_index,
+        } => {
+            u1(output, 15)?;
+            u1(output, *reference_kind)?;
+            u2(output, *reference_index)?;
+        }
+        CpInfo::MethodType { descriptor_index } => {
+            u1(output, 16)?;
+            u2(output, *descriptor_index)?;
+        }
+        CpInfo::Dynamic {
+            bootstrap_method_attr_index,
+            name_and_type_index,
+        } => {
+            u1(output, 17)?;
+            u2(output, *bootstrap_method_attr_index)?;
+            u2(output, *name_and_type_index)?;
+        }
+        CpInfo::InvokeDynamic {
+            bootstrap_method_attr_index,
+            name_and_type_index,
+        } => {
+            u1(output, 18)?;
+            u2(output, *bootstrap_method_attr_index)?;
+            u2(output, *name_and_type_index)?;
+        }
+        CpInfo::Module { name_index } => {
+            u1(output, 19)?;
+            u2(output, *name_index)?;
+        }
+        CpInfo::Package { name_index } => {
+            u1(output, 20)?;
+            u2(output, *name_index)?;
+        }
+    }
+    Ok(())
+}
+
+fn write_attributes<O: io::Write>(
+    output: &mut O,
+    attributes: &[AttributeInfo],
+) -> Result<(), ParseError> {
+    write_count(output, attributes.len(), "attributes_count")?;
+    for attribute in attributes {
+        let Ok(attribute_length) = u32::try_from(attribute.info.len()) else {
+            return Err(ParseError::Unsupported {
+                context: "attribute_length",
+            });
+        };
+        output.write_all(&attribute.attribute_name_index.to_be_bytes())?;
+        output.write_all(&attribute_length.to_be_bytes())?;
+        output.write_all(&attribute.info)?;
+    }
+    Ok(())
+}
+
+impl ClassFile {
+    /// Encode the class file, the inverse of [`parse`].
+    ///
+    /// Attributes are written from their `info` as they are; their `offset` is not used.
+    pub fn write<O: io::Write>(&self, output: &mut O) -> Result<(), ParseError> {
+        output.write_all(&self.magic.to_be_bytes())?;
+        output.write_all(&self.minor_version.to_be_bytes())?;
+        output.write_all(&self.major_version.to_be_bytes())?;
+
+        if self.constant_pool.is_empty() {
+            return Err(ParseError::EmptyConstantPool);
+        }
+        write_count(output, self.constant_pool.len(), "constant_pool_count")?;
+        // Index 0, and the slot after a long or double, are unusable.
+        let mut unusable = true;
+        for (index, entry) in self.constant_pool.iter().enumerate() {
+            match (entry, unusable) {
+                (None, true) => unusable = false,
+                (Some(entry), false) => {
+                    write_cp_info(output, index as u16, entry)?;
+                    unusable = matches!(entry, CpInfo::Long(..) | CpInfo::Double(..));
+                }
+                _ => {
+                    return Err(ParseError::Unsupported {
+                        context: "constant_pool",
+                    });
+                }
+            }
+        }
+        if unusable {
+            return Err(ParseError::Unsupported {
+                context: "constant_pool",
+            });
+        }
+
+        output.write_all(&self.access_flags.to_be_bytes())?;
+        output.write_all(&self.this_class.to_be_bytes())?;
+        output.write_all(&self.super_class.to_be_bytes())?;
+        write_count(output, self.interfaces.len(), "interfaces_count")?;
+        for interface in &self.interfaces {
+            output.write_all(&interface.to_be_bytes())?;
+        }
+
+        write_count(output, self.fields.len(), "fields_count")?;
+        for field in &self.fields {
+            output.write_all(&field.access_flags.to_be_bytes())?;
+            output.write_all(&field.name_index.to_be_bytes())?;
+            output.write_all(&field.descriptor_index.to_be_bytes())?;
+            write_attributes(output, &field.attributes)?;
+        }
+
+        write_count(output, self.methods.len(), "methods_count")?;
+        for method in &self.methods {
+            output.write_all(&method.access_flags.to_be_bytes())?;
+            output.write_all(&method.name_index.to_be_bytes())?;
+            output.write_all(&method.descriptor_index.to_be_bytes())?;
+            write_attributes(output, &method.attributes)?;
+        }
+
+        write_attributes(output, &self.attributes)
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, ParseError> {
+        let mut bytes = vec![];
+        self.write(&mut bytes)?;
+        Ok(bytes)
+    }
+}
+
+impl CpInfo {
+    /// Name of the constant kind, e.g. `CONSTANT_Utf8`.
+    pub(crate) fn kind(&self) -> &'static str {
+        match self {
+            CpInfo::Utf8(..) => "CONSTANT_Utf8",
+            CpInfo::Integer(..) => "CONSTANT_Integer",
+            CpInfo::Float(..) => "CONSTANT_Float",
+            CpInfo::Long(..) => "CONSTANT_Long",
+            CpInfo::Double(..) => "CONSTANT_Double",
+            CpInfo::Class { .. } => "CONSTANT_Class",
+            CpInfo::String { .. } => "CONSTANT_String",
+            CpInfo::Fieldref { .. } => "CONSTANT_Fieldref",
+            CpInfo::Methodref { .. } => "CONSTANT_Methodref",
+            CpInfo::InterfaceMethodref { .. } => "CONSTANT_InterfaceMethodref",
+            CpInfo::NameAndType { .. } => "CONSTANT_NameAndType",
+            CpInfo::MethodHandle { .. } => "CONSTANT_MethodHandle",
+            CpInfo::MethodType { .. } => "CONSTANT_MethodType",
+            CpInfo::Dynamic { .. } => "CONSTANT_Dynamic",
+            CpInfo::InvokeDynamic { .. } => "CONSTANT_InvokeDynamic",
+            CpInfo::Module { .. } => "CONSTANT_Module",
+            CpInfo::Package { .. } => "CONSTANT_Package",
+        }
+    }
+
+    /// Constant pool indexes this entry refers to.
+    pub(crate) fn references(&self) -> Vec<u16> {
+        match self {
+            CpInfo::Utf8(..)
+            | CpInfo::Integer(..)
+            | CpInfo::Float(..)
+            | CpInfo::Long(..)
+            | CpInfo::Double(..) => vec![],
+            CpInfo::Class { name_index }
+            | CpInfo::Module { name_index }
+            | CpInfo::Package { name_index } => vec![*name_index],
+            CpInfo::String { string_index } => vec![*string_index],
+            CpInfo::Fieldref {
+                class_index,
+                name_and_type_index,
+            }
+            | CpInfo::Methodref {
+                class_index,
+                name_and_type_index,
+            }
+            | CpInfo::InterfaceMethodref {
+                class_index,
+                name_and_type_index,
+            } => vec![*class_index, *name_and_type_index],
+            CpInfo::NameAndType {
+                name_index,
+                descriptor_index,
+            } => vec![*name_index, *descriptor_index],
+            CpInfo::MethodHandle {
+                reference_index, ..
+            } => vec![*reference_index],
+            CpInfo::MethodType { descriptor_index } => vec![*descriptor_index],
+            CpInfo::Dynamic {
+                name_and_type_index,
+                ..
+            }
+            | CpInfo::InvokeDynamic {
+                name_and_type_index,
+                ..
+            } => vec![*name_and_type_index],
+        }
+    }
+}
+
+fn mark(flags: &mut [bool], index: u16) {
+    if let Some(flag) = flags.get_mut(index as usize) {
+        *flag = true;
+    }
+}
+
+/// How a constant pool index is used where it is referred to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Usage {
+    /// A `CONSTANT_Utf8` holding a field or method descriptor, or a signature.
+    Descriptor,
+    Other,
+}
+
+impl ClassFile {
+    /// Resolve the constant pool entries one at a time, with their indexes.
+    ///
+    /// Unlike [`crate::wrap`], nothing is resolved before it is asked for, so scanning for a
+    /// constant can stop early. The unusable slots after `CONSTANT_Long` and `CONSTANT_Double`
+    /// are skipped.
+    pub fn resolved_constants(
+        &self,
+    ) -> impl Iterator<Item = (u16, Result<crate::CpInfo<&str>, ParseError>)> {
+        (0..self.constant_pool.len() as u16).filter_map(|index| {
+            let resolved = crate::parse_cp_info(&self.constant_pool, index).transpose()?;
+            Some((index, resolved))
+        })
+    }
+
+    /// The number of references to each constant, indexed like `constant_pool`, counting the
+    /// ones from other constants as well as from the class, its members and their attributes.
+    ///
+    /// Within attributes, the references of the predefined ones are counted, down to nested
+    /// attributes and instructions. Attributes of other names are taken as referring to nothing
+    /// but their name.
+    ///
+    /// Fails for malformed attributes, as [`crate::wrap`] does.
+    pub fn constant_reference_counts(&self) -> Result<Vec<u32>, ParseError> {
+        let mut counts = vec![0; self.constant_pool.len()];
+        let mut count = |index: u16| {
+            if let Some(count) = counts.get_mut(index as usize) {
+                *count += 1;
+            }
+        };
+        for entry in self.constant_pool.iter().flatten() {
+            for index in entry.references() {
+                count(index);
+            }
+        }
+        self.visit_references(&mut count)?;
+        Ok(counts)
+    }
+
+    /// Whether each constant, indexed like `constant_pool`, is referred to from the class, its
+    /// members or their attributes, directly or through other constants.
+    ///
+    /// Fails for malformed attributes, as [`crate::wrap`] does, and for references past the end
+    /// of the constant pool.
+    pub(crate) fn referenced_constants(&self) -> Result<Vec<bool>, ParseError> {
+        let mut referenced = vec![false; self.constant_pool.len()];
+        let mut pending = vec![];
+        self.visit_references(&mut |index| pending.push(index))?;
+        while let Some(index) = pending.pop() {
+            let Some(seen) = referenced.get_mut(index as usize) else {
+                return Err(ParseError::InvalidCpIndex {
+                    index,
+                    pool_size: self.constant_pool.len(),
+                });
+            };
+            if *seen {
+                continue;
+            }
+            *seen = true;
+            if let Some(Some(entry)) = self.constant_pool.get(index as usize) {
+                pending.extend(entry.references());
+            }
+        }
+        Ok(referenced)
+    }
+
+    /// Rename the class `from` to `to`, both internal names such as `com/example/Main`, in the
+    /// class names, descriptors and signatures of the constant pool and attributes.
+    ///
+    /// Constants are rewritten in place, unless also used otherwise, e.g. by a string constant
+    /// or as the name of a member, in which case those uses are moved to a copy of the constant
+    /// first. A constant shared with an attribute, which cannot be moved, is an error, as are
+    /// malformed attributes. Names of inner classes in signatures after `.` are not renamed.
+    pub fn rename_class(&mut self, from: &str, to: &str) -> Result<(), ParseError> {
+        let len = self.constant_pool.len();
+        let mut class_names = vec![false; len];
+        let mut descriptors = vec![false; len];
+        let mut others = vec![false; len];
+        for entry in self.constant_pool.iter().flatten() {
+            match entry {
+                CpInfo::Class { name_index } => mark(&mut class_names, *name_index),
+                CpInfo::NameAndType {
+                    name_index,
+                    descriptor_index,
+                } => {
+                    mark(&mut others, *name_index);
+                    mark(&mut descriptors, *descriptor_index);
+                }
+                CpInfo::MethodType { descriptor_index } => {
+                    mark(&mut descriptors, *descriptor_index)
+                }
+                entry => {
+                    for index in entry.references() {
+                        mark(&mut others, index);
+                    }
+                }
+            }
+        }
+        self.visit_references_with_usage(&mut |index, usage| match usage {
+            Usage::Descriptor => mark(&mut descriptors, index),
+            Usage::Other => mark(&mut others, index),
+        })?;
+
+        // Uses within attributes other than their names cannot be moved.
+        let mut fixed = vec![false; len];
+        let attributes = self
+            .attributes
+            .iter()
+            .chain(self.fields.iter().flat_map(|field| &field.attributes))
+            .chain(self.methods.iter().flat_map(|method| &method.attributes));
+        for attribute in attributes {
+            self.visit_attribute_references(
+                attribute.attribute_name_index,
+                &attribute.info,
+                MAX_NESTING_DEPTH,
+                &mut |index, usage| {
+                    if usage == Usage::Other {
+                        mark(&mut fixed, index);
+                    }
+                },
+            )?;
+        }
+
+        let mut renamed = vec![];
+        for (index, entry) in self.constant_pool.iter().enumerate() {
+            let Some(CpInfo::Utf8(value)) = entry else {
+                continue;
+            };
+            let value = if class_names[index] && value == from {
+                to.to_string()
+            } else if class_names[index] || descriptors[index] {
+                // Array classes are named by their descriptor.
+                let Some(value) = crate::signature::rename_class(value, from, to)? else {
+                    continue;
+                };
+                value
+            } else {
+                continue;
+            };
+            if fixed[index] {
+                return Err(ParseError::Unsupported {
+                    context: "rename_class CONSTANT_Utf8 shared with an attribute",
+                });
+            }
+            renamed.push((index, value));
+        }
+
+        let copies = renamed.iter().filter(|(index, _)| others[*index]).count();
+        if len + copies > u16::MAX as usize {
+            return Err(ParseError::LimitExceeded {
+                limit: "constant_pool_count",
+                max: u16::MAX as u64,
+            });
+        }
+        for (index, value) in renamed {
+            let Some(CpInfo::Utf8(old)) = self.constant_pool[index].replace(CpInfo::Utf8(value))
+            else {
+                unreachable!()
+            };
+            if others[index] {
+                let copy = self.constant_pool.len() as u16;
+                self.constant_pool.push(Some(CpInfo::Utf8(old)));
+                self.move_other_uses(index as u16, copy);
+            }
+        }
+        Ok(())
+    }
+
+    /// Rename the method `name` with the descriptor `descriptor` to `new_name`.
+    ///
+    /// Only the declaration is renamed, not the references to the method, which may as well be
+    /// to a method of another class. The name constant may be shared, e.g. with a
+    /// `CONSTANT_NameAndType`, so it is left untouched and the method points to a
+    /// `CONSTANT_Utf8` of `new_name` instead, added unless there is one already.
+    pub fn rename_method(
+        &mut self,
+        name: &str,
+        descriptor: &str,
+        new_name: &str,
+    ) -> Result<(), ParseError> {
+        let members = self
+            .methods
+            .iter()
+            .map(|method| (method.name_index, method.descriptor_index))
+            .collect::<Vec<_>>();
+        let (position, name_index) = self.rename_member(&members, name, descriptor, new_name)?;
+        self.methods[position].name_index = name_index;
+        Ok(())
+    }
+
+    /// Like [`ClassFile::rename_method`], for the field `name` with the descriptor `descriptor`.
+    pub fn rename_field(
+        &mut self,
+        name: &str,
+        descriptor: &str,
+        new_name: &str,
+    ) -> Result<(), ParseError> {
+        let members = self
+            .fields
+            .iter()
+            .map(|field| (field.name_index, field.descriptor_index))
+            .collect::<Vec<_>>();
+        let (position, name_index) = self.rename_member(&members, name, descriptor, new_name)?;
+        self.fields[position].name_index = name_index;
+        Ok(())
+    }
+
+    /// The position in `members`, pairs of `name_index` and `descriptor_index`, of the member to
+    /// rename, and the index of the `CONSTANT_Utf8` of its new name.
+    fn rename_member(
+        &mut self,
+        members: &[(u16, u16)],
+        name: &str,
+        descriptor: &str,
+        new_name: &str,
+    ) -> Result<(usize, u16), ParseError> {
+        let is = |index: u16, value: &str| {
+            matches!(
+                self.constant_pool.get(index as usize),
+                Some(Some(CpInfo::Utf8(utf8))) if utf8 == value
+            )
+        };
+        let Some(position) = members
+            .iter()
+            .position(|(n, d)| is(*n, name) && is(*d, descriptor))
+        else {
+            return Err(ParseError::MemberNotFound {
+                name: name.to_string(),
+                descriptor: descriptor.to_string(),
+            });
+        };
+        if members
+            .iter()
+            .any(|(n, d)| is(*n, new_name) && is(*d, descriptor))
+        {
+            return Err(ParseError::DuplicateMember {
+                name: new_name.to_string(),
+                descriptor: descriptor.to_string(),
+            });
+        }
+
+        let existing = self
+            .constant_pool
+            .iter()
+            .position(|entry| matches!(entry, Some(CpInfo::Utf8(utf8)) if utf8 == new_name));
+        let name_index = match existing {
+            Some(index) => index as u16,
+            None => {
+                if self.constant_pool.len() >= u16::MAX as usize {
+                    return Err(ParseError::LimitExceeded {
+                        limit: "constant_pool_count",
+                        max: u16::MAX as u64,
+                    });
+                }
+                self.constant_pool
+                    .push(Some(CpInfo::Utf8(new_name.to_string())));
+                (self.constant_pool.len() - 1) as u16
+            }
+        };
+        Ok((position, name_index))
+    }
+
+    /// Point the uses of `from` as other than a class name or a descriptor to `to`, except the
+    /// ones within attributes.
+    fn move_other_uses(&mut self, from: u16, to: u16) {
+        let repoint = |index: &mut u16| {
+            if *index == from {
+                *index = to;
+            }
+        };
+        for entry in self.constant_pool.iter_mut().flatten() {
+            match entry {
+                CpInfo::String {
+                    string_index: index,
+                }
+                | CpInfo::NameAndType {
+                    name_index: index, ..
+                }
+                | CpInfo::Module { name_index: index }
+                | CpInfo::Package { name_index: index } => repoint(index),
+                _ => {}
+            }
+        }
+        for (name_index, attributes) in self
+            .fields
+            .iter_mut()
+            .map(|f| (&mut f.name_index, &mut f.attributes))
+            .chain(
+                self.methods
+                    .iter_mut()
+                    .map(|m| (&mut m.name_index, &mut m.attributes)),
+            )
+        {
+            repoint(name_index);
+            for attribute in attributes {
+                repoint(&mut attribute.attribute_name_index);
+            }
+        }
+        for attribute in &mut self.attributes {
+            repoint(&mut attribute.attribute_name_index);
+        }
+    }
+
+    /// Call `visit` with every constant pool index referred to from outside the constant pool,
+    /// once per reference. Index 0, which stands for "none" where it is allowed, is skipped.
+    ///
+    /// Attributes are expected to be well-formed, as checked by [`crate::wrap`].
+    pub(crate) fn visit_references(&self, visit: &mut impl FnMut(u16)) -> Result<(), ParseError> {
+        self.visit_references_with_usage(&mut |index, _| visit(index))
+    }
+
+    /// Like [`ClassFile::visit_references`], also telling how each index is used.
+    fn visit_references_with_usage(
+        &self,
+        visit: &mut impl FnMut(u16, Usage),
+    ) -> Result<(), ParseError> {
+        let mut visit = |index: u16, usage| {
+            if index != 0 {
+                visit(index, usage)
+            }
+        };
+
+        visit(self.this_class, Usage::Other);
+        visit(self.super_class, Usage::Other);
+        for interface in &self.interfaces {
+            visit(*interface, Usage::Other);
+        }
+        for (name_index, descriptor_index, attributes) in self
+            .fields
+            .iter()
+            .map(|f| (f.name_index, f.descriptor_index, &f.attributes))
+            .chain(
+                self.methods
+                    .iter()
+                    .map(|m| (m.name_index, m.descriptor_index, &m.attributes)),
+            )
+        {
+            visit(name_index, Usage::Other);
+            visit(descriptor_index, Usage::Descriptor);
+            for attribute in attributes {
+                visit(attribute.attribute_name_index, Usage::Other);
+                self.visit_attribute_references(
+                    attribute.attribute_name_index,
+                    &attribute.info,
+                    MAX_NESTING_DEPTH,
+                    &mut visit,
+                )?;
+            }
+        }
+        for attribute in &self.attributes {
+            visit(attribute.attribute_name_index, Usage::Other);
+            self.visit_attribute_references(
+                attribute.attribute_name_index,
+                &attribute.info,
+                MAX_NESTING_DEPTH,
+                &mut visit,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Visit the references within `info`, excluding `attribute_name_index` itself, with
+    /// `depth` levels of nesting left.
+    fn visit_attribute_references(
+        &self,
+        attribute_name_index: u16,
+        info: &[u8],
+        depth: usize,
+        visit: &mut impl FnMut(u16, Usage),
+    ) -> Result<(), ParseError> {
+        struct References<F>(F);
+
+        impl<F: FnMut(u16, Usage)> crate::layout::Visit for References<F> {
+            fn reference(&mut self, reference: crate::layout::Reference) -> Result<(), ParseError> {
+                (self.0)(reference.index, reference.usage);
+                Ok(())
+            }
+        }
+
+        let Some(Some(CpInfo::Utf8(name))) = self.constant_pool.get(attribute_name_index as usize)
+        else {
+            return Ok(());
+        };
+        crate::layout::walk_attribute(
+            &self.constant_pool,
+            name,
+            info,
+            depth,
+            &mut References(visit),
+        )?;
+        Ok(())
+    }
 }
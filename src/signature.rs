@@ -0,0 +1,374 @@
+/// https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-4.html#jvms-4.7.9.1
+///
+/// Generic signatures are only rendered in Java source form; they are not modelled.
+use crate::descriptor::DescriptorError;
+use crate::raw::MAX_NESTING_DEPTH;
+
+/// A method signature with each part in Java source form.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct MethodSignature {
+    /// e.g. `<T extends java.lang.Comparable<T>>`, or empty without type parameters.
+    pub(crate) type_parameters: String,
+    pub(crate) parameters: Vec<String>,
+    pub(crate) return_type: String,
+    pub(crate) throws: Vec<String>,
+}
+
+struct Parser<'a> {
+    value: &'a str,
+    position: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self) -> DescriptorError {
+        DescriptorError {
+            value: self.value.to_string(),
+            position: self.position,
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.value.as_bytes().get(self.position).copied()
+    }
+
+    /// One level deeper than `depth` levels left, failing when none is left.
+    fn nest(&self, depth: usize) -> Result<usize, DescriptorError> {
+        depth.checked_sub(1).ok_or_else(|| self.error())
+    }
+
+    fn expect(&mut self, c: u8) -> Result<(), DescriptorError> {
+        if self.peek() != Some(c) {
+            return Err(self.error());
+        }
+        self.position += 1;
+        Ok(())
+    }
+
+    fn identifier(&mut self) -> Result<&str, DescriptorError> {
+        let start = self.position;
+        let len = self.value[start..]
+            .find(['.', ';', '[', '/', '<', '>', ':'])
+            .unwrap_or(self.value.len() - start);
+        if len == 0 {
+            return Err(self.error());
+        }
+        self.position += len;
+        Ok(&self.value[start..start + len])
+    }
+
+    fn java_type(&mut self, depth: usize) -> Result<String, DescriptorError> {
+        let name = match self.peek() {
+            Some(b'B') => "byte",
+            Some(b'C') => "char",
+            Some(b'D') => "double",
+            Some(b'F') => "float",
+            Some(b'I') => "int",
+            Some(b'J') => "long",
+            Some(b'S') => "short",
+            Some(b'Z') => "boolean",
+            _ => return self.reference_type(depth),
+        };
+        self.position += 1;
+        Ok(name.to_string())
+    }
+
+    /// Type arguments nest up to `depth` levels, and array dimensions without limit.
+    fn reference_type(&mut self, depth: usize) -> Result<String, DescriptorError> {
+        let depth = self.nest(depth)?;
+        match self.peek() {
+            Some(b'L') => self.class_type(depth),
+            Some(b'T') => {
+                self.position += 1;
+                let name = self.identifier()?.to_string();
+                self.expect(b';')?;
+                Ok(name)
+            }
+            Some(b'[') => {
+                let mut dimensions = 0;
+                while self.peek() == Some(b'[') {
+                    self.position += 1;
+                    dimensions += 1;
+                }
+                let mut rendered = self.java_type(depth)?;
+                rendered.push_str(&"[]".repeat(dimensions));
+                Ok(rendered)
+            }
+            _ => Err(self.error()),
+        }
+    }
+
+    fn class_type(&mut self, depth: usize) -> Result<String, DescriptorError> {
+        self.expect(b'L')?;
+        let mut rendered = self.identifier()?.to_string();
+        while self.peek() == Some(b'/') {
+            self.position += 1;
+            rendered.push('.');
+            rendered.push_str(self.identifier()?);
+        }
+        loop {
+            if self.peek() == Some(b'<') {
+                rendered.push_str(&self.type_arguments(depth)?);
+            }
+            if self.peek() != Some(b'.') {
+                break;
+            }
+            self.position += 1;
+            rendered.push('.');
+            rendered.push_str(self.identifier()?);
+        }
+        self.expect(b';')?;
+        Ok(rendered)
+    }
+
+    fn type_arguments(&mut self, depth: usize) -> Result<String, DescriptorError> {
+        self.expect(b'<')?;
+        let mut arguments = vec![];
+        while self.peek() != Some(b'>') {
+            let argument = match self.peek() {
+                Some(b'*') => {
+                    self.position += 1;
+                    "?".to_string()
+                }
+                Some(b'+') => {
+                    self.position += 1;
+                    format!("? extends {}", self.reference_type(depth)?)
+                }
+                Some(b'-') => {
+                    self.position += 1;
+                    format!("? super {}", self.reference_type(depth)?)
+                }
+                _ => self.reference_type(depth)?,
+            };
+            arguments.push(argument);
+        }
+        self.position += 1;
+        if arguments.is_empty() {
+            return Err(self.error());
+        }
+        Ok(format!("<{}>", arguments.join(", ")))
+    }
+
+    fn type_parameters(&mut self) -> Result<String, DescriptorError> {
+        self.expect(b'<')?;
+        let mut parameters = vec![];
+        while self.peek() != Some(b'>') {
+            let name = self.identifier()?.to_string();
+            self.expect(b':')?;
+            let mut bounds = vec![];
+            // The class bound may be empty when there are interface bounds.
+            if !matches!(self.peek(), Some(b':') | Some(b'>')) {
+                bounds.push(self.reference_type(MAX_NESTING_DEPTH)?);
+            }
+            while self.peek() == Some(b':') {
+                self.position += 1;
+                bounds.push(self.reference_type(MAX_NESTING_DEPTH)?);
+            }
+            bounds.retain(|bound| bound != "java.lang.Object");
+            if bounds.is_empty() {
+                parameters.push(name);
+            } else {
+                parameters.push(format!("{name} extends {}", bounds.join(" & ")));
+            }
+        }
+        self.position += 1;
+        if parameters.is_empty() {
+            return Err(self.error());
+        }
+        Ok(format!("<{}>", parameters.join(", ")))
+    }
+}
+
+pub(crate) fn parse_method_signature(value: &str) -> Result<MethodSignature, DescriptorError> {
+    let mut parser = Parser { value, position: 0 };
+    let type_parameters = if parser.peek() == Some(b'<') {
+        parser.type_parameters()?
+    } else {
+        String::new()
+    };
+
+    parser.expect(b'(')?;
+    let mut parameters = vec![];
+    while parser.peek() != Some(b')') {
+        parameters.push(parser.java_type(MAX_NESTING_DEPTH)?);
+    }
+    parser.position += 1;
+
+    let return_type = if parser.peek() == Some(b'V') {
+        parser.position += 1;
+        "void".to_string()
+    } else {
+        parser.java_type(MAX_NESTING_DEPTH)?
+    };
+
+    let mut throws = vec![];
+    while parser.peek() == Some(b'^') {
+        parser.position += 1;
+        throws.push(parser.reference_type(MAX_NESTING_DEPTH)?);
+    }
+    if parser.position != value.len() {
+        return Err(parser.error());
+    }
+
+    Ok(MethodSignature {
+        type_parameters,
+        parameters,
+        return_type,
+        throws,
+    })
+}
+
+/// Finds the class names in descriptors and signatures, which share the same grammar for types.
+struct ClassNames<'a> {
+    value: &'a [u8],
+    position: usize,
+    /// Ranges of the class names, excluding the simple names of inner classes after `.`.
+    found: Vec<std::ops::Range<usize>>,
+    /// Whether type arguments nest deeper than [`MAX_NESTING_DEPTH`] levels.
+    too_deep: bool,
+}
+
+impl ClassNames<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.value.get(self.position).copied()
+    }
+
+    fn expect(&mut self, c: u8) -> Option<()> {
+        (self.peek()? == c).then(|| self.position += 1)
+    }
+
+    /// One level deeper than `depth` levels left, failing when none is left.
+    fn nest(&mut self, depth: usize) -> Option<usize> {
+        let depth = depth.checked_sub(1);
+        self.too_deep |= depth.is_none();
+        depth
+    }
+
+    /// Up to, but excluding, one of `terminators`.
+    fn until(&mut self, terminators: &[u8]) -> Option<std::ops::Range<usize>> {
+        let start = self.position;
+        while !terminators.contains(&self.peek()?) {
+            self.position += 1;
+        }
+        (self.position > start).then_some(start..self.position)
+    }
+
+    fn java_type(&mut self, depth: usize) -> Option<()> {
+        let depth = self.nest(depth)?;
+        while self.peek()? == b'[' {
+            self.position += 1;
+        }
+        match self.peek()? {
+            b'B' | b'C' | b'D' | b'F' | b'I' | b'J' | b'S' | b'Z' | b'V' => self.position += 1,
+            b'T' => {
+                self.position += 1;
+                self.until(b";")?;
+                self.position += 1;
+            }
+            b'L' => {
+                self.position += 1;
+                let name = self.until(b"<.;")?;
+                self.found.push(name);
+                loop {
+                    match self.peek()? {
+                        b'<' => self.type_arguments(depth)?,
+                        b'.' => {
+                            self.position += 1;
+                            self.until(b"<.;")?;
+                        }
+                        _ => break,
+                    }
+                }
+                self.expect(b';')?;
+            }
+            _ => return None,
+        }
+        Some(())
+    }
+
+    fn type_arguments(&mut self, depth: usize) -> Option<()> {
+        self.expect(b'<')?;
+        while self.peek()? != b'>' {
+            match self.peek()? {
+                b'*' => self.position += 1,
+                b'+' | b'-' => {
+                    self.position += 1;
+                    self.java_type(depth)?;
+                }
+                _ => self.java_type(depth)?,
+            }
+        }
+        self.position += 1;
+        Some(())
+    }
+
+    fn type_parameters(&mut self) -> Option<()> {
+        self.expect(b'<')?;
+        while self.peek()? != b'>' {
+            self.until(b":")?;
+            self.position += 1;
+            if !matches!(self.peek()?, b':' | b'>') {
+                self.java_type(MAX_NESTING_DEPTH)?;
+            }
+            while self.peek()? == b':' {
+                self.position += 1;
+                self.java_type(MAX_NESTING_DEPTH)?;
+            }
+        }
+        self.position += 1;
+        Some(())
+    }
+
+    /// A field or method descriptor, or a class, field or method signature.
+    fn descriptor_or_signature(&mut self) -> Option<()> {
+        if self.peek() == Some(b'<') {
+            self.type_parameters()?;
+        }
+        while let Some(c) = self.peek() {
+            match c {
+                b'(' | b')' | b'^' => self.position += 1,
+                _ => self.java_type(MAX_NESTING_DEPTH)?,
+            }
+        }
+        Some(())
+    }
+}
+
+/// `value`, a descriptor or signature, with the class `from` replaced by `to`, or `None` when
+/// it does not refer to `from` or cannot be parsed. Type arguments nested deeper than
+/// [`MAX_NESTING_DEPTH`] levels are an error, as the class names in them would go unrenamed.
+pub(crate) fn rename_class(
+    value: &str,
+    from: &str,
+    to: &str,
+) -> Result<Option<String>, DescriptorError> {
+    let mut names = ClassNames {
+        value: value.as_bytes(),
+        position: 0,
+        found: vec![],
+        too_deep: false,
+    };
+    if names.descriptor_or_signature().is_none() {
+        if names.too_deep {
+            return Err(DescriptorError {
+                value: value.to_string(),
+                position: names.position,
+            });
+        }
+        return Ok(None);
+    }
+
+    let mut renamed = String::with_capacity(value.len());
+    let mut rest = 0;
+    for name in names.found {
+        if &value[name.clone()] == from {
+            renamed.push_str(&value[rest..name.start]);
+            renamed.push_str(to);
+            rest = name.end;
+        }
+    }
+    if rest == 0 {
+        return Ok(None);
+    }
+    renamed.push_str(&value[rest..]);
+    Ok(Some(renamed))
+}
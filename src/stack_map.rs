@@ -0,0 +1,254 @@
+/// https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-4.html#jvms-4.7.4
+use serde::Serialize;
+
+use crate::owned::{IntoOwned, owned_str};
+use crate::raw::{AttributeContext, ParseError};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum VerificationTypeInfo<S: AsRef<str>> {
+    TopVariable,
+    IntegerVariable,
+    FloatVariable,
+    DoubleVariable,
+    LongVariable,
+    NullVariable,
+    UninitializedThisVariable,
+    /// Internal name of the class, or the descriptor of an array class.
+    ObjectVariable(S),
+    /// `offset` is the one of the `new` instruction which created the object.
+    UninitializedVariable {
+        offset: u16,
+    },
+}
+
+/// A frame as encoded, with the offset relative to the previous frame.
+///
+/// The extended forms are kept apart from the compact ones, although they describe the same
+/// state, so that frames can be told apart as they are written in the class file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum StackMapFrame<S: AsRef<str>> {
+    /// `frame_type` 0-63.
+    SameFrame { offset_delta: u16 },
+    /// `frame_type` 64-127.
+    SameLocals1StackItemFrame {
+        offset_delta: u16,
+        stack: VerificationTypeInfo<S>,
+    },
+    /// `frame_type` 247.
+    SameLocals1StackItemFrameExtended {
+        offset_delta: u16,
+        stack: VerificationTypeInfo<S>,
+    },
+    /// `frame_type` 248-250, the last `chopped` locals being absent.
+    ChopFrame { offset_delta: u16, chopped: u8 },
+    /// `frame_type` 251.
+    SameFrameExtended { offset_delta: u16 },
+    /// `frame_type` 252-254.
+    AppendFrame {
+        offset_delta: u16,
+        locals: Vec<VerificationTypeInfo<S>>,
+    },
+    /// `frame_type` 255.
+    FullFrame {
+        offset_delta: u16,
+        locals: Vec<VerificationTypeInfo<S>>,
+        stack: Vec<VerificationTypeInfo<S>>,
+    },
+}
+
+impl<S: AsRef<str>> StackMapFrame<S> {
+    /// The offset of the frame is `offset_delta` for the first frame, and the offset of the
+    /// previous frame plus `offset_delta + 1` for the others.
+    pub fn offset_delta(&self) -> u16 {
+        match self {
+            Self::SameFrame { offset_delta }
+            | Self::SameLocals1StackItemFrame { offset_delta, .. }
+            | Self::SameLocals1StackItemFrameExtended { offset_delta, .. }
+            | Self::ChopFrame { offset_delta, .. }
+            | Self::SameFrameExtended { offset_delta }
+            | Self::AppendFrame { offset_delta, .. }
+            | Self::FullFrame { offset_delta, .. } => *offset_delta,
+        }
+    }
+}
+
+fn verification_type_info<S: AsRef<str>>(
+    input: &mut &[u8],
+    cx: AttributeContext<'_>,
+    object: &mut impl FnMut(u16) -> Result<S, ParseError>,
+) -> Result<VerificationTypeInfo<S>, ParseError> {
+    let tag = cx.u1(input, "StackMapTable verification_type_info tag")?;
+    let info = match tag {
+        0 => VerificationTypeInfo::TopVariable,
+        1 => VerificationTypeInfo::IntegerVariable,
+        2 => VerificationTypeInfo::FloatVariable,
+        3 => VerificationTypeInfo::DoubleVariable,
+        4 => VerificationTypeInfo::LongVariable,
+        5 => VerificationTypeInfo::NullVariable,
+        6 => VerificationTypeInfo::UninitializedThisVariable,
+        7 => {
+            let cpool_index = cx.u2(input, "StackMapTable Object_variable_info")?;
+            VerificationTypeInfo::ObjectVariable(object(cpool_index)?)
+        }
+        8 => VerificationTypeInfo::UninitializedVariable {
+            offset: cx.u2(input, "StackMapTable Uninitialized_variable_info")?,
+        },
+        _ => {
+            return Err(cx.malformed(format!("verification_type_info tag {tag}")));
+        }
+    };
+    Ok(info)
+}
+
+fn verification_type_infos<S: AsRef<str>>(
+    input: &mut &[u8],
+    cx: AttributeContext<'_>,
+    count: usize,
+    object: &mut impl FnMut(u16) -> Result<S, ParseError>,
+) -> Result<Vec<VerificationTypeInfo<S>>, ParseError> {
+    // Each entry takes at least a byte.
+    let mut items = Vec::with_capacity(count.min(input.len()));
+    for _ in 0..count {
+        items.push(verification_type_info(input, cx, object)?);
+    }
+    Ok(items)
+}
+
+/// Decode the `info` of a `StackMapTable`, resolving `Object_variable_info` with `object`.
+pub(crate) fn parse_stack_map_table<S: AsRef<str>>(
+    cx: AttributeContext<'_>,
+    mut object: impl FnMut(u16) -> Result<S, ParseError>,
+) -> Result<Vec<StackMapFrame<S>>, ParseError> {
+    let input = &mut &cx.info[..];
+    let number_of_entries = cx.u2(input, "StackMapTable number_of_entries")?;
+    let mut frames = Vec::with_capacity((number_of_entries as usize).min(input.len()));
+    for _ in 0..number_of_entries {
+        let frame_type = cx.u1(input, "StackMapTable frame_type")?;
+        let frame = match frame_type {
+            0..=63 => StackMapFrame::SameFrame {
+                offset_delta: frame_type as u16,
+            },
+            64..=127 => StackMapFrame::SameLocals1StackItemFrame {
+                offset_delta: frame_type as u16 - 64,
+                stack: verification_type_info(input, cx, &mut object)?,
+            },
+            247 => StackMapFrame::SameLocals1StackItemFrameExtended {
+                offset_delta: cx.u2(input, "StackMapTable offset_delta")?,
+                stack: verification_type_info(input, cx, &mut object)?,
+            },
+            248..=250 => StackMapFrame::ChopFrame {
+                offset_delta: cx.u2(input, "StackMapTable offset_delta")?,
+                chopped: 251 - frame_type,
+            },
+            251 => StackMapFrame::SameFrameExtended {
+                offset_delta: cx.u2(input, "StackMapTable offset_delta")?,
+            },
+            252..=254 => {
+                let offset_delta = cx.u2(input, "StackMapTable offset_delta")?;
+                let locals =
+                    verification_type_infos(input, cx, frame_type as usize - 251, &mut object)?;
+                StackMapFrame::AppendFrame {
+                    offset_delta,
+                    locals,
+                }
+            }
+            255 => {
+                let offset_delta = cx.u2(input, "StackMapTable offset_delta")?;
+                let number_of_locals = cx.u2(input, "StackMapTable number_of_locals")?;
+                let locals =
+                    verification_type_infos(input, cx, number_of_locals as usize, &mut object)?;
+                let number_of_stack_items = cx.u2(input, "StackMapTable number_of_stack_items")?;
+                let stack = verification_type_infos(
+                    input,
+                    cx,
+                    number_of_stack_items as usize,
+                    &mut object,
+                )?;
+                StackMapFrame::FullFrame {
+                    offset_delta,
+                    locals,
+                    stack,
+                }
+            }
+            // Reserved.
+            128..=246 => {
+                return Err(cx.malformed(format!("reserved frame_type {frame_type}")));
+            }
+        };
+        frames.push(frame);
+    }
+    if !input.is_empty() {
+        return Err(cx.length_mismatch("StackMapTable attribute_length"));
+    }
+    Ok(frames)
+}
+
+impl<S: AsRef<str>> IntoOwned for VerificationTypeInfo<S> {
+    type Owned = VerificationTypeInfo<String>;
+
+    fn into_owned(self) -> Self::Owned {
+        match self {
+            Self::TopVariable => VerificationTypeInfo::TopVariable,
+            Self::IntegerVariable => VerificationTypeInfo::IntegerVariable,
+            Self::FloatVariable => VerificationTypeInfo::FloatVariable,
+            Self::DoubleVariable => VerificationTypeInfo::DoubleVariable,
+            Self::LongVariable => VerificationTypeInfo::LongVariable,
+            Self::NullVariable => VerificationTypeInfo::NullVariable,
+            Self::UninitializedThisVariable => VerificationTypeInfo::UninitializedThisVariable,
+            Self::ObjectVariable(class) => VerificationTypeInfo::ObjectVariable(owned_str(class)),
+            Self::UninitializedVariable { offset } => {
+                VerificationTypeInfo::UninitializedVariable { offset }
+            }
+        }
+    }
+}
+
+impl<S: AsRef<str>> IntoOwned for StackMapFrame<S> {
+    type Owned = StackMapFrame<String>;
+
+    fn into_owned(self) -> Self::Owned {
+        match self {
+            Self::SameFrame { offset_delta } => StackMapFrame::SameFrame { offset_delta },
+            Self::SameLocals1StackItemFrame {
+                offset_delta,
+                stack,
+            } => StackMapFrame::SameLocals1StackItemFrame {
+                offset_delta,
+                stack: stack.into_owned(),
+            },
+            Self::SameLocals1StackItemFrameExtended {
+                offset_delta,
+                stack,
+            } => StackMapFrame::SameLocals1StackItemFrameExtended {
+                offset_delta,
+                stack: stack.into_owned(),
+            },
+            Self::ChopFrame {
+                offset_delta,
+                chopped,
+            } => StackMapFrame::ChopFrame {
+                offset_delta,
+                chopped,
+            },
+            Self::SameFrameExtended { offset_delta } => {
+                StackMapFrame::SameFrameExtended { offset_delta }
+            }
+            Self::AppendFrame {
+                offset_delta,
+                locals,
+            } => StackMapFrame::AppendFrame {
+                offset_delta,
+                locals: locals.into_owned(),
+            },
+            Self::FullFrame {
+                offset_delta,
+                locals,
+                stack,
+            } => StackMapFrame::FullFrame {
+                offset_delta,
+                locals: locals.into_owned(),
+                stack: stack.into_owned(),
+            },
+        }
+    }
+}
@@ -0,0 +1,411 @@
+/// Renders a class in the style of `javap`.
+use std::fmt::Write as _;
+
+use crate::descriptor::{FieldType, parse_field_descriptor, parse_method_descriptor};
+use crate::name::split_nested_name;
+use crate::signature::{MethodSignature, parse_method_signature};
+use crate::{
+    Annotation, AttributeInfo, ClassAccessFlags, ClassFile, ElementValue, FieldAccessFlags,
+    FieldInfo, MethodAccessFlags, MethodInfo,
+};
+
+#[derive(Debug, Clone)]
+pub struct TextOptions {
+    /// Number of spaces members are indented by.
+    pub indent: usize,
+    /// Sort fields and methods by name and descriptor instead of keeping the class file order.
+    pub sort_members: bool,
+}
+
+impl Default for TextOptions {
+    fn default() -> Self {
+        Self {
+            indent: 2,
+            sort_members: false,
+        }
+    }
+}
+
+fn java_name(internal_name: &str) -> String {
+    internal_name.replace('/', ".")
+}
+
+/// The name the constructors of `class` are declared with: the simple name from its own
+/// `InnerClasses` entry, or from [`split_nested_name`] without one. Anonymous classes cannot
+/// declare constructors, so theirs are named after the binary name, e.g. `com.example.Main$1`,
+/// as `javap` does.
+pub(crate) fn constructor_name<S: AsRef<str>, B: AsRef<[u8]>>(class: &ClassFile<S, B>) -> String {
+    let this_class = class.this_class.as_ref();
+    let inner_class = match class.attributes.by_name("InnerClasses") {
+        Some(AttributeInfo::InnerClasses(classes)) => classes
+            .iter()
+            .find(|inner_class| inner_class.inner_class_info.as_ref() == this_class),
+        _ => None,
+    };
+    match inner_class {
+        Some(inner_class) => match &inner_class.inner_name {
+            Some(inner_name) => inner_name.as_ref().to_string(),
+            None => java_name(this_class),
+        },
+        None => split_nested_name(this_class).1.to_string(),
+    }
+}
+
+/// The name a method is declared with, `class_name` for constructors, and `None` for the
+/// static initializer, which is declared as `static {}`. Without `class_name`, the names are
+/// kept as in the class file.
+fn declared_name<'a, S: AsRef<str>, B: AsRef<[u8]>>(
+    method: &'a MethodInfo<S, B>,
+    class_name: Option<&'a str>,
+) -> Option<&'a str> {
+    match (method.name.as_ref(), class_name) {
+        ("<init>", Some(class_name)) => Some(class_name),
+        ("<clinit>", Some(..)) => None,
+        (name, _) => Some(name),
+    }
+}
+
+/// Descriptors which cannot be parsed are written as they are.
+fn field_type(descriptor: &str) -> String {
+    match parse_field_descriptor(descriptor) {
+        Ok(field_type) => field_type.to_string(),
+        Err(..) => descriptor.to_string(),
+    }
+}
+
+fn element_value<S: AsRef<str>>(value: &ElementValue<S>) -> String {
+    match value {
+        ElementValue::Byte(value) => value.to_string(),
+        ElementValue::Char(value) => match char::from_u32(*value as u32) {
+            Some(c) => format!("{:?}", c),
+            None => format!("'\\u{value:04x}'"),
+        },
+        ElementValue::Double(value) => format!("{value:?}"),
+        ElementValue::Float(value) => format!("{value:?}f"),
+        ElementValue::Int(value) => value.to_string(),
+        ElementValue::Long(value) => format!("{value}L"),
+        ElementValue::Short(value) => value.to_string(),
+        ElementValue::Boolean(value) => value.to_string(),
+        ElementValue::String(value) => format!("{:?}", value.as_ref()),
+        ElementValue::EnumConst {
+            type_name,
+            const_name,
+        } => format!("{}.{}", field_type(type_name.as_ref()), const_name.as_ref()),
+        ElementValue::ClassInfo(class) => match class.as_ref() {
+            "V" => "void.class".to_string(),
+            class => format!("{}.class", field_type(class)),
+        },
+        ElementValue::Nested(nested) => annotation(nested),
+        ElementValue::Array(values) => {
+            let values = values.iter().map(element_value).collect::<Vec<_>>();
+            format!("{{{}}}", values.join(", "))
+        }
+    }
+}
+
+/// In Java source form, e.g. `@java.lang.Deprecated(since="9")`.
+fn annotation<S: AsRef<str>>(annotation: &Annotation<S>) -> String {
+    let mut text = format!("@{}", field_type(annotation.type_descriptor()));
+    match annotation.elements() {
+        [] => {}
+        [(name, value)] if name.as_ref() == "value" => {
+            write!(text, "({})", element_value(value)).unwrap();
+        }
+        elements => {
+            let elements = elements
+                .iter()
+                .map(|(name, value)| format!("{}={}", name.as_ref(), element_value(value)))
+                .collect::<Vec<_>>();
+            write!(text, "({})", elements.join(", ")).unwrap();
+        }
+    }
+    text
+}
+
+/// The annotations of a `package-info` class followed by the package declaration, if any.
+fn package_info<S: AsRef<str>, B: AsRef<[u8]>>(class: &ClassFile<S, B>) -> String {
+    let mut text = String::new();
+    for attribute in &class.attributes {
+        if let AttributeInfo::RuntimeVisibleAnnotations(annotations)
+        | AttributeInfo::RuntimeInvisibleAnnotations(annotations) = attribute
+        {
+            for item in annotations {
+                writeln!(text, "{}", annotation(item)).unwrap();
+            }
+        }
+    }
+    if let Some((package, _)) = class.this_class.as_ref().rsplit_once('/') {
+        writeln!(text, "package {};", java_name(package)).unwrap();
+    }
+    text
+}
+
+fn class_header<S: AsRef<str>, B: AsRef<[u8]>>(class: &ClassFile<S, B>) -> String {
+    let flags = class
+        .access_flags
+        .iter()
+        .fold(0, |flags, flag| flags | *flag as u16);
+    let is_interface = flags & ClassAccessFlags::AccInterface as u16 != 0;
+
+    let mut header = String::new();
+    for (flag, keyword) in [
+        (ClassAccessFlags::AccPublic, "public "),
+        (ClassAccessFlags::AccAbstract, "abstract "),
+        (ClassAccessFlags::AccFinal, "final "),
+    ] {
+        // Interfaces are always abstract.
+        if flags & flag as u16 != 0
+            && !(is_interface && matches!(flag, ClassAccessFlags::AccAbstract))
+        {
+            header.push_str(keyword);
+        }
+    }
+    header.push_str(if is_interface { "interface " } else { "class " });
+    header.push_str(&java_name(class.this_class.as_ref()));
+
+    if let Some(super_class) = &class.super_class
+        && super_class.as_ref() != "java/lang/Object"
+    {
+        write!(header, " extends {}", java_name(super_class.as_ref())).unwrap();
+    }
+    if !class.interfaces.is_empty() {
+        let interfaces = class
+            .interfaces
+            .iter()
+            .map(|interface| java_name(interface.as_ref()))
+            .collect::<Vec<_>>()
+            .join(",");
+        let keyword = if is_interface {
+            "extends"
+        } else {
+            "implements"
+        };
+        write!(header, " {keyword} {interfaces}").unwrap();
+    }
+    header
+}
+
+fn field<S: AsRef<str>, B: AsRef<[u8]>>(field: &FieldInfo<S, B>) -> String {
+    let flags = field
+        .access_flags
+        .iter()
+        .fold(0, |flags, flag| flags | *flag as u16);
+
+    let mut line = String::new();
+    for (flag, keyword) in [
+        (FieldAccessFlags::AccPublic, "public "),
+        (FieldAccessFlags::AccProtected, "protected "),
+        (FieldAccessFlags::AccPrivate, "private "),
+        (FieldAccessFlags::AccStatic, "static "),
+        (FieldAccessFlags::AccFinal, "final "),
+        (FieldAccessFlags::AccTransient, "transient "),
+        (FieldAccessFlags::AccVolatile, "volatile "),
+    ] {
+        if flags & flag as u16 != 0 {
+            line.push_str(keyword);
+        }
+    }
+    write!(
+        line,
+        "{} {};",
+        field_type(field.descriptor.as_ref()),
+        field.name.as_ref()
+    )
+    .unwrap();
+    line
+}
+
+fn method_flags<S: AsRef<str>, B: AsRef<[u8]>>(method: &MethodInfo<S, B>) -> u16 {
+    method
+        .access_flags
+        .iter()
+        .fold(0, |flags, flag| flags | *flag as u16)
+}
+
+fn method_modifiers(flags: u16) -> String {
+    let mut modifiers = String::new();
+    for (flag, keyword) in [
+        (MethodAccessFlags::AccPublic, "public "),
+        (MethodAccessFlags::AccProtected, "protected "),
+        (MethodAccessFlags::AccPrivate, "private "),
+        (MethodAccessFlags::AccAbstract, "abstract "),
+        (MethodAccessFlags::AccStatic, "static "),
+        (MethodAccessFlags::AccFinal, "final "),
+        (MethodAccessFlags::AccSynchronized, "synchronized "),
+        (MethodAccessFlags::AccNative, "native "),
+        (MethodAccessFlags::AccStrict, "strictfp "),
+    ] {
+        if flags & flag as u16 != 0 {
+            modifiers.push_str(keyword);
+        }
+    }
+    modifiers
+}
+
+fn declared_exceptions<S: AsRef<str>, B: AsRef<[u8]>>(method: &MethodInfo<S, B>) -> Vec<String> {
+    match method.attributes.by_name("Exceptions") {
+        Some(AttributeInfo::Exceptions(exceptions)) => exceptions
+            .iter()
+            .map(|exception| java_name(exception.as_ref()))
+            .collect(),
+        _ => vec![],
+    }
+}
+
+fn method<S: AsRef<str>, B: AsRef<[u8]>>(
+    method: &MethodInfo<S, B>,
+    class_name: &str,
+    is_interface: bool,
+) -> String {
+    let Some(name) = declared_name(method, Some(class_name)) else {
+        return "static {}".to_string();
+    };
+    let flags = method_flags(method);
+    let mut line = method_modifiers(flags);
+    if method.is_default_method(is_interface) {
+        line.push_str("default ");
+    }
+
+    let descriptor = method.descriptor.as_ref();
+    let Ok(parsed) = parse_method_descriptor(descriptor) else {
+        write!(line, "{name}{descriptor};").unwrap();
+        return line;
+    };
+
+    let varargs = flags & MethodAccessFlags::AccVarargs as u16 != 0;
+    let mut parameters = parsed
+        .parameters
+        .iter()
+        .map(|parameter| parameter.to_string())
+        .collect::<Vec<_>>();
+    if varargs
+        && let Some(FieldType::Array(component)) = parsed.parameters.last()
+        && let Some(last) = parameters.last_mut()
+    {
+        *last = format!("{component}...");
+    }
+    if method.name.as_ref() != "<init>" {
+        match &parsed.return_type {
+            Some(return_type) => write!(line, "{return_type} ").unwrap(),
+            None => line.push_str("void "),
+        }
+    }
+    write!(line, "{name}({})", parameters.join(", ")).unwrap();
+
+    let exceptions = declared_exceptions(method);
+    if !exceptions.is_empty() {
+        write!(line, " throws {}", exceptions.join(", ")).unwrap();
+    }
+    line.push(';');
+    line
+}
+
+/// The method as it would be declared in Java source, generics included.
+///
+/// Types come from the `Signature` attribute when it is present and well-formed, otherwise
+/// from the descriptor. Parameters are named `arg0`, `arg1`, ... as the class file does not
+/// need to record their names. Constructors are named `class_name` and have no return type,
+/// when it is given.
+pub(crate) fn java_signature<S: AsRef<str>, B: AsRef<[u8]>>(
+    method: &MethodInfo<S, B>,
+    class_name: Option<&str>,
+) -> String {
+    let Some(name) = declared_name(method, class_name) else {
+        return "static {}".to_string();
+    };
+    let flags = method_flags(method);
+    let mut signature = method_modifiers(flags);
+
+    let parsed = method
+        .signature()
+        .and_then(|signature| parse_method_signature(signature).ok());
+    let parsed = match parsed {
+        Some(parsed) => parsed,
+        None => {
+            let descriptor = method.descriptor.as_ref();
+            let Ok(descriptor) = parse_method_descriptor(descriptor) else {
+                write!(signature, "{name}{descriptor}").unwrap();
+                return signature;
+            };
+            MethodSignature {
+                type_parameters: String::new(),
+                parameters: descriptor
+                    .parameters
+                    .iter()
+                    .map(|parameter| parameter.to_string())
+                    .collect(),
+                return_type: match &descriptor.return_type {
+                    Some(return_type) => return_type.to_string(),
+                    None => "void".to_string(),
+                },
+                throws: vec![],
+            }
+        }
+    };
+
+    if !parsed.type_parameters.is_empty() {
+        write!(signature, "{} ", parsed.type_parameters).unwrap();
+    }
+
+    let varargs = flags & MethodAccessFlags::AccVarargs as u16 != 0;
+    let count = parsed.parameters.len();
+    let parameters = parsed
+        .parameters
+        .iter()
+        .enumerate()
+        .map(|(i, parameter)| match parameter.strip_suffix("[]") {
+            Some(component) if varargs && i + 1 == count => format!("{component}... arg{i}"),
+            _ => format!("{parameter} arg{i}"),
+        })
+        .collect::<Vec<_>>();
+    if class_name.is_none() || method.name.as_ref() != "<init>" {
+        write!(signature, "{} ", parsed.return_type).unwrap();
+    }
+    write!(signature, "{name}({})", parameters.join(", ")).unwrap();
+
+    let throws = if parsed.throws.is_empty() {
+        declared_exceptions(method)
+    } else {
+        parsed.throws
+    };
+    if !throws.is_empty() {
+        write!(signature, " throws {}", throws.join(", ")).unwrap();
+    }
+    signature
+}
+
+pub(crate) fn render<S: AsRef<str>, B: AsRef<[u8]>>(
+    class: &ClassFile<S, B>,
+    options: &TextOptions,
+) -> String {
+    let indent = " ".repeat(options.indent);
+    let mut text = String::new();
+
+    if let Some(AttributeInfo::SourceFile(source_file)) = class.attributes.by_name("SourceFile") {
+        writeln!(text, "Compiled from \"{}\"", source_file.as_ref()).unwrap();
+    }
+    if class.is_package_info() {
+        text.push_str(&package_info(class));
+        return text;
+    }
+    writeln!(text, "{} {{", class_header(class)).unwrap();
+
+    let mut fields = class.fields.iter().collect::<Vec<_>>();
+    let mut methods = class.methods.iter().collect::<Vec<_>>();
+    if options.sort_members {
+        fields.sort_by_key(|field| (field.name.as_ref(), field.descriptor.as_ref()));
+        methods.sort_by_key(|method| (method.name.as_ref(), method.descriptor.as_ref()));
+    }
+    for item in fields {
+        writeln!(text, "{indent}{}", field(item)).unwrap();
+    }
+    let is_interface = class.is_interface();
+    let class_name = constructor_name(class);
+    for item in methods {
+        let line = method(item, &class_name, is_interface);
+        writeln!(text, "{indent}{line}").unwrap();
+    }
+
+    text.push_str("}\n");
+    text
+}
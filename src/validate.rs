@@ -0,0 +1,214 @@
+/// Structural checks of a raw class file which do not prevent it from being parsed.
+use serde::Serialize;
+
+use std::io;
+
+use crate::ClassAccessFlags;
+use crate::layout::{Reference, Visit, walk_attribute};
+use crate::raw::{self, MAX_NESTING_DEPTH, ParseError, read_u2, read_u4};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum Finding {
+    /// `attribute_length` differs from the length the attribute's own counts add up to.
+    ///
+    /// `expected` is `None` when the counts point past the end of the attribute.
+    AttributeLength {
+        name: String,
+        /// Offset of the attribute from the beginning of the class file.
+        offset: u64,
+        declared: u32,
+        expected: Option<u32>,
+    },
+    /// A `CONSTANT_Utf8` is `length` bytes long, but the modified UTF-8 encoding the JVM
+    /// expects takes `expected` bytes.
+    ///
+    /// The two differ when the constant contains NUL or supplementary characters in standard
+    /// UTF-8 form. `expected` over 65535 means the string cannot be stored in one constant.
+    Utf8Length {
+        index: u16,
+        length: u16,
+        expected: usize,
+    },
+    /// `ACC_SUPER` is not set on a class of version 52 or later.
+    ///
+    /// The JVM has ignored the flag since then, but compilers still set it on every class, so its
+    /// absence hints at hand-crafted or tampered bytecode.
+    MissingAccSuper,
+    /// A `Code` attribute with a `code_length` of 0.
+    ///
+    /// It decodes to no instructions, but a method with code always ends with at least a
+    /// `return` or `athrow`, so it hints at a broken generator.
+    EmptyCode {
+        /// Offset of the attribute from the beginning of the class file.
+        offset: u64,
+    },
+}
+
+/// Findings of [`validate`], in file order.
+#[derive(Debug, Default, Serialize)]
+pub struct ParseReport {
+    findings: Vec<Finding>,
+}
+
+impl ParseReport {
+    pub fn findings(&self) -> &[Finding] {
+        &self.findings
+    }
+
+    pub fn is_clean(&self) -> bool {
+        self.findings.is_empty()
+    }
+}
+
+/// Check `raw` for structural inconsistencies.
+///
+/// The parser already rejects class files with bytes after the last attribute, so only the
+/// lengths of attributes whose layout is known are checked here, including the ones nested in
+/// `Code`, along with empty code, the encoding of `CONSTANT_Utf8` entries and the class access
+/// flags.
+pub fn validate(raw: &raw::ClassFile) -> ParseReport {
+    let mut report = ParseReport::default();
+    let is_class = raw.access_flags
+        & (ClassAccessFlags::AccInterface as u16 | ClassAccessFlags::AccModule as u16)
+        == 0;
+    if raw.major_version >= 52
+        && is_class
+        && raw.access_flags & ClassAccessFlags::AccSuper as u16 == 0
+    {
+        report.findings.push(Finding::MissingAccSuper);
+    }
+
+    for (index, entry) in raw.constant_pool.iter().enumerate() {
+        let Some(raw::CpInfo::Utf8(value)) = entry else {
+            continue;
+        };
+        let expected = modified_utf8_length(value);
+        if expected != value.len() {
+            report.findings.push(Finding::Utf8Length {
+                index: index as u16,
+                length: value.len() as u16,
+                expected,
+            });
+        }
+    }
+
+    let attributes = raw
+        .fields
+        .iter()
+        .flat_map(|field| &field.attributes)
+        .chain(raw.methods.iter().flat_map(|method| &method.attributes))
+        .chain(&raw.attributes);
+    for attribute in attributes {
+        check_attribute(
+            raw,
+            attribute.attribute_name_index,
+            attribute.offset,
+            &attribute.info,
+            MAX_NESTING_DEPTH,
+            &mut report,
+        );
+    }
+    report
+}
+
+fn check_attribute(
+    raw: &raw::ClassFile,
+    attribute_name_index: u16,
+    offset: u64,
+    info: &[u8],
+    depth: usize,
+    report: &mut ParseReport,
+) {
+    let Some(Some(raw::CpInfo::Utf8(name))) = raw.constant_pool.get(attribute_name_index as usize)
+    else {
+        return;
+    };
+
+    let expected = expected_length(&raw.constant_pool, name, info);
+    if expected != Some(info.len()) {
+        report.findings.push(Finding::AttributeLength {
+            name: name.clone(),
+            offset,
+            declared: info.len() as u32,
+            expected: expected.map(|expected| expected as u32),
+        });
+    }
+
+    if name == "Code" && info.get(4..8) == Some(&[0; 4]) {
+        report.findings.push(Finding::EmptyCode { offset });
+    }
+
+    // Deeper attributes are left to `wrap`, which rejects them.
+    if name == "Code"
+        && let Some(depth) = depth.checked_sub(1)
+    {
+        for (attribute_name_index, position, info) in code_attributes(info).unwrap_or_default() {
+            check_attribute(
+                raw,
+                attribute_name_index,
+                offset + 6 + position as u64,
+                info,
+                depth,
+                report,
+            );
+        }
+    }
+}
+
+/// https://docs.oracle.com/javase/specs/jvms/se25/html/jvms-4.html#jvms-4.4.7
+fn modified_utf8_length(value: &str) -> usize {
+    value
+        .chars()
+        .map(|c| match c as u32 {
+            0 => 2,
+            0x01..=0x7f => 1,
+            0x80..=0x7ff => 2,
+            0x800..=0xffff => 3,
+            // Encoded as a surrogate pair of three bytes each.
+            _ => 6,
+        })
+        .sum()
+}
+
+/// The length implied by the counts within `info`, or `None` when they point past the end.
+///
+/// Attributes of unknown layout, and the ones malformed otherwise, are taken as they are.
+fn expected_length(pool: &[Option<raw::CpInfo>], name: &str, info: &[u8]) -> Option<usize> {
+    /// Skips over the instructions and nested attributes, checked on their own.
+    struct Lengths;
+
+    impl Visit for Lengths {
+        const DEEP: bool = false;
+
+        fn reference(&mut self, _: Reference) -> Result<(), ParseError> {
+            Ok(())
+        }
+    }
+
+    match walk_attribute(pool, name, info, MAX_NESTING_DEPTH, &mut Lengths) {
+        Ok(length) => Some(length.unwrap_or(info.len())),
+        Err(ParseError::Io(e)) if e.kind() == io::ErrorKind::UnexpectedEof => None,
+        Err(_) => Some(info.len()),
+    }
+}
+
+/// `(attribute_name_index, position, info)` of the attributes nested in a well-formed `Code`.
+fn code_attributes(info: &[u8]) -> Option<Vec<(u16, usize, &[u8])>> {
+    let mut input = info.get(4..)?;
+    let code_length = read_u4(&mut input).ok()? as usize;
+    input = input.get(code_length..)?;
+    let exception_table_length = read_u2(&mut input).ok()? as usize;
+    input = input.get(8 * exception_table_length..)?;
+
+    let attributes_count = read_u2(&mut input).ok()?;
+    let mut attributes = Vec::with_capacity(attributes_count as usize);
+    for _ in 0..attributes_count {
+        let position = info.len() - input.len();
+        let attribute_name_index = read_u2(&mut input).ok()?;
+        let attribute_length = read_u4(&mut input).ok()? as usize;
+        let (body, rest) = input.split_at_checked(attribute_length)?;
+        input = rest;
+        attributes.push((attribute_name_index, position, body));
+    }
+    Some(attributes)
+}
@@ -0,0 +1,130 @@
+/// The API of a class without any attribute, for scanning many classes quickly.
+use std::io;
+
+use serde::Serialize;
+
+use crate::raw::{self, ParseError};
+use crate::{ClassAccessFlags, ClassFileVersion, FieldAccessFlags, MethodAccessFlags};
+
+/// Flags, names and descriptors of a class and its members, as read by
+/// [`crate::parse_signatures_only`].
+#[derive(Debug, Serialize)]
+pub struct SignatureView {
+    version: ClassFileVersion,
+    access_flags: Vec<ClassAccessFlags>,
+    this_class: String,
+    super_class: Option<String>,
+    interfaces: Vec<String>,
+    fields: Vec<MemberSignature<FieldAccessFlags>>,
+    methods: Vec<MemberSignature<MethodAccessFlags>>,
+}
+
+/// A field, with `F` being [`FieldAccessFlags`], or a method, with [`MethodAccessFlags`].
+#[derive(Debug, Serialize)]
+pub struct MemberSignature<F> {
+    access_flags: Vec<F>,
+    name: String,
+    descriptor: String,
+}
+
+impl SignatureView {
+    pub fn version(&self) -> &ClassFileVersion {
+        &self.version
+    }
+
+    pub fn access_flags(&self) -> &[ClassAccessFlags] {
+        &self.access_flags
+    }
+
+    /// Internal name of the class, e.g. `com/example/Main`.
+    pub fn this_class(&self) -> &str {
+        &self.this_class
+    }
+
+    /// `None` for `java/lang/Object` and modules.
+    pub fn super_class(&self) -> Option<&str> {
+        self.super_class.as_deref()
+    }
+
+    pub fn interfaces(&self) -> &[String] {
+        &self.interfaces
+    }
+
+    pub fn fields(&self) -> &[MemberSignature<FieldAccessFlags>] {
+        &self.fields
+    }
+
+    pub fn methods(&self) -> &[MemberSignature<MethodAccessFlags>] {
+        &self.methods
+    }
+}
+
+impl<F> MemberSignature<F> {
+    pub fn access_flags(&self) -> &[F] {
+        &self.access_flags
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn descriptor(&self) -> &str {
+        &self.descriptor
+    }
+}
+
+fn utf8(pool: &[Option<raw::CpInfo>], index: u16) -> Result<String, ParseError> {
+    crate::resolve_utf8(pool, index).map(str::to_string)
+}
+
+fn class_name(pool: &[Option<raw::CpInfo>], index: u16) -> Result<String, ParseError> {
+    crate::resolve_class(pool, index).map(str::to_string)
+}
+
+pub(crate) fn parse<I: io::Read>(input: &mut I) -> Result<SignatureView, ParseError> {
+    let raw = raw::parse_skipping_attributes(input)?;
+    let pool = &raw.constant_pool;
+
+    let fields = raw
+        .fields
+        .iter()
+        .map(|field| {
+            Ok(MemberSignature {
+                access_flags: crate::parse_field_access_flags(field.access_flags),
+                name: utf8(pool, field.name_index)?,
+                descriptor: utf8(pool, field.descriptor_index)?,
+            })
+        })
+        .collect::<Result<_, ParseError>>()?;
+    let methods = raw
+        .methods
+        .iter()
+        .map(|method| {
+            Ok(MemberSignature {
+                access_flags: crate::parse_method_access_flags(method.access_flags),
+                name: utf8(pool, method.name_index)?,
+                descriptor: utf8(pool, method.descriptor_index)?,
+            })
+        })
+        .collect::<Result<_, ParseError>>()?;
+
+    Ok(SignatureView {
+        version: ClassFileVersion {
+            major_version: raw.major_version,
+            minor_version: raw.minor_version,
+        },
+        access_flags: crate::parse_class_access_flags(raw.access_flags),
+        this_class: crate::validate_this_class(&raw)?.to_string(),
+        super_class: match raw.super_class {
+            0 => None,
+            index => Some(class_name(pool, index)?),
+        },
+        interfaces: raw
+            .interfaces
+            .iter()
+            .map(|index| class_name(pool, *index))
+            .collect::<Result<_, _>>()?,
+        fields,
+        methods,
+    })
+}
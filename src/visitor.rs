@@ -0,0 +1,58 @@
+/// Callbacks for the members of a class as they are read, so that they need not all be kept.
+use std::io;
+
+use crate::raw::{self, Member, ParseError};
+use crate::{
+    AttributeInfo, AttributeNames, FieldInfo, MethodInfo, parse_attribute_info, parse_field,
+    parse_method, validate_this_class,
+};
+
+/// Receives each field, method and class attribute from [`crate::parse_with_visitor`], in file
+/// order, dropped as soon as the callback returns. Every callback does nothing by default.
+pub trait ClassVisitor {
+    fn visit_field(&mut self, field: &FieldInfo<&str, &[u8]>) {
+        let _ = field;
+    }
+
+    fn visit_method(&mut self, method: &MethodInfo<&str, &[u8]>) {
+        let _ = method;
+    }
+
+    /// Attributes of the class itself; those of a field or method come with it.
+    fn visit_attribute(&mut self, attribute: &AttributeInfo<&str, &[u8]>) {
+        let _ = attribute;
+    }
+}
+
+pub(crate) fn parse<I: io::Read, V: ClassVisitor + ?Sized>(
+    input: &mut I,
+    visitor: &mut V,
+) -> Result<raw::ClassFile, ParseError> {
+    let (classfile, mut members) = raw::parse_members(input)?;
+    validate_this_class(&classfile)?;
+
+    let pool = &classfile.constant_pool;
+    let names = AttributeNames::new(pool);
+    let depth = raw::MAX_NESTING_DEPTH;
+    while let Some(member) = members.next()? {
+        match member {
+            Member::Field(field) => {
+                visitor.visit_field(&parse_field(pool, &names, depth, &field)?);
+            }
+            Member::Method(method) => {
+                visitor.visit_method(&parse_method(pool, &names, depth, &method)?);
+            }
+            Member::Attribute(attribute) => {
+                visitor.visit_attribute(&parse_attribute_info(
+                    pool,
+                    &names,
+                    depth,
+                    attribute.attribute_name_index,
+                    attribute.offset,
+                    &attribute.info,
+                )?);
+            }
+        }
+    }
+    Ok(classfile)
+}
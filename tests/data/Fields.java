@@ -0,0 +1,8 @@
+package com.example;
+
+import java.util.List;
+
+public class Fields {
+
+    List<String>[] lists;
+}
@@ -0,0 +1,17 @@
+package com.example;
+
+public interface Greeter {
+
+    void greet();
+
+    default void hello() {
+        helper();
+    }
+
+    static Greeter create() {
+        return null;
+    }
+
+    private void helper() {
+    }
+}
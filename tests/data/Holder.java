@@ -0,0 +1,6 @@
+package com.example;
+
+import java.util.ArrayList;
+
+public class Holder<T> extends ArrayList<T> {
+}
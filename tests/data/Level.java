@@ -0,0 +1,10 @@
+package com.example;
+
+public enum Level {
+    LOW,
+    HIGH;
+
+    private transient int uses;
+
+    protected volatile String label;
+}
@@ -1,6 +1,9 @@
 package com.example;
 
+import java.io.IOException;
 import java.io.Serializable;
+import java.util.Arrays;
+import java.util.List;
 
 public class Main implements Serializable, Cloneable {
 
@@ -14,8 +17,57 @@ public class Main implements Serializable, Cloneable {
 
     public static final String CONDY_PLEASE = "Condy" + "!!!";
 
+    public static final String INTERNAL_NAME = "com/example/Main";
+
+    private final Runnable anonymous = new Runnable() {
+        @Override
+        public void run() {
+        }
+    };
+
+    private static final List<String> NAMES = Arrays.asList("a", "b");
+
     public static void main() throws Exception {
         Runnable indyPlease = () -> System.out.println("Hello, World!");
         indyPlease.run();
     }
+
+    public static <T extends Comparable<T>> List<T> of(T... elems) throws IOException {
+        return Arrays.asList(elems);
+    }
+
+    // Blocks longer than 63 bytes need the extended StackMapTable frames.
+    public static int frames(int x) {
+        try {
+            x = x * 31 + 1;
+            x = x * 31 + 1;
+            x = x * 31 + 1;
+            x = x * 31 + 1;
+            x = x * 31 + 1;
+            x = x * 31 + 1;
+            x = x * 31 + 1;
+            x = x * 31 + 1;
+            x = x * 31 + 1;
+            x = x * 31 + 1;
+            x = x * 31 + 1;
+            x = x * 31 + 1;
+        } catch (RuntimeException e) {
+            x = 0;
+        }
+        if (x > 0) {
+            x = x * 31 + 1;
+            x = x * 31 + 1;
+            x = x * 31 + 1;
+            x = x * 31 + 1;
+            x = x * 31 + 1;
+            x = x * 31 + 1;
+            x = x * 31 + 1;
+            x = x * 31 + 1;
+            x = x * 31 + 1;
+            x = x * 31 + 1;
+            x = x * 31 + 1;
+            x = x * 31 + 1;
+        }
+        return x;
+    }
 }
@@ -0,0 +1,10 @@
+package com.example;
+
+public @interface Marker {
+
+    String value() default "none";
+
+    int[] counts() default {1, 2};
+
+    Class<?> type();
+}
@@ -0,0 +1,19 @@
+package com.example;
+
+import java.util.function.Supplier;
+
+public class Methods implements Comparable<Methods> {
+
+    static final Supplier<String> NAME = () -> "methods";
+
+    protected synchronized native void poke();
+
+    private static int sum(int... values) {
+        return values.length;
+    }
+
+    @Override
+    public int compareTo(Methods other) {
+        return 0;
+    }
+}
@@ -0,0 +1,10 @@
+package com.example;
+
+public class Nested {
+
+    protected static class Inner {
+    }
+
+    private abstract class Hidden {
+    }
+}
@@ -0,0 +1,16 @@
+package com.example;
+
+@Deprecated
+public class Old {
+
+    /** Also the name of an attribute, which the pool then has. */
+    @Deprecated
+    public static final String MARKER = "Synthetic";
+
+    @Deprecated
+    public void old() {
+    }
+
+    public void current() {
+    }
+}
@@ -0,0 +1,35 @@
+package com.example;
+
+abstract class Shape {
+
+    abstract double area();
+
+    static Shape unit() {
+        return null;
+    }
+
+    private void reset() {
+    }
+}
+
+public class Overrides extends Shape {
+
+    @Override
+    double area() {
+        return 0.0d;
+    }
+
+    // Hides, not overrides.
+    static Shape unit() {
+        return null;
+    }
+
+    // Shape.reset is private.
+    void reset() {
+    }
+
+    @Override
+    public String toString() {
+        return "Overrides";
+    }
+}
@@ -0,0 +1,6 @@
+package com.example;
+
+import java.util.List;
+
+public record Pair<T>(int count, List<T> items) {
+}
@@ -0,0 +1,14 @@
+package com.example;
+
+public class Parameters {
+
+    enum Color {
+        RED,
+        GREEN,
+    }
+
+    class Inner {
+        Inner(final int value) {
+        }
+    }
+}
@@ -0,0 +1,10 @@
+package com.example;
+
+public sealed interface Shape {
+
+    record Circle(double radius) implements Shape {
+    }
+
+    final class Square implements Shape {
+    }
+}
@@ -0,0 +1,25 @@
+package com.example;
+
+import java.lang.annotation.ElementType;
+import java.lang.annotation.Retention;
+import java.lang.annotation.RetentionPolicy;
+import java.lang.annotation.Target;
+import java.util.List;
+
+public class TypeUses<@TypeUses.Nullable T> {
+
+    @Retention(RetentionPolicy.RUNTIME)
+    @Target({ElementType.TYPE_USE, ElementType.TYPE_PARAMETER})
+    @interface Nullable {
+    }
+
+    List<@Nullable String> names;
+
+    @Nullable String[] array() {
+        return null;
+    }
+
+    Object cast(Object o) {
+        return (@Nullable String) o;
+    }
+}
@@ -0,0 +1,2 @@
+@Deprecated(since = "1.0", forRemoval = true)
+package com.example;
@@ -1,3 +1,4 @@
+use std::borrow::Cow;
 use std::fs;
 use std::path::Path;
 use std::process::Command;
@@ -8,10 +9,19 @@ use tempfile::{TempDir, tempdir};
 fn javac<I: IntoIterator<Item = P>, P: AsRef<Path> + AsRef<OsStr>>(
     srcdir: P,
     files: I,
+) -> anyhow::Result<TempDir> {
+    javac_with_args(srcdir, files, [] as [&str; 0])
+}
+
+fn javac_with_args<I: IntoIterator<Item = P>, P: AsRef<Path> + AsRef<OsStr>, A: AsRef<OsStr>>(
+    srcdir: P,
+    files: I,
+    args: impl IntoIterator<Item = A>,
 ) -> anyhow::Result<TempDir> {
     let output = tempdir()?;
 
     Command::new("javac")
+        .args(args)
         .arg("--source-path")
         .arg(srcdir)
         .arg("-d")
@@ -22,22 +32,3836 @@ fn javac<I: IntoIterator<Item = P>, P: AsRef<Path> + AsRef<OsStr>>(
     Ok(output)
 }
 
+fn attribute<'a>(attributes: &'a serde_json::Value, name: &str) -> Option<&'a serde_json::Value> {
+    attributes.as_array()?.iter().find_map(|attr| {
+        if cfg!(feature = "tagged") {
+            (attr["tag"] == name).then(|| &attr["value"])
+        } else {
+            attr.get(name)
+        }
+    })
+}
+
+/// The constants the class attribute `name` of the class file at `path` refers to, resolved and
+/// sorted by their JSON, as told by the reference counts with and without it.
+fn attribute_references(path: &Path, name: &str) -> anyhow::Result<Vec<serde_json::Value>> {
+    let mut raw = libjcdump::parse_raw(&mut fs::File::open(path)?)?;
+    let counts = raw.constant_reference_counts()?;
+    let (name_index, _) = raw
+        .resolved_constants()
+        .find(|(_, constant)| matches!(constant, Ok(libjcdump::CpInfo::Utf8(n)) if *n == name))
+        .unwrap();
+    raw.attributes
+        .retain(|attribute| attribute.attribute_name_index != name_index);
+    let without = raw.constant_reference_counts()?;
+
+    let mut references = vec![];
+    for (index, constant) in raw.resolved_constants() {
+        let index = index as usize;
+        for _ in without[index]..counts[index] {
+            references.push(serde_json::to_value(constant.as_ref().unwrap())?);
+        }
+    }
+    references.sort_by_cached_key(|reference| reference.to_string());
+    Ok(references)
+}
+
 #[test]
 fn simple() -> anyhow::Result<()> {
     let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
     let output = javac(srcdir.clone(), [srcdir.join("Main.java")])?;
 
     let mut main = fs::File::open(output.path().join("./com/example/Main.class"))?;
-    let raw = jcdump::parse_raw(&mut main)?;
-    let data = jcdump::wrap(&raw)?;
+    let raw = libjcdump::parse_raw(&mut main)?;
+    let data = libjcdump::wrap(&raw)?;
     serde_json::to_writer_pretty(io::stdout(), &data)?;
     println!();
 
     let mut module = fs::File::open(output.path().join("./module-info.class"))?;
-    let raw = jcdump::parse_raw(&mut module)?;
-    let data = jcdump::wrap(&raw)?;
+    let raw = libjcdump::parse_raw(&mut module)?;
+    let data = libjcdump::wrap(&raw)?;
     serde_json::to_writer_pretty(io::stdout(), &data)?;
     println!();
 
+    let Some(libjcdump::AttributeInfo::Module(module)) = data.attributes().by_name("Module") else {
+        panic!("no Module attribute");
+    };
+    assert_eq!(module.name(), "com.example");
+    assert!(module.flags().is_empty());
+    let [java_base] = module.requires() else {
+        panic!("{:?}", module.requires());
+    };
+    assert_eq!(java_base.module(), "java.base");
+    assert_eq!(
+        java_base.flags(),
+        [libjcdump::RequiresAccessFlags::AccMandated]
+    );
+    assert!(java_base.version().is_some());
+    let [exports] = module.exports() else {
+        panic!("{:?}", module.exports());
+    };
+    assert_eq!(exports.package(), "com/example");
+    assert!(exports.to().is_empty());
+    assert!(module.opens().is_empty() && module.uses().is_empty());
+    assert!(module.provides().is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn enclosing_method_in_field_initializer() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Main.java")])?;
+
+    let mut anonymous = fs::File::open(output.path().join("./com/example/Main$1.class"))?;
+    let raw = libjcdump::parse_raw(&mut anonymous)?;
+    let data = libjcdump::wrap(&raw)?;
+    assert_eq!(data.enclosing_class(), Some("com/example/Main"));
+    assert_eq!(data.enclosing_method(), None);
+    let data = data.to_json_value()?;
+
+    let enclosing = attribute(&data["attributes"], "EnclosingMethod").unwrap();
+    assert_eq!(enclosing["class"], "com/example/Main");
+    assert!(enclosing["method"].is_null());
+
+    let mut main = fs::File::open(output.path().join("./com/example/Main.class"))?;
+    let raw = libjcdump::parse_raw(&mut main)?;
+    let data = libjcdump::wrap(&raw)?;
+    assert_eq!(data.enclosing_class(), None);
+
+    Ok(())
+}
+
+#[test]
+fn nest_attributes() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Main.java")])?;
+
+    let mut main = fs::File::open(output.path().join("./com/example/Main.class"))?;
+    let raw = libjcdump::parse_raw(&mut main)?;
+    let data = libjcdump::wrap(&raw)?;
+    assert_eq!(data.nest_host(), None);
+    assert_eq!(data.nest_members(), Some(&["com/example/Main$1"][..]));
+
+    let mut anonymous = fs::File::open(output.path().join("./com/example/Main$1.class"))?;
+    let raw = libjcdump::parse_raw(&mut anonymous)?;
+    let data = libjcdump::wrap(&raw)?;
+    assert_eq!(data.nest_host(), Some("com/example/Main"));
+    assert_eq!(data.nest_members(), None);
+
+    Ok(())
+}
+
+#[test]
+fn nest_attribute_references() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Main.java")])?;
+
+    let class = |name| serde_json::to_value(libjcdump::CpInfo::Class { name }).unwrap();
+    let utf8 = |value| serde_json::to_value(libjcdump::CpInfo::Utf8(value)).unwrap();
+    assert_eq!(
+        attribute_references(
+            &output.path().join("./com/example/Main.class"),
+            "NestMembers"
+        )?,
+        [class("com/example/Main$1"), utf8("NestMembers")]
+    );
+    assert_eq!(
+        attribute_references(
+            &output.path().join("./com/example/Main$1.class"),
+            "NestHost"
+        )?,
+        [class("com/example/Main"), utf8("NestHost")]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn effective_access_flags() -> anyhow::Result<()> {
+    use libjcdump::ClassAccessFlags::*;
+
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Nested.java")])?;
+
+    let flags = |class: &str| -> anyhow::Result<_> {
+        let mut class = fs::File::open(output.path().join(class))?;
+        let raw = libjcdump::parse_raw(&mut class)?;
+        let data = libjcdump::wrap(&raw)?;
+        Ok((
+            data.to_json_value()?["access_flags"].clone(),
+            data.effective_access_flags(),
+        ))
+    };
+
+    // Not nested, so as in the class file.
+    let (flags_of_class, effective) = flags("./com/example/Nested.class")?;
+    assert_eq!(flags_of_class, serde_json::json!(["AccPublic", "AccSuper"]));
+    assert_eq!(effective, [AccPublic, AccSuper]);
+
+    // `protected` is compiled to `public`, and cannot be represented in the class flags.
+    let (flags_of_class, effective) = flags("./com/example/Nested$Inner.class")?;
+    assert_eq!(flags_of_class, serde_json::json!(["AccPublic", "AccSuper"]));
+    assert_eq!(effective, [AccSuper]);
+
+    // `private` is compiled to package access.
+    let (flags_of_class, effective) = flags("./com/example/Nested$Hidden.class")?;
+    assert_eq!(
+        flags_of_class,
+        serde_json::json!(["AccSuper", "AccAbstract"])
+    );
+    assert_eq!(effective, [AccSuper, AccAbstract]);
+
+    Ok(())
+}
+
+#[test]
+fn string_constants() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Main.java")])?;
+
+    let mut main = fs::File::open(output.path().join("./com/example/Main.class"))?;
+    let raw = libjcdump::parse_raw(&mut main)?;
+    let data = libjcdump::wrap(&raw)?;
+
+    let strings = data.string_constants().collect::<Vec<_>>();
+    let value = data.to_json_value()?;
+    let constant_pool = value["constant_pool"].as_array().unwrap();
+    let expected = constant_pool
+        .iter()
+        .filter_map(|entry| {
+            let entry = serde_json::json!([entry]);
+            attribute(&entry, "Utf8")
+                .or_else(|| attribute(&entry, "String").map(|string| &string["string"]))
+                .cloned()
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(serde_json::to_value(&strings)?, serde_json::json!(expected));
+    // The value of a String constant is also the Utf8 it refers to.
+    assert_eq!(
+        strings
+            .iter()
+            .filter(|&&string| string == "Condy!!!")
+            .count(),
+        2
+    );
+    assert!(strings.contains(&"Hello, World!"));
+    assert!(strings.contains(&"com/example/Main"));
+    assert!(strings.contains(&"Code"));
+
+    Ok(())
+}
+
+#[test]
+fn unknown_attribute_location() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac_with_args(srcdir.clone(), [srcdir.join("Main.java")], ["-g"])?;
+
+    let bytes = fs::read(output.path().join("./com/example/Main.class"))?;
+    let raw = libjcdump::parse_raw(&mut bytes.as_slice())?;
+    let data = libjcdump::wrap(&raw)?;
+
+    // LocalVariableTypeTable, not decoded by this crate, within the Code attribute of `of`.
+    let unknown = data
+        .all_attributes()
+        .find_map(|(_, attribute)| match attribute {
+            libjcdump::AttributeInfo::Unknown {
+                name,
+                length,
+                offset,
+                info,
+            } => Some((*name, *length, *offset as usize, *info)),
+            _ => None,
+        })
+        .unwrap();
+    let (name, length, offset, info) = unknown;
+    assert_eq!(name, "LocalVariableTypeTable");
+    assert_eq!(length, info.len());
+    // attribute_name_index, then attribute_length and the contents.
+    let value = serde_json::to_value(&data)?;
+    let name_index = u16::from_be_bytes([bytes[offset], bytes[offset + 1]]);
+    let constant = serde_json::json!([value["constant_pool"][name_index as usize]]);
+    assert_eq!(
+        attribute(&constant, "Utf8"),
+        Some(&serde_json::json!("LocalVariableTypeTable"))
+    );
+    assert_eq!(bytes[offset + 2..offset + 6], (length as u32).to_be_bytes());
+    assert_eq!(&bytes[offset + 6..offset + 6 + length], info);
+
+    let of = value["methods"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|method| method["name"] == "of")
+        .unwrap();
+    let code = attribute(&of["attributes"], "Code").unwrap();
+    let unknown = attribute(&code["attributes"], "Unknown").unwrap();
+    assert_eq!(unknown["length"], length);
+    assert_eq!(unknown["offset"], offset);
+
+    Ok(())
+}
+
+#[test]
+fn record_components() -> anyhow::Result<()> {
+    use libjcdump::AttributeLocation;
+
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(
+        srcdir.clone(),
+        [srcdir.join("Main.java"), srcdir.join("Pair.java")],
+    )?;
+
+    let mut main = fs::File::open(output.path().join("./com/example/Main.class"))?;
+    let raw = libjcdump::parse_raw(&mut main)?;
+    let data = libjcdump::wrap(&raw)?;
+    assert!(!data.is_record());
+
+    let mut pair = fs::File::open(output.path().join("./com/example/Pair.class"))?;
+    let raw = libjcdump::parse_raw(&mut pair)?;
+    let data = libjcdump::wrap(&raw)?;
+    assert!(data.is_record());
+    let components = data.record_components().unwrap();
+    assert_eq!(
+        components
+            .iter()
+            .map(|c| (c.name(), c.descriptor()))
+            .collect::<Vec<_>>(),
+        [("count", "I"), ("items", "Ljava/util/List;")]
+    );
+    assert!(matches!(
+        components[1].attributes().by_name("Signature"),
+        Some(libjcdump::AttributeInfo::Signature("Ljava/util/List<TT;>;"))
+    ));
+
+    // Right after the Record attribute, before the fields.
+    let all = data
+        .all_attributes()
+        .map(|(location, attribute)| (location, attribute.name()))
+        .collect::<Vec<_>>();
+    let record = all.iter().position(|(_, name)| *name == "Record").unwrap();
+    assert_eq!(
+        all[record + 1..]
+            .iter()
+            .take_while(|(location, _)| matches!(location, AttributeLocation::RecordComponent(_)))
+            .copied()
+            .collect::<Vec<_>>(),
+        [(AttributeLocation::RecordComponent(1), "Signature")]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn permitted_subclasses() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Shape.java")])?;
+
+    let mut shape = fs::File::open(output.path().join("./com/example/Shape.class"))?;
+    let raw = libjcdump::parse_raw(&mut shape)?;
+    let data = libjcdump::wrap(&raw)?;
+    assert!(data.is_sealed());
+    assert_eq!(
+        data.permitted_subclasses(),
+        Some(&["com/example/Shape$Circle", "com/example/Shape$Square"][..])
+    );
+
+    let mut square = fs::File::open(output.path().join("./com/example/Shape$Square.class"))?;
+    let raw = libjcdump::parse_raw(&mut square)?;
+    let data = libjcdump::wrap(&raw)?;
+    assert!(!data.is_sealed());
+    assert_eq!(data.permitted_subclasses(), None);
+
+    let class = |name| serde_json::to_value(libjcdump::CpInfo::Class { name }).unwrap();
+    let utf8 = |value| serde_json::to_value(libjcdump::CpInfo::Utf8(value)).unwrap();
+    assert_eq!(
+        attribute_references(
+            &output.path().join("./com/example/Shape.class"),
+            "PermittedSubclasses"
+        )?,
+        [
+            class("com/example/Shape$Circle"),
+            class("com/example/Shape$Square"),
+            utf8("PermittedSubclasses")
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn class_access_flags() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(
+        srcdir.clone(),
+        [
+            srcdir.join("Shape.java"),
+            srcdir.join("Marker.java"),
+            srcdir.join("Level.java"),
+            srcdir.join("module-info.java"),
+        ],
+    )?;
+
+    let read = |name: &str| -> anyhow::Result<_> {
+        let mut file = fs::File::open(output.path().join(name))?;
+        Ok(libjcdump::parse_raw(&mut file)?)
+    };
+
+    let raw = read("./com/example/Shape.class")?;
+    let shape = libjcdump::wrap(&raw)?;
+    assert!(shape.is_public() && shape.is_interface() && shape.is_abstract());
+    assert!(!shape.is_final() && !shape.is_annotation() && !shape.is_synthetic());
+
+    let mut raw = read("./com/example/Shape$Square.class")?;
+    let square = libjcdump::wrap(&raw)?;
+    assert!(square.is_final() && !square.is_interface() && !square.is_abstract());
+    let flags = serde_json::to_value(&square)?["access_flags"].clone();
+    assert!(flags.is_array());
+
+    // Bits no release assigns are reserved, and ignored rather than rejected.
+    raw.access_flags |= 0x0100 | 0x0040;
+    raw.methods[0].access_flags |= 0x8000;
+    let square = libjcdump::wrap(&raw)?;
+    assert_eq!(serde_json::to_value(&square)?["access_flags"], flags);
+
+    let raw = read("./com/example/Marker.class")?;
+    let marker = libjcdump::wrap(&raw)?;
+    assert!(marker.is_annotation() && marker.is_interface());
+
+    let raw = read("./com/example/Level.class")?;
+    let level = libjcdump::wrap(&raw)?;
+    assert!(level.is_enum() && level.is_final() && !level.is_interface());
+
+    let raw = read("./module-info.class")?;
+    let module = libjcdump::wrap(&raw)?;
+    assert!(module.is_module() && !module.is_public());
+
+    Ok(())
+}
+
+#[test]
+fn field_access_flags() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Level.java")])?;
+
+    let mut level = fs::File::open(output.path().join("./com/example/Level.class"))?;
+    let raw = libjcdump::parse_raw(&mut level)?;
+    let data = libjcdump::wrap(&raw)?;
+    let field = |name| data.fields().iter().find(|f| f.name() == name).unwrap();
+
+    let low = field("LOW");
+    assert!(low.is_enum_constant() && low.is_public() && low.is_static() && low.is_final());
+    assert!(!low.is_synthetic());
+
+    let values = field("$VALUES");
+    assert!(values.is_synthetic() && values.is_private() && !values.is_enum_constant());
+
+    let uses = field("uses");
+    assert!(uses.is_transient() && uses.is_private() && !uses.is_static());
+
+    let label = field("label");
+    assert!(label.is_volatile() && label.is_protected() && !label.is_final());
+
+    Ok(())
+}
+
+#[test]
+fn method_access_flags() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(
+        srcdir.clone(),
+        [srcdir.join("Methods.java"), srcdir.join("Marker.java")],
+    )?;
+
+    let mut methods = fs::File::open(output.path().join("./com/example/Methods.class"))?;
+    let raw = libjcdump::parse_raw(&mut methods)?;
+    let data = libjcdump::wrap(&raw)?;
+    let method = |name, descriptor| {
+        data.methods()
+            .iter()
+            .find(|m| m.name() == name && m.descriptor() == descriptor)
+            .unwrap()
+    };
+
+    let init = method("<init>", "()V");
+    assert!(init.is_constructor() && init.is_public() && !init.is_class_initializer());
+    let clinit = method("<clinit>", "()V");
+    assert!(clinit.is_class_initializer() && clinit.is_static() && !clinit.is_constructor());
+
+    let poke = method("poke", "()V");
+    assert!(poke.is_native() && poke.is_synchronized() && poke.is_protected());
+    assert!(!poke.is_abstract() && !poke.is_final());
+
+    let sum = method("sum", "([I)I");
+    assert!(sum.is_varargs() && sum.is_private() && sum.is_static());
+
+    let bridge = method("compareTo", "(Ljava/lang/Object;)I");
+    assert!(bridge.is_bridge() && bridge.is_synthetic() && bridge.is_public());
+    let compare_to = method("compareTo", "(Lcom/example/Methods;)I");
+    assert!(!compare_to.is_bridge() && !compare_to.is_synthetic());
+
+    let lambda = data
+        .methods()
+        .iter()
+        .find(|m| m.name().starts_with("lambda$"))
+        .unwrap();
+    assert!(lambda.is_synthetic() && lambda.is_private() && !lambda.is_bridge());
+    assert!(data.methods().iter().all(|m| !m.is_strict()));
+
+    let mut marker = fs::File::open(output.path().join("./com/example/Marker.class"))?;
+    let raw = libjcdump::parse_raw(&mut marker)?;
+    let data = libjcdump::wrap(&raw)?;
+    assert!(
+        data.methods()
+            .iter()
+            .all(|m| m.is_abstract() && m.is_public())
+    );
+
+    Ok(())
+}
+
+#[test]
+fn find_method() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Methods.java")])?;
+
+    let mut methods = fs::File::open(output.path().join("./com/example/Methods.class"))?;
+    let raw = libjcdump::parse_raw(&mut methods)?;
+    let data = libjcdump::wrap(&raw)?;
+
+    let bridge = data
+        .find_method("compareTo", Some("(Ljava/lang/Object;)I"))
+        .unwrap();
+    assert!(bridge.is_bridge());
+    let compare_to = data
+        .find_method("compareTo", Some("(Lcom/example/Methods;)I"))
+        .unwrap();
+    assert!(!compare_to.is_bridge());
+    assert_eq!(
+        data.find_method("compareTo", None).map(|m| m.descriptor()),
+        Some("(Lcom/example/Methods;)I")
+    );
+    assert!(data.find_method("compareTo", Some("()I")).is_none());
+    assert!(data.find_method("missing", None).is_none());
+
+    let overloads = data.find_methods("compareTo");
+    assert_eq!(overloads.len(), 2);
+    assert!(overloads.iter().any(|m| m.is_bridge()));
+    assert!(data.find_methods("missing").is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn instructions_with_lines() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Main.java")])?;
+
+    let mut main = fs::File::open(output.path().join("./com/example/Main.class"))?;
+    let raw = libjcdump::parse_raw(&mut main)?;
+    let data = libjcdump::wrap(&raw)?;
+
+    let source = fs::read_to_string(srcdir.join("Main.java"))?;
+    let line_of = |text: &str| {
+        source
+            .lines()
+            .position(|line| line.contains(text))
+            .map(|n| n as u16 + 1)
+    };
+
+    let method = data.methods().iter().find(|m| m.name() == "main").unwrap();
+    let instructions = method.code().unwrap().instructions_with_lines()?;
+    assert_eq!(instructions[0].0.offset(), 0);
+    assert_eq!(instructions[0].1, line_of("Runnable indyPlease"));
+    assert_eq!(
+        instructions.last().unwrap().0.operation(),
+        &libjcdump::Operation::Return
+    );
+    assert_eq!(
+        instructions.last().unwrap().1,
+        line_of("indyPlease.run()").map(|n| n + 1)
+    );
+
+    Ok(())
+}
+
+#[test]
+fn truncated() {
+    let data = [0xCA, 0xFE, 0xBA, 0xBE, 0x00, 0x00, 0x00, 0x3D, 0x00];
+    let err = libjcdump::parse_raw(&mut &data[..]).unwrap_err();
+    assert!(
+        matches!(
+            err,
+            libjcdump::ParseError::UnexpectedEof {
+                while_reading: "constant_pool_count",
+                offset: 8,
+            }
+        ),
+        "{err}"
+    );
+}
+
+#[test]
+fn invokedynamic_call_site() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Main.java")])?;
+
+    let mut main = fs::File::open(output.path().join("./com/example/Main.class"))?;
+    let raw = libjcdump::parse_raw(&mut main)?;
+    let data = libjcdump::wrap(&raw)?;
+
+    let method = data.methods().iter().find(|m| m.name() == "main").unwrap();
+    let instructions = data.disassemble(method.code().unwrap())?;
+    let call_site = instructions
+        .iter()
+        .find_map(|instruction| instruction.call_site())
+        .unwrap();
+    assert_eq!(call_site.name(), "run");
+    assert_eq!(call_site.descriptor(), "()Ljava/lang/Runnable;");
+    assert_eq!(
+        call_site.bootstrap_method().class(),
+        "java/lang/invoke/LambdaMetafactory"
+    );
+    assert_eq!(call_site.bootstrap_method().name(), "metafactory");
+    assert_eq!(call_site.bootstrap_method().bootstrap_arguments().len(), 3);
+
+    Ok(())
+}
+
+#[cfg(feature = "fingerprint")]
+#[test]
+fn reproducibility_fingerprint_ignores_debug_info() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let with_debug = javac(srcdir.clone(), [srcdir.join("Main.java")])?;
+    let without_debug = javac_with_args(srcdir.clone(), [srcdir.join("Main.java")], ["-g:none"])?;
+
+    let fingerprint = |dir: &TempDir| -> anyhow::Result<[u8; 32]> {
+        let mut main = fs::File::open(dir.path().join("./com/example/Main.class"))?;
+        let raw = libjcdump::parse_raw(&mut main)?;
+        Ok(libjcdump::wrap(&raw)?.reproducibility_fingerprint()?)
+    };
+    assert_eq!(fingerprint(&with_debug)?, fingerprint(&without_debug)?);
+
+    Ok(())
+}
+
+#[cfg(feature = "zip")]
+#[test]
+fn parse_from_jar() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Main.java")])?;
+    let jar_dir = tempdir()?;
+    let jar = jar_dir.path().join("main.jar");
+    Command::new("jar")
+        .arg("--create")
+        .arg("--file")
+        .arg(&jar)
+        .arg("-C")
+        .arg(output.path())
+        .arg(".")
+        .status()?;
+
+    let raw = libjcdump::parse_from_jar(&jar, "com/example/Main.class")?;
+    assert_eq!(libjcdump::wrap(&raw)?.this_class(), "com/example/Main");
+
+    assert!(matches!(
+        libjcdump::parse_from_jar(&jar, "com/example/Missing.class"),
+        Err(libjcdump::ParseError::EntryNotFound { name }) if name == "com/example/Missing.class"
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn attributes_by_name() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Main.java")])?;
+
+    let mut main = fs::File::open(output.path().join("./com/example/Main.class"))?;
+    let raw = libjcdump::parse_raw(&mut main)?;
+    let data = libjcdump::wrap(&raw)?;
+
+    assert!(matches!(
+        data.attributes().by_name("SourceFile"),
+        Some(libjcdump::AttributeInfo::SourceFile("Main.java"))
+    ));
+    assert!(data.attributes().by_name("Signature").is_none());
+
+    let method = data.methods().iter().find(|m| m.name() == "main").unwrap();
+    assert!(method.attributes().by_name("Code").is_some());
+    let code = method.code().unwrap();
+    assert_eq!(
+        code.attributes()
+            .by_name("LineNumberTable")
+            .map(|a| a.name()),
+        Some("LineNumberTable")
+    );
+
+    Ok(())
+}
+
+#[test]
+fn attribute_names() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Main.java")])?;
+
+    let mut main = fs::File::open(output.path().join("./com/example/Main.class"))?;
+    let mut raw = libjcdump::parse_raw(&mut main)?;
+    let names = raw
+        .methods
+        .iter()
+        .map(|method| {
+            method
+                .attributes
+                .iter()
+                .map(|attribute| {
+                    match raw
+                        .resolved_constants()
+                        .find(|(index, _)| *index == attribute.attribute_name_index)
+                    {
+                        Some((_, Ok(libjcdump::CpInfo::Utf8(name)))) => name,
+                        other => panic!("{other:?}"),
+                    }
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    // Every method has Code, whose name is resolved once and then reused.
+    let data = libjcdump::wrap(&raw)?;
+    assert!(data.methods().len() > 1);
+    let resolved = data
+        .methods()
+        .iter()
+        .map(|method| {
+            method
+                .attributes()
+                .into_iter()
+                .map(|attribute| attribute.name())
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(resolved, names);
+    drop((data, names));
+
+    let len = raw.constant_pool.len() as u16;
+    raw.methods[1].attributes[0].attribute_name_index = len;
+    assert!(matches!(
+        libjcdump::wrap(&raw),
+        Err(libjcdump::ParseError::InvalidCpIndex { index, .. }) if index == len
+    ));
+
+    // this_class is a CONSTANT_Class, not the name of anything.
+    raw.methods[1].attributes[0].attribute_name_index = raw.this_class;
+    assert!(matches!(
+        libjcdump::wrap(&raw),
+        Err(libjcdump::ParseError::UnexpectedCpType { index, expected: "CONSTANT_Utf8" })
+            if index == raw.this_class
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn all_attributes() -> anyhow::Result<()> {
+    use libjcdump::AttributeLocation;
+
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Main.java")])?;
+
+    let mut main = fs::File::open(output.path().join("./com/example/Main.class"))?;
+    let raw = libjcdump::parse_raw(&mut main)?;
+    let data = libjcdump::wrap(&raw)?;
+
+    let attributes = data
+        .all_attributes()
+        .map(|(location, attribute)| (location, attribute.name()))
+        .collect::<Vec<_>>();
+    assert_eq!(attributes[0].0, AttributeLocation::Class);
+    assert!(attributes.contains(&(AttributeLocation::Class, "SourceFile")));
+    assert!(attributes.contains(&(AttributeLocation::Field(0), "ConstantValue")));
+
+    let main = data
+        .methods()
+        .iter()
+        .position(|m| m.name() == "main")
+        .unwrap();
+    let code = attributes
+        .iter()
+        .position(|attribute| *attribute == (AttributeLocation::Method(main), "Code"))
+        .unwrap();
+    assert_eq!(
+        attributes[code + 1],
+        (AttributeLocation::Code(main), "LineNumberTable")
+    );
+
+    Ok(())
+}
+
+#[test]
+fn debug_summarizes_bytes() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Main.java")])?;
+
+    let mut main = fs::File::open(output.path().join("./com/example/Main.class"))?;
+    let mut raw = libjcdump::parse_raw(&mut main)?;
+    let data = libjcdump::wrap(&raw)?;
+
+    let code = data
+        .methods()
+        .iter()
+        .filter_map(|m| m.code())
+        .max_by_key(|code| code.opcodes().last().map(|(offset, _)| offset))
+        .unwrap();
+    assert!(code.opcodes().last().unwrap().0 > 16);
+    let debug = format!("{code:?}");
+    assert!(debug.contains(" bytes"), "{debug}");
+    assert!(!debug.contains("bytecode: ["), "{debug}");
+
+    let code = &raw.methods[0].attributes[0];
+    assert_eq!(
+        format!("{code:?}"),
+        format!(
+            "AttributeInfo {{ offset: {}, attribute_name_index: {}, info: {} bytes }}",
+            code.offset,
+            code.attribute_name_index,
+            code.info.len()
+        )
+    );
+
+    // The SourceFile attribute renamed after its own value, which is not an attribute name.
+    let position = data
+        .attributes()
+        .into_iter()
+        .position(|attribute| attribute.name() == "SourceFile")
+        .unwrap();
+    let source_file = &mut raw.attributes[position];
+    source_file.attribute_name_index =
+        u16::from_be_bytes([source_file.info[0], source_file.info[1]]);
+    source_file.info = vec![0; 1024];
+    let data = libjcdump::wrap(&raw)?;
+    let unknown = data.attributes().by_name("Main.java").unwrap();
+    let debug = format!("{unknown:?}");
+    assert!(
+        debug.starts_with("Unknown { name: \"Main.java\""),
+        "{debug}"
+    );
+    assert!(debug.ends_with("info: 1024 bytes }"), "{debug}");
+
+    Ok(())
+}
+
+#[test]
+fn field_type() -> anyhow::Result<()> {
+    use libjcdump::{BaseType, FieldType};
+
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(
+        srcdir.clone(),
+        [srcdir.join("Main.java"), srcdir.join("Fields.java")],
+    )?;
+
+    let mut main = fs::File::open(output.path().join("./com/example/Main.class"))?;
+    let raw = libjcdump::parse_raw(&mut main)?;
+    let data = libjcdump::wrap(&raw)?;
+    let field = |name| data.fields().iter().find(|f| f.name() == name).unwrap();
+    assert_eq!(field("ZERO").field_type()?, FieldType::Base(BaseType::Int));
+    assert_eq!(
+        field("NAMES").field_type()?,
+        FieldType::Object("java/util/List".to_string())
+    );
+
+    let mut fields = fs::File::open(output.path().join("./com/example/Fields.class"))?;
+    let raw = libjcdump::parse_raw(&mut fields)?;
+    let data = libjcdump::wrap(&raw)?;
+    let lists = &data.fields()[0];
+    assert_eq!(lists.descriptor(), "[Ljava/util/List;");
+    assert_eq!(
+        lists.field_type()?,
+        FieldType::Array(Box::new(FieldType::Object("java/util/List".to_string())))
+    );
+
+    Ok(())
+}
+
+#[test]
+fn signature() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(
+        srcdir.clone(),
+        [
+            srcdir.join("Main.java"),
+            srcdir.join("Fields.java"),
+            srcdir.join("Holder.java"),
+        ],
+    )?;
+    let read = |name: &str| fs::read(output.path().join("./com/example").join(name));
+
+    let bytes = read("Main.class")?;
+    let raw = libjcdump::parse_raw(&mut bytes.as_slice())?;
+    let data = libjcdump::wrap(&raw)?;
+    assert_eq!(data.signature(), None);
+    let field = |name| data.fields().iter().find(|f| f.name() == name).unwrap();
+    assert_eq!(
+        field("NAMES").signature(),
+        Some("Ljava/util/List<Ljava/lang/String;>;")
+    );
+    assert_eq!(field("ZERO").signature(), None);
+    let method = |name| data.methods().iter().find(|m| m.name() == name).unwrap();
+    assert_eq!(
+        method("of").signature(),
+        Some("<T::Ljava/lang/Comparable<TT;>;>([TT;)Ljava/util/List<TT;>;")
+    );
+    assert_eq!(method("main").signature(), None);
+
+    let bytes = read("Fields.class")?;
+    let raw = libjcdump::parse_raw(&mut bytes.as_slice())?;
+    let data = libjcdump::wrap(&raw)?;
+    assert_eq!(
+        data.fields()[0].signature(),
+        Some("[Ljava/util/List<Ljava/lang/String;>;")
+    );
+
+    let bytes = read("Holder.class")?;
+    let raw = libjcdump::parse_raw(&mut bytes.as_slice())?;
+    let data = libjcdump::wrap(&raw)?;
+    assert_eq!(
+        data.signature(),
+        Some("<T:Ljava/lang/Object;>Ljava/util/ArrayList<TT;>;")
+    );
+
+    Ok(())
+}
+
+#[test]
+fn signature_nesting() -> anyhow::Result<()> {
+    // An abstract method `m()V` of the class `A`, with a Signature nesting `depth` levels.
+    let class = |depth: usize| {
+        let mut pool = vec![];
+        let utf8 = |pool: &mut Vec<u8>, value: &str| {
+            pool.push(1);
+            pool.extend((value.len() as u16).to_be_bytes());
+            pool.extend(value.as_bytes());
+        };
+        utf8(&mut pool, "A"); // #1
+        pool.extend([7, 0, 1]); // #2 Class
+        utf8(&mut pool, "java/lang/Object"); // #3
+        pool.extend([7, 0, 3]); // #4 Class
+        utf8(&mut pool, "m"); // #5
+        utf8(&mut pool, "()V"); // #6
+        utf8(&mut pool, "Signature"); // #7
+        let signature = format!("(){}LA;{}", "LA<".repeat(depth), ">;".repeat(depth));
+        utf8(&mut pool, &signature); // #8
+
+        let mut bytes = vec![0xca, 0xfe, 0xba, 0xbe, 0, 0, 0, 55, 0, 9];
+        bytes.extend(pool);
+        bytes.extend([0x04, 0x21, 0, 2, 0, 4, 0, 0, 0, 0]);
+        bytes.extend([0, 1, 0x04, 0x01, 0, 5, 0, 6, 0, 1, 0, 7, 0, 0, 0, 2, 0, 8]);
+        bytes.extend([0, 0]);
+        bytes
+    };
+
+    let bytes = class(3);
+    let mut raw = libjcdump::parse_raw(&mut bytes.as_slice())?;
+    let data = libjcdump::wrap(&raw)?;
+    assert_eq!(
+        data.methods()[0].java_signature(),
+        "public abstract A<A<A<A>>> m()"
+    );
+    raw.rename_class("A", "B")?;
+
+    // Deep enough to overflow the stack if it were followed, and rendered from the descriptor.
+    let bytes = class(13000);
+    let mut raw = libjcdump::parse_raw(&mut bytes.as_slice())?;
+    let data = libjcdump::wrap(&raw)?;
+    assert_eq!(
+        data.methods()[0].java_signature(),
+        "public abstract void m()"
+    );
+    let text = data.to_text(&libjcdump::TextOptions::default());
+    assert!(text.contains("public abstract void m()"));
+    assert!(matches!(
+        raw.rename_class("A", "B"),
+        Err(libjcdump::ParseError::InvalidDescriptor { .. })
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn marker_attributes() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Old.java")])?;
+
+    let mut old = fs::File::open(output.path().join("./com/example/Old.class"))?;
+    let mut raw = libjcdump::parse_raw(&mut old)?;
+    let data = libjcdump::wrap(&raw)?;
+    assert!(data.is_deprecated());
+    assert!(matches!(
+        data.attributes().by_name("Deprecated"),
+        Some(libjcdump::AttributeInfo::Deprecated)
+    ));
+    assert!(data.fields()[0].is_deprecated());
+    let method = |name| data.methods().iter().find(|m| m.name() == name).unwrap();
+    assert!(method("old").is_deprecated());
+    assert!(!method("current").is_deprecated());
+    assert!(!data.is_synthetic_attribute());
+
+    // javac sets ACC_SYNTHETIC instead, so turn the attribute of the class into one.
+    let utf8_index = |name: &str| {
+        raw.resolved_constants()
+            .find(|(_, entry)| matches!(entry, Ok(libjcdump::CpInfo::Utf8(s)) if *s == name))
+            .map(|(index, _)| index)
+            .unwrap()
+    };
+    let (deprecated, synthetic) = (utf8_index("Deprecated"), utf8_index("Synthetic"));
+    let position = raw
+        .attributes
+        .iter()
+        .position(|attribute| attribute.attribute_name_index == deprecated)
+        .unwrap();
+    raw.attributes[position].attribute_name_index = synthetic;
+    let data = libjcdump::wrap(&raw)?;
+    assert!(data.is_synthetic_attribute());
+    assert!(!data.is_deprecated());
+    assert!(data.methods().iter().all(|m| !m.is_synthetic_attribute()));
+    drop(data);
+
+    raw.attributes[position].info.push(0);
+    assert!(matches!(
+        libjcdump::wrap(&raw),
+        Err(libjcdump::ParseError::MalformedAttribute { name, .. }) if name == "Synthetic"
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn parse_signatures_only() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Main.java")])?;
+
+    let bytes = fs::read(output.path().join("./com/example/Main.class"))?;
+    let view = libjcdump::parse_signatures_only(&mut bytes.as_slice())?;
+    let raw = libjcdump::parse_raw(&mut bytes.as_slice())?;
+    let data = libjcdump::wrap(&raw)?;
+
+    assert_eq!(view.this_class(), "com/example/Main");
+    assert_eq!(view.super_class(), Some("java/lang/Object"));
+    assert_eq!(
+        view.interfaces(),
+        ["java/io/Serializable", "java/lang/Cloneable"]
+    );
+    assert_eq!(
+        view.fields()
+            .iter()
+            .map(|f| (f.name(), f.descriptor()))
+            .collect::<Vec<_>>(),
+        data.fields()
+            .iter()
+            .map(|f| (f.name(), f.descriptor()))
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(
+        view.methods()
+            .iter()
+            .map(|m| (m.name(), m.descriptor()))
+            .collect::<Vec<_>>(),
+        data.methods()
+            .iter()
+            .map(|m| (m.name(), m.descriptor()))
+            .collect::<Vec<_>>()
+    );
+    let main = view.methods().iter().find(|m| m.name() == "main").unwrap();
+    assert_eq!(
+        main.access_flags(),
+        [
+            libjcdump::MethodAccessFlags::AccPublic,
+            libjcdump::MethodAccessFlags::AccStatic
+        ]
+    );
+
+    assert!(matches!(
+        libjcdump::parse_signatures_only(&mut &bytes[..bytes.len() - 1]),
+        Err(libjcdump::ParseError::UnexpectedEof {
+            while_reading: "attribute info",
+            ..
+        })
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn parse_with_visitor() -> anyhow::Result<()> {
+    #[derive(Default)]
+    struct Names {
+        fields: Vec<String>,
+        methods: Vec<String>,
+        attributes: Vec<String>,
+    }
+
+    impl libjcdump::ClassVisitor for Names {
+        fn visit_field(&mut self, field: &libjcdump::FieldInfo<&str, &[u8]>) {
+            self.fields.push(field.name().to_string());
+        }
+
+        fn visit_method(&mut self, method: &libjcdump::MethodInfo<&str, &[u8]>) {
+            self.methods.push(method.name().to_string());
+        }
+
+        fn visit_attribute(&mut self, attribute: &libjcdump::AttributeInfo<&str, &[u8]>) {
+            self.attributes.push(attribute.name().to_string());
+        }
+    }
+
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Main.java")])?;
+
+    let path = output.path().join("./com/example/Main.class");
+    let mut names = Names::default();
+    let header = libjcdump::parse_with_visitor(&mut fs::File::open(&path)?, &mut names)?;
+    assert!(header.fields.is_empty() && header.methods.is_empty());
+    assert!(header.attributes.is_empty());
+
+    let raw = libjcdump::parse_raw(&mut fs::File::open(&path)?)?;
+    let data = libjcdump::wrap(&raw)?;
+    assert_eq!(header.constant_pool.len(), raw.constant_pool.len());
+    assert_eq!(
+        names.fields,
+        data.fields().iter().map(|f| f.name()).collect::<Vec<_>>()
+    );
+    assert_eq!(
+        names.methods,
+        data.methods().iter().map(|m| m.name()).collect::<Vec<_>>()
+    );
+    assert_eq!(
+        names.attributes,
+        data.attributes()
+            .into_iter()
+            .map(|a| a.name())
+            .collect::<Vec<_>>()
+    );
+
+    let bytes = fs::read(&path)?;
+    assert!(
+        libjcdump::parse_with_visitor(&mut &bytes[..bytes.len() - 1], &mut Names::default())
+            .is_err()
+    );
+
+    Ok(())
+}
+
+#[test]
+fn text_options() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Main.java")])?;
+
+    let mut main = fs::File::open(output.path().join("./com/example/Main.class"))?;
+    let raw = libjcdump::parse_raw(&mut main)?;
+    let data = libjcdump::wrap(&raw)?;
+
+    let text = data.to_text(&libjcdump::TextOptions::default());
+    let lines = text.lines().collect::<Vec<_>>();
+    assert_eq!(lines[0], "Compiled from \"Main.java\"");
+    assert_eq!(lines[2], "  public static final int ZERO;");
+    assert!(lines.contains(&"  public static void main() throws java.lang.Exception;"));
+    assert!(lines.contains(&"  public Main();"));
+    assert!(lines.contains(&"  static {}"));
+
+    let sorted = data.to_text(&libjcdump::TextOptions {
+        indent: 4,
+        sort_members: true,
+    });
+    let lines = sorted.lines().collect::<Vec<_>>();
+    assert_eq!(
+        lines[2],
+        "    public static final java.lang.String CONDY_PLEASE;"
+    );
+    assert_eq!(lines.last(), Some(&"}"));
+
+    Ok(())
+}
+
+#[test]
+fn interface_default_methods() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Greeter.java")])?;
+
+    let mut greeter = fs::File::open(output.path().join("./com/example/Greeter.class"))?;
+    let raw = libjcdump::parse_raw(&mut greeter)?;
+    let data = libjcdump::wrap(&raw)?;
+
+    let defaults = data
+        .methods()
+        .iter()
+        .filter(|m| m.is_default_method(true))
+        .map(|m| m.name())
+        .collect::<Vec<_>>();
+    assert_eq!(defaults, ["hello"]);
+    assert!(data.methods().iter().all(|m| !m.is_default_method(false)));
+
+    let text = data.to_text(&libjcdump::TextOptions::default());
+    let lines = text.lines().collect::<Vec<_>>();
+    assert!(lines.contains(&"  public abstract void greet();"));
+    assert!(lines.contains(&"  public default void hello();"));
+    assert!(lines.contains(&"  public static com.example.Greeter create();"));
+    assert!(lines.contains(&"  private void helper();"));
+
+    Ok(())
+}
+
+#[test]
+fn constant_value_index_zero() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Main.java")])?;
+
+    let mut main = fs::File::open(output.path().join("./com/example/Main.class"))?;
+    let mut raw = libjcdump::parse_raw(&mut main)?;
+    // ZERO's only attribute is ConstantValue.
+    raw.fields[0].attributes[0].info = vec![0, 0];
+
+    assert!(matches!(
+        libjcdump::wrap(&raw),
+        Err(libjcdump::ParseError::InvalidConstantValueIndex)
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn constant_pool_lookup_errors() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Main.java")])?;
+
+    let mut main = fs::File::open(output.path().join("./com/example/Main.class"))?;
+    let mut raw = libjcdump::parse_raw(&mut main)?;
+    let len = raw.constant_pool.len();
+    // ZERO's only attribute is ConstantValue.
+    let constant_value = &mut raw.fields[0].attributes[0];
+    constant_value.info = (len as u16).to_be_bytes().to_vec();
+    assert!(matches!(
+        libjcdump::wrap(&raw),
+        Err(libjcdump::ParseError::InvalidCpIndex { index, pool_size })
+            if index as usize == len && pool_size == len
+    ));
+
+    // The name of the attribute is no constant a field may take.
+    let constant_value = &mut raw.fields[0].attributes[0];
+    let name_index = constant_value.attribute_name_index;
+    constant_value.info = name_index.to_be_bytes().to_vec();
+    assert!(matches!(
+        libjcdump::wrap(&raw),
+        Err(libjcdump::ParseError::UnexpectedCpType { index, .. }) if index == name_index
+    ));
+
+    raw.constant_pool.clear();
+    assert!(matches!(
+        libjcdump::wrap(&raw),
+        Err(libjcdump::ParseError::EmptyConstantPool)
+    ));
+    assert!(matches!(
+        raw.to_bytes(),
+        Err(libjcdump::ParseError::EmptyConstantPool)
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn malformed_attribute() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Old.java")])?;
+
+    let mut old = fs::File::open(output.path().join("./com/example/Old.class"))?;
+    let mut raw = libjcdump::parse_raw(&mut old)?;
+    let data = libjcdump::wrap(&raw)?;
+    let position = data
+        .attributes()
+        .into_iter()
+        .position(|attribute| attribute.name() == "RuntimeVisibleAnnotations")
+        .unwrap();
+    drop(data);
+
+    // Give @Deprecated an element, named as its type, of the unknown tag 'X'.
+    let info = &mut raw.attributes[position].info;
+    let type_index = [info[2], info[3]];
+    *info = [&[0, 1], &type_index[..], &[0, 1], &type_index[..], b"X"].concat();
+    assert!(matches!(
+        libjcdump::wrap(&raw),
+        Err(libjcdump::ParseError::MalformedAttribute { name, .. })
+            if name == "RuntimeVisibleAnnotations"
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn class_loader() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Main.java")])?;
+
+    let loader = libjcdump::ClassLoader::new([output.path()]);
+    let raw = loader.load("com/example/Main")?.unwrap();
+    let data = libjcdump::wrap(&raw)?;
+    assert!(data.methods().iter().any(|m| m.name() == "main"));
+
+    assert!(loader.load("com/example/Missing")?.is_none());
+    assert!(loader.find("com/example/../example/Main").is_none());
+
+    Ok(())
+}
+
+#[test]
+fn class_loader_superclass_cycle() -> anyhow::Result<()> {
+    let dir = tempdir()?;
+    // `A extends B` and `B extends A`, neither declaring any method.
+    for (this_class, super_class) in [("A", "B"), ("B", "A")] {
+        let mut pool = vec![];
+        let utf8 = |pool: &mut Vec<u8>, value: &str| {
+            pool.push(1);
+            pool.extend((value.len() as u16).to_be_bytes());
+            pool.extend(value.as_bytes());
+        };
+        utf8(&mut pool, this_class); // #1
+        pool.extend([7, 0, 1]); // #2 Class
+        utf8(&mut pool, super_class); // #3
+        pool.extend([7, 0, 3]); // #4 Class
+
+        let mut bytes = vec![0xca, 0xfe, 0xba, 0xbe, 0, 0, 0, 55, 0, 5];
+        bytes.extend(pool);
+        bytes.extend([0x00, 0x21, 0, 2, 0, 4, 0, 0, 0, 0, 0, 0, 0, 0]);
+        fs::write(dir.path().join(format!("{this_class}.class")), bytes)?;
+    }
+
+    let loader = libjcdump::ClassLoader::new([dir.path()]);
+    assert!(matches!(
+        loader.resolve_method("A", "m", "()V")?,
+        libjcdump::Linkage::NotFound
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn compact_constant_pool() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Main.java")])?;
+
+    let mut main = fs::File::open(output.path().join("./com/example/Main.class"))?;
+    let mut raw = libjcdump::parse_raw(&mut main)?;
+    // Leaves a copy of the class name behind for the string constant, and the old method name
+    // unreferenced.
+    raw.rename_class("com/example/Main", "org/example/Renamed")?;
+    raw.rename_class("org/example/Renamed", "com/example/Main")?;
+    raw.rename_method("frames", "(I)I", "hash")?;
+    let removable = raw.pool_analysis()?.removable_count();
+    assert_eq!(removable, 2);
+    let text = libjcdump::wrap(&raw)?.to_text(&libjcdump::TextOptions::default());
+    let len = raw.constant_pool.len();
+
+    raw.compact_pool()?;
+    assert_eq!(raw.constant_pool.len(), len - removable);
+    assert_eq!(raw.pool_analysis()?.removable_count(), 0);
+    let bytes = raw.to_bytes()?;
+    let raw = libjcdump::parse_raw(&mut bytes.as_slice())?;
+    assert!(libjcdump::validate(&raw).is_clean());
+    let data = libjcdump::wrap(&raw)?;
+    assert_eq!(data.to_text(&libjcdump::TextOptions::default()), text);
+
+    let constant_pool = data.to_json_value()?["constant_pool"].clone();
+    let source_file = if cfg!(feature = "tagged") {
+        serde_json::json!({"tag": "Utf8", "value": "Main.java"})
+    } else {
+        serde_json::json!({"Utf8": "Main.java"})
+    };
+    assert!(constant_pool.as_array().unwrap().contains(&source_file));
+    let frames = if cfg!(feature = "tagged") {
+        serde_json::json!({"tag": "Utf8", "value": "frames"})
+    } else {
+        serde_json::json!({"Utf8": "frames"})
+    };
+    assert!(!constant_pool.as_array().unwrap().contains(&frames));
+
+    Ok(())
+}
+
+#[test]
+fn code_metrics() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Main.java")])?;
+
+    let mut main = fs::File::open(output.path().join("./com/example/Main.class"))?;
+    let raw = libjcdump::parse_raw(&mut main)?;
+    let data = libjcdump::wrap(&raw)?;
+
+    let method = data.methods().iter().find(|m| m.name() == "main").unwrap();
+    // invokedynamic, astore_0, aload_0, invokeinterface, return
+    assert_eq!(method.code_metrics(), Some((1, 1, 13)));
+
+    Ok(())
+}
+
+#[test]
+fn invalid_super_class() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Main.java")])?;
+
+    let mut main = fs::File::open(output.path().join("./com/example/Main.class"))?;
+    let mut raw = libjcdump::parse_raw(&mut main)?;
+    // javac puts the `Object.<init>` called by the constructor first.
+    raw.super_class = 1;
+
+    let err = libjcdump::wrap(&raw).unwrap_err();
+    assert!(matches!(
+        err,
+        libjcdump::ParseError::InvalidSuperClass {
+            found: "CONSTANT_Methodref"
+        }
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn invalid_this_class() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Main.java")])?;
+
+    let mut main = fs::File::open(output.path().join("./com/example/Main.class"))?;
+    let mut raw = libjcdump::parse_raw(&mut main)?;
+    let this_class = raw.this_class;
+
+    raw.this_class = 0;
+    assert!(matches!(
+        libjcdump::wrap(&raw).unwrap_err(),
+        libjcdump::ParseError::InvalidThisClass { found: "index 0" }
+    ));
+
+    raw.this_class = 1;
+    assert!(matches!(
+        libjcdump::wrap(&raw).unwrap_err(),
+        libjcdump::ParseError::InvalidThisClass {
+            found: "CONSTANT_Methodref"
+        }
+    ));
+
+    raw.this_class = this_class;
+    raw.access_flags = 0x8000; // ACC_MODULE
+    match libjcdump::wrap(&raw).unwrap_err() {
+        libjcdump::ParseError::InvalidModuleThisClass { name } => {
+            assert_eq!(name, "com/example/Main")
+        }
+        err => panic!("{err}"),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn invalid_descriptor() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Main.java")])?;
+
+    let mut main = fs::File::open(output.path().join("./com/example/Main.class"))?;
+    let mut raw = libjcdump::parse_raw(&mut main)?;
+    let name = libjcdump::wrap(&raw)?.methods()[0].name().to_string();
+
+    // A method name where its descriptor is expected.
+    raw.methods[0].descriptor_index = raw.methods[0].name_index;
+    match libjcdump::wrap(&raw).unwrap_err() {
+        libjcdump::ParseError::InvalidDescriptor { value, position } => {
+            assert_eq!(value, name);
+            assert_eq!(position, 0);
+        }
+        err => panic!("{err}"),
+    }
+
+    // At most 255 array dimensions.
+    let descriptor = format!("{}I", "[".repeat(255));
+    let field_type = libjcdump::parse_field_descriptor(&descriptor)?;
+    assert_eq!(field_type.to_string(), format!("int{}", "[]".repeat(255)));
+    let descriptor = format!("{}I", "[".repeat(256));
+    let err = libjcdump::parse_field_descriptor(&descriptor).unwrap_err();
+    assert_eq!(err.position, 255);
+    let descriptor = format!("({}I)V", "[".repeat(256));
+    let err = libjcdump::parse_method_descriptor(&descriptor).unwrap_err();
+    assert_eq!(err.position, 256);
+
+    // A field with a descriptor far too deep to parse recursively.
+    let mut pool = vec![];
+    let utf8 = |pool: &mut Vec<u8>, value: &str| {
+        pool.push(1);
+        pool.extend((value.len() as u16).to_be_bytes());
+        pool.extend(value.as_bytes());
+    };
+    utf8(&mut pool, "Deep"); // #1
+    pool.extend([7, 0, 1]); // #2 Class
+    utf8(&mut pool, "java/lang/Object"); // #3
+    pool.extend([7, 0, 3]); // #4 Class
+    utf8(&mut pool, "x"); // #5
+    utf8(&mut pool, &format!("{}I", "[".repeat(60000))); // #6
+
+    let mut bytes = vec![0xca, 0xfe, 0xba, 0xbe, 0, 0, 0, 55, 0, 7];
+    bytes.extend(pool);
+    bytes.extend([0x00, 0x21, 0, 2, 0, 4, 0, 0]);
+    bytes.extend([0, 1, 0x00, 0x02, 0, 5, 0, 6, 0, 0]);
+    bytes.extend([0, 0, 0, 0]);
+    let raw = libjcdump::parse_raw(&mut bytes.as_slice())?;
+    assert!(matches!(
+        libjcdump::wrap(&raw),
+        Err(libjcdump::ParseError::InvalidDescriptor { position: 255, .. })
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn illegal_opcode() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Main.java")])?;
+
+    let mut main = fs::File::open(output.path().join("./com/example/Main.class"))?;
+    let mut raw = libjcdump::parse_raw(&mut main)?;
+    let (method, attribute) = {
+        let data = libjcdump::wrap(&raw)?;
+        let method = data.methods().iter().position(|m| m.name() == "main");
+        let method = method.unwrap();
+        let attributes = data.methods()[method].attributes();
+        let attribute = attributes.iter().position(|a| a.name() == "Code");
+        (method, attribute.unwrap())
+    };
+    // Replace astore_0 following the 5 bytes long invokedynamic; code starts at offset 8.
+    raw.methods[method].attributes[attribute].info[8 + 5] = 0xcb;
+
+    let data = libjcdump::wrap(&raw)?;
+    let code = data.methods()[method].code().unwrap();
+    assert!(matches!(
+        code.instructions(),
+        Err(libjcdump::ParseError::IllegalOpcode {
+            opcode: 0xcb,
+            offset: 5
+        })
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn corrupted_input_does_not_panic() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Main.java")])?;
+
+    let bytes = fs::read(output.path().join("./com/example/Main.class"))?;
+    for i in 0..bytes.len() {
+        for value in [0x00, 0x01, 0xff] {
+            let mut bytes = bytes.clone();
+            bytes[i] = value;
+
+            let Ok(raw) = libjcdump::parse_raw(&mut &bytes[..]) else {
+                continue;
+            };
+            let Ok(data) = libjcdump::wrap(&raw) else {
+                continue;
+            };
+            for method in data.methods() {
+                if let Some(code) = method.code() {
+                    let _ = data.disassemble(code);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn validate_attribute_lengths() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Main.java")])?;
+
+    let mut main = fs::File::open(output.path().join("./com/example/Main.class"))?;
+    let mut raw = libjcdump::parse_raw(&mut main)?;
+    assert!(libjcdump::validate(&raw).is_clean());
+
+    // ZERO's only attribute is ConstantValue.
+    raw.fields[0].attributes[0].info.push(0);
+    let offset = raw.fields[0].attributes[0].offset;
+    assert_eq!(
+        libjcdump::validate(&raw).findings(),
+        [libjcdump::Finding::AttributeLength {
+            name: "ConstantValue".to_string(),
+            offset,
+            declared: 3,
+            expected: Some(2),
+        }]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn empty_code() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Main.java")])?;
+
+    let mut main = fs::File::open(output.path().join("./com/example/Main.class"))?;
+    let mut raw = libjcdump::parse_raw(&mut main)?;
+
+    // max_stack, max_locals, code_length, exception_table_length and attributes_count.
+    let code = &mut raw.methods[0].attributes[0];
+    code.info = vec![0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0];
+    let offset = code.offset;
+    assert_eq!(
+        libjcdump::validate(&raw).findings(),
+        [libjcdump::Finding::EmptyCode { offset }]
+    );
+
+    let data = libjcdump::wrap(&raw)?;
+    let Some(libjcdump::AttributeInfo::Code(code)) = data.methods()[0].attributes().by_name("Code")
+    else {
+        panic!("no Code attribute");
+    };
+    assert!(code.instructions()?.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn code_attribute() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Main.java")])?;
+
+    let mut main = fs::File::open(output.path().join("./com/example/Main.class"))?;
+    let raw = libjcdump::parse_raw(&mut main)?;
+    let data = libjcdump::wrap(&raw)?;
+    let frames = data
+        .methods()
+        .iter()
+        .find(|m| m.name() == "frames")
+        .unwrap();
+    let Some(libjcdump::AttributeInfo::Code(code)) = frames.attributes().by_name("Code") else {
+        panic!("no Code attribute");
+    };
+    assert_eq!(code.max_locals(), 2);
+    assert!(code.max_stack() >= 2);
+    assert!(!code.bytecode().is_empty());
+
+    let [handler] = code.exception_table() else {
+        panic!("{:?}", code.exception_table());
+    };
+    assert_eq!(handler.catch_type(), Some("java/lang/RuntimeException"));
+    assert_eq!(handler.start_pc(), 0);
+    assert!(handler.start_pc() < handler.end_pc());
+    assert!(handler.end_pc() <= handler.handler_pc());
+    assert!((handler.handler_pc() as usize) < code.bytecode().len());
+
+    Ok(())
+}
+
+#[test]
+fn attribute_length_mismatch() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Main.java")])?;
+
+    let mut main = fs::File::open(output.path().join("./com/example/Main.class"))?;
+    let mut raw = libjcdump::parse_raw(&mut main)?;
+    let is_bootstrap_methods = |index: u16| {
+        matches!(
+            raw.resolved_constants().find(|(i, _)| *i == index),
+            Some((_, Ok(libjcdump::CpInfo::Utf8("BootstrapMethods"))))
+        )
+    };
+    let position = raw
+        .attributes
+        .iter()
+        .position(|attribute| is_bootstrap_methods(attribute.attribute_name_index))
+        .unwrap();
+    let attribute = &mut raw.attributes[position];
+    attribute.info.extend([0; 8]);
+    assert!(attribute.info.len() > 16);
+    let (expected_offset, expected_snippet) = (attribute.offset, attribute.info[..16].to_vec());
+
+    let err = libjcdump::wrap(&raw).unwrap_err();
+    let libjcdump::ParseError::AttributeLengthMismatch {
+        name,
+        offset,
+        context,
+        snippet,
+    } = &err
+    else {
+        panic!("{err}");
+    };
+    assert_eq!(name, "BootstrapMethods");
+    assert_eq!(*offset, expected_offset);
+    assert_eq!(*context, "BootstrapMethods attribute_length");
+    assert_eq!(*snippet, expected_snippet);
+    assert!(err.to_string().ends_with(&format!(
+        "starting with [{}]",
+        expected_snippet
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect::<Vec<_>>()
+            .join(" ")
+    )));
+
+    // Running out of bytes is a length mismatch too, named after the field cut short. Methods
+    // are decoded before the attributes of the class.
+    let code = &mut raw.methods[0].attributes[0];
+    code.info.truncate(3);
+    assert!(matches!(
+        libjcdump::wrap(&raw),
+        Err(libjcdump::ParseError::AttributeLengthMismatch { name, context, .. })
+            if name == "Code" && context == "Code max_locals"
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn ldc_dynamic_constant() -> anyhow::Result<()> {
+    // javac does not emit `ldc` of a CONSTANT_Dynamic, so the class is assembled by hand:
+    // `static Object get() { return ConstantBootstraps.nullConstant(...); }` through condy.
+    let mut pool = vec![];
+    let utf8 = |pool: &mut Vec<u8>, value: &str| {
+        pool.push(1);
+        pool.extend((value.len() as u16).to_be_bytes());
+        pool.extend(value.as_bytes());
+    };
+    utf8(&mut pool, "Condy"); // #1
+    pool.extend([7, 0, 1]); // #2 Class
+    utf8(&mut pool, "java/lang/Object"); // #3
+    pool.extend([7, 0, 3]); // #4 Class
+    utf8(&mut pool, "java/lang/invoke/ConstantBootstraps"); // #5
+    pool.extend([7, 0, 5]); // #6 Class
+    utf8(&mut pool, "nullConstant"); // #7
+    utf8(
+        &mut pool,
+        "(Ljava/lang/invoke/MethodHandles$Lookup;Ljava/lang/String;Ljava/lang/Class;)Ljava/lang/Object;",
+    ); // #8
+    pool.extend([12, 0, 7, 0, 8]); // #9 NameAndType
+    pool.extend([10, 0, 6, 0, 9]); // #10 Methodref
+    pool.extend([15, 6, 0, 10]); // #11 MethodHandle REF_invokeStatic
+    utf8(&mut pool, "_"); // #12
+    utf8(&mut pool, "Ljava/lang/Object;"); // #13
+    pool.extend([12, 0, 12, 0, 13]); // #14 NameAndType
+    pool.extend([17, 0, 0, 0, 14]); // #15 Dynamic
+    utf8(&mut pool, "get"); // #16
+    utf8(&mut pool, "()Ljava/lang/Object;"); // #17
+    utf8(&mut pool, "Code"); // #18
+    utf8(&mut pool, "BootstrapMethods"); // #19
+
+    let mut bytes = vec![0xca, 0xfe, 0xba, 0xbe, 0, 0, 0, 55, 0, 20];
+    bytes.extend(pool);
+    bytes.extend([0x00, 0x21, 0, 2, 0, 4, 0, 0, 0, 0]);
+    // A static method, with `ldc #15; areturn`.
+    bytes.extend([0, 1, 0x00, 0x09, 0, 16, 0, 17, 0, 1]);
+    bytes.extend([
+        0, 18, 0, 0, 0, 15, 0, 1, 0, 0, 0, 0, 0, 3, 0x12, 15, 0xb0, 0, 0, 0, 0,
+    ]);
+    bytes.extend([0, 1, 0, 19, 0, 0, 0, 6, 0, 1, 0, 11, 0, 0]);
+
+    let raw = libjcdump::parse_raw(&mut bytes.as_slice())?;
+    let data = libjcdump::wrap(&raw)?;
+    let code = data.methods()[0].code().unwrap();
+    let instructions = data.disassemble(code)?;
+    let call_site = instructions[0].call_site().unwrap();
+    assert_eq!(call_site.name(), "_");
+    assert_eq!(call_site.descriptor(), "Ljava/lang/Object;");
+    assert_eq!(
+        call_site.bootstrap_method().class(),
+        "java/lang/invoke/ConstantBootstraps"
+    );
+    assert_eq!(call_site.bootstrap_method().name(), "nullConstant");
+    assert!(instructions[1].call_site().is_none());
+
+    // `newarray` of an atype no JVMS release assigns, in place of `ldc #15`.
+    let mut malformed = bytes.clone();
+    let ldc = bytes
+        .windows(3)
+        .position(|w| w == [0x12, 15, 0xb0])
+        .unwrap();
+    malformed[ldc..ldc + 2].copy_from_slice(&[0xbc, 3]);
+    let raw = libjcdump::parse_raw(&mut malformed.as_slice())?;
+    let data = libjcdump::wrap(&raw)?;
+    assert!(matches!(
+        data.disassemble(data.methods()[0].code().unwrap()),
+        Err(libjcdump::ParseError::MalformedAttribute { name, detail })
+            if name == "Code" && detail == "newarray atype 3 is not assigned"
+    ));
+
+    // A reference_kind of 0 for the MethodHandle #11.
+    let handle = bytes.windows(4).position(|w| w == [15, 6, 0, 10]).unwrap();
+    bytes[handle + 1] = 0;
+    let raw = libjcdump::parse_raw(&mut bytes.as_slice())?;
+    assert!(matches!(
+        libjcdump::wrap(&raw),
+        Err(libjcdump::ParseError::InvalidConstant {
+            index: 11,
+            field: "reference_kind",
+            value: 0
+        })
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn disassemble_with_loader() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Main.java")])?;
+
+    let loader = libjcdump::ClassLoader::new([output.path()]);
+    let raw = loader.load("com/example/Main")?.unwrap();
+    let data = libjcdump::wrap(&raw)?;
+
+    let constructor = data.methods().iter().find(|m| m.name() == "<init>");
+    let instructions =
+        data.disassemble_with_loader(constructor.unwrap().code().unwrap(), &loader)?;
+    let linkages = instructions
+        .iter()
+        .filter_map(|instruction| instruction.linkage())
+        .collect::<Vec<_>>();
+    // super(), then the constructor of the anonymous class.
+    assert!(matches!(
+        linkages[0],
+        libjcdump::Linkage::Unknown { class } if class == "java/lang/Object"
+    ));
+    assert!(matches!(
+        linkages[1],
+        libjcdump::Linkage::Found { class, .. } if class == "com/example/Main$1"
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn overriding_methods() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Overrides.java")])?;
+
+    let loader = libjcdump::ClassLoader::new([output.path()]);
+    let raw = loader.load("com/example/Overrides")?.unwrap();
+    let data = libjcdump::wrap(&raw)?;
+
+    let overriding = data.overriding_methods(&loader)?;
+    let names = overriding
+        .iter()
+        .map(|(method, _)| method.name())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["area", "toString"]);
+    assert!(matches!(
+        &overriding[0].1,
+        libjcdump::Linkage::Found { class, .. } if class == "com/example/Shape"
+    ));
+    assert!(matches!(
+        &overriding[1].1,
+        libjcdump::Linkage::Unknown { class } if class == "java/lang/Object"
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn missing_bootstrap_methods() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Main.java")])?;
+
+    let mut main = fs::File::open(output.path().join("./com/example/Main.class"))?;
+    let mut raw = libjcdump::parse_raw(&mut main)?;
+    let position = {
+        let data = libjcdump::wrap(&raw)?;
+        let attributes = data.attributes();
+        attributes
+            .iter()
+            .position(|a| a.name() == "BootstrapMethods")
+    };
+    raw.attributes.remove(position.unwrap());
+
+    assert!(matches!(
+        libjcdump::wrap(&raw),
+        Err(libjcdump::ParseError::MissingBootstrapMethods)
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn resolved_constants() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Main.java")])?;
+
+    let mut main = fs::File::open(output.path().join("./com/example/Main.class"))?;
+    let raw = libjcdump::parse_raw(&mut main)?;
+
+    let (index, _) = raw
+        .resolved_constants()
+        .find(|(_, constant)| matches!(constant, Ok(libjcdump::CpInfo::Long(0))))
+        .unwrap();
+    // The slot after a long is unusable.
+    assert!(raw.resolved_constants().all(|(i, _)| i != index + 1));
+    assert!(
+        raw.resolved_constants()
+            .all(|(_, constant)| constant.is_ok())
+    );
+
+    // Index 0 and the slots after the long and the double are not counted.
+    let data = libjcdump::wrap(&raw)?;
+    assert_eq!(data.real_constant_count(), raw.constant_pool.len() - 3);
+    assert_eq!(data.real_constant_count(), raw.resolved_constants().count());
+
+    Ok(())
+}
+
+#[test]
+fn method_handle_reference_index() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Main.java")])?;
+
+    let mut main = fs::File::open(output.path().join("./com/example/Main.class"))?;
+    let raw = libjcdump::parse_raw(&mut main)?;
+
+    let data = libjcdump::wrap(&raw)?.to_json_value()?;
+    let handle = attribute(&data["constant_pool"], "MethodHandle").unwrap();
+    assert!(handle.get("reference_index").is_none());
+
+    let options = libjcdump::ParseOptions {
+        method_handle_reference_index: true,
+        ..Default::default()
+    };
+    let data = libjcdump::wrap_with_options(&raw, &options)?.to_json_value()?;
+    let handle = attribute(&data["constant_pool"], "MethodHandle").unwrap();
+    let index = handle["reference_index"].as_u64().unwrap() as u16;
+    let (_, reference) = raw.resolved_constants().find(|(i, _)| *i == index).unwrap();
+    match reference? {
+        libjcdump::CpInfo::Methodref { class, name, .. } => {
+            assert_eq!(handle["class"], class);
+            assert_eq!(handle["name"], name);
+        }
+        reference => panic!("{reference:?}"),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn keywords() -> anyhow::Result<()> {
+    use libjcdump::{ClassAccessFlags, FieldAccessFlags, MethodAccessFlags, ReferenceKind};
+
+    assert_eq!(
+        "invokevirtual".parse::<ReferenceKind>()?,
+        ReferenceKind::RefInvokeVirtual
+    );
+    assert_eq!(
+        ReferenceKind::RefNewInvokeSpecial.to_string(),
+        "newinvokespecial"
+    );
+    assert_eq!(
+        "public".parse::<ClassAccessFlags>()?,
+        ClassAccessFlags::AccPublic
+    );
+    assert_eq!(
+        "protected".parse::<FieldAccessFlags>()?,
+        FieldAccessFlags::AccProtected
+    );
+    assert_eq!(
+        "strictfp".parse::<MethodAccessFlags>()?,
+        MethodAccessFlags::AccStrict
+    );
+    assert_eq!(MethodAccessFlags::AccBridge.to_string(), "bridge");
+    assert_eq!(
+        "synchronized".parse::<MethodAccessFlags>()?,
+        MethodAccessFlags::AccSynchronized
+    );
+    assert_eq!(
+        serde_json::to_string(&[
+            MethodAccessFlags::AccProtected,
+            MethodAccessFlags::AccSynchronized
+        ])?,
+        r#"["AccProtected","AccSynchronized"]"#
+    );
+
+    let err = "volatile".parse::<MethodAccessFlags>().unwrap_err();
+    assert_eq!(err.keyword, "volatile");
+    assert!("Public".parse::<ClassAccessFlags>().is_err());
+
+    Ok(())
+}
+
+#[test]
+fn split_nested_name() -> anyhow::Result<()> {
+    use libjcdump::split_nested_name;
+
+    assert_eq!(
+        split_nested_name("com/example/Outer$Inner$Leaf"),
+        (Some("com/example/Outer$Inner"), "Leaf")
+    );
+    assert_eq!(split_nested_name("com/example/Main"), (None, "Main"));
+    assert_eq!(split_nested_name("Outer$1"), (Some("Outer"), "1"));
+    assert_eq!(split_nested_name("Outer$1Local"), (Some("Outer"), "Local"));
+    assert_eq!(
+        split_nested_name("com/sun/proxy/$Proxy1"),
+        (None, "$Proxy1")
+    );
+    assert_eq!(split_nested_name("Outer$"), (None, "Outer$"));
+    assert_eq!(
+        split_nested_name("a/Outer$$Lambda"),
+        (None, "Outer$$Lambda")
+    );
+    // Only the class name is split, not the package.
+    assert_eq!(split_nested_name("a$b/Main"), (None, "Main"));
+
+    // The nested classes of Main.java.
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Main.java")])?;
+    let mut anonymous = fs::File::open(output.path().join("./com/example/Main$1.class"))?;
+    let raw = libjcdump::parse_raw(&mut anonymous)?;
+    let data = libjcdump::wrap(&raw)?;
+    assert_eq!(
+        split_nested_name(data.this_class()),
+        (Some("com/example/Main"), "1")
+    );
+
+    Ok(())
+}
+
+#[test]
+fn wide_constants_json() -> anyhow::Result<()> {
+    let value = |constant: libjcdump::CpInfo<&str>| {
+        let value = serde_json::to_value(constant).unwrap();
+        if cfg!(feature = "tagged") {
+            value["value"].clone()
+        } else {
+            value.as_object().unwrap().values().next().unwrap().clone()
+        }
+    };
+
+    assert_eq!(
+        value(libjcdump::CpInfo::Long(i64::MAX)),
+        serde_json::json!({"value": "9223372036854775807", "bits": "0x7fffffffffffffff"})
+    );
+    assert_eq!(
+        value(libjcdump::CpInfo::Long(-1)),
+        serde_json::json!({"value": "-1", "bits": "0xffffffffffffffff"})
+    );
+    assert_eq!(
+        value(libjcdump::CpInfo::Double(f64::NAN)),
+        serde_json::json!({"value": "NaN", "bits": "0x7ff8000000000000"})
+    );
+    assert_eq!(
+        value(libjcdump::CpInfo::Double(0.5)),
+        serde_json::json!({"value": "0.5", "bits": "0x3fe0000000000000"})
+    );
+
+    Ok(())
+}
+
+#[test]
+fn java_signature() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Main.java")])?;
+
+    let mut main = fs::File::open(output.path().join("./com/example/Main.class"))?;
+    let raw = libjcdump::parse_raw(&mut main)?;
+    let data = libjcdump::wrap(&raw)?;
+
+    let method = |name: &str| data.methods().iter().find(|m| m.name() == name).unwrap();
+    assert_eq!(
+        method("main").java_signature(),
+        "public static void main() throws java.lang.Exception"
+    );
+    assert_eq!(
+        method("of").java_signature(),
+        "public static <T extends java.lang.Comparable<T>> java.util.List<T> of(T... arg0) throws java.io.IOException"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn class_java_signature() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Main.java")])?;
+
+    let signature = |path: &str, name: &str| -> anyhow::Result<String> {
+        let mut class = fs::File::open(output.path().join(path))?;
+        let raw = libjcdump::parse_raw(&mut class)?;
+        let data = libjcdump::wrap(&raw)?;
+        let method = data.methods().iter().find(|m| m.name() == name).unwrap();
+        Ok(data.java_signature(method))
+    };
+    assert_eq!(
+        signature("./com/example/Main.class", "<init>")?,
+        "public Main()"
+    );
+    assert_eq!(
+        signature("./com/example/Main.class", "<clinit>")?,
+        "static {}"
+    );
+    assert_eq!(
+        signature("./com/example/Main.class", "main")?,
+        "public static void main() throws java.lang.Exception"
+    );
+    // Anonymous classes have no name to declare constructors with.
+    assert_eq!(
+        signature("./com/example/Main$1.class", "<init>")?,
+        "com.example.Main$1(com.example.Main arg0)"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn parsed_class_from_bytes() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Main.java")])?;
+
+    let bytes = fs::read(output.path().join("./com/example/Main.class"))?;
+    let len = bytes.len();
+    let parsed = libjcdump::ParsedClass::from_bytes(bytes)?;
+    drop(output);
+
+    // Outlives both the buffer and the directory it was read from.
+    let parsed = std::thread::spawn(move || parsed).join().unwrap();
+    assert_eq!(parsed.bytes().len(), len);
+    assert!(
+        parsed
+            .get()
+            .methods()
+            .iter()
+            .any(|method| method.name() == "main")
+    );
+    assert_eq!(
+        serde_json::to_value(&parsed)?,
+        parsed.get().to_json_value()?
+    );
+
+    assert!(matches!(
+        libjcdump::ParsedClass::from_bytes(&parsed.bytes()[..8]),
+        Err(libjcdump::ParseError::UnexpectedEof { .. })
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn into_owned() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac_with_args(srcdir.clone(), [srcdir.join("Main.java")], ["-g"])?;
+
+    fn read(path: &Path) -> anyhow::Result<(serde_json::Value, libjcdump::OwnedClassFile)> {
+        let raw = libjcdump::parse_raw(&mut fs::File::open(path)?)?;
+        let data = libjcdump::wrap(&raw)?;
+        Ok((serde_json::to_value(&data)?, data.into_owned()))
+    }
+    let (expected, owned) = read(&output.path().join("./com/example/Main.class"))?;
+    drop(output);
+
+    let owned = std::thread::spawn(move || owned).join().unwrap();
+    assert_eq!(owned.this_class(), "com/example/Main");
+    assert_eq!(serde_json::to_value(&owned)?, expected);
+
+    Ok(())
+}
+
+#[test]
+fn write_round_trip() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Main.java")])?;
+
+    let bytes = fs::read(output.path().join("./com/example/Main.class"))?;
+    let raw = libjcdump::parse_raw(&mut bytes.as_slice())?;
+    assert_eq!(raw.to_bytes()?, bytes);
+
+    // Standard UTF-8 encodes NUL in one byte, modified UTF-8 in two.
+    let mut bytes = bytes;
+    let position = bytes
+        .windows(8)
+        .position(|window| window == b"Condy!!!")
+        .unwrap();
+    bytes[position + 5] = 0;
+    let raw = libjcdump::parse_raw(&mut bytes.as_slice())?;
+    assert!(matches!(
+        libjcdump::validate(&raw).findings(),
+        [libjcdump::Finding::Utf8Length {
+            length: 8,
+            expected: 9,
+            ..
+        }]
+    ));
+    assert_eq!(raw.to_bytes()?, bytes);
+
+    Ok(())
+}
+
+#[test]
+fn hex_unknown() -> anyhow::Result<()> {
+    let mut pool = vec![];
+    let utf8 = |pool: &mut Vec<u8>, value: &str| {
+        pool.push(1);
+        pool.extend((value.len() as u16).to_be_bytes());
+        pool.extend(value.as_bytes());
+    };
+    utf8(&mut pool, "Vendor"); // #1
+    pool.extend([7, 0, 1]); // #2 Class
+    utf8(&mut pool, "java/lang/Object"); // #3
+    pool.extend([7, 0, 3]); // #4 Class
+    utf8(&mut pool, "x"); // #5
+    utf8(&mut pool, "I"); // #6
+    utf8(&mut pool, "com.example.Vendor"); // #7
+
+    let mut bytes = vec![0xca, 0xfe, 0xba, 0xbe, 0, 0, 0, 55, 0, 8];
+    bytes.extend(pool);
+    bytes.extend([0x00, 0x21, 0, 2, 0, 4, 0, 0]);
+    // A field `int x` with a three byte vendor attribute.
+    bytes.extend([0, 1, 0x00, 0x02, 0, 5, 0, 6, 0, 1]);
+    bytes.extend([0, 7, 0, 0, 0, 3, 1, 2, 3]);
+    bytes.extend([0, 0]);
+    // A twenty byte vendor attribute of the class, over two lines.
+    bytes.extend([0, 1, 0, 7, 0, 0, 0, 20]);
+    bytes.extend(b"jcdump\x00\x01\x02\x03 hex dump!");
+
+    let raw = libjcdump::parse_raw(&mut bytes.as_slice())?;
+    let data = libjcdump::wrap_with_options(
+        &raw,
+        &libjcdump::ParseOptions {
+            hex_unknown: true,
+            ..Default::default()
+        },
+    )?;
+    let value = serde_json::to_value(&data)?;
+
+    let unknown = attribute(&value["attributes"], "Unknown").unwrap();
+    assert_eq!(unknown["name"], "com.example.Vendor");
+    assert_eq!(
+        unknown["info"],
+        serde_json::json!([
+            "0000: 6a 63 64 75 6d 70 00 01 02 03 20 68 65 78 20 64  jcdump.... hex d",
+            format!("0010: 75 6d 70 21{}  ump!", " ".repeat(12 * 3)),
+        ])
+    );
+    let unknown = attribute(&value["fields"][0]["attributes"], "Unknown").unwrap();
+    assert_eq!(
+        unknown["info"],
+        serde_json::json!([format!("0000: 01 02 03{}  ...", " ".repeat(13 * 3))])
+    );
+
+    // Serialized in base64 again outside of the class file wrapped with `hex_unknown`.
+    let attributes = serde_json::to_value(data.attributes())?;
+    assert!(attribute(&attributes, "Unknown").unwrap()["info"].is_string());
+    let value = libjcdump::wrap(&raw)?.to_json_value()?;
+    assert!(attribute(&value["attributes"], "Unknown").unwrap()["info"].is_string());
+
+    Ok(())
+}
+
+#[test]
+fn validate_missing_acc_super() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Main.java")])?;
+
+    let mut main = fs::File::open(output.path().join("./com/example/Main.class"))?;
+    let mut raw = libjcdump::parse_raw(&mut main)?;
+    raw.access_flags &= !0x0020;
+    assert_eq!(
+        libjcdump::validate(&raw).findings(),
+        [libjcdump::Finding::MissingAccSuper]
+    );
+
+    // Not expected on modules.
+    let mut module = fs::File::open(output.path().join("./module-info.class"))?;
+    let raw = libjcdump::parse_raw(&mut module)?;
+    assert!(libjcdump::validate(&raw).is_clean());
+
+    Ok(())
+}
+
+#[test]
+fn stack_map_table_extended_frames() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Main.java")])?;
+
+    let mut main = fs::File::open(output.path().join("./com/example/Main.class"))?;
+    let raw = libjcdump::parse_raw(&mut main)?;
+    let data = libjcdump::wrap(&raw)?;
+
+    let method = data.methods().iter().find(|m| m.name() == "frames");
+    let code = method.unwrap().code().unwrap();
+    let Some(libjcdump::AttributeInfo::StackMapTable(frames)) =
+        code.attributes().by_name("StackMapTable")
+    else {
+        panic!("no StackMapTable");
+    };
+    assert!(matches!(
+        frames.as_slice(),
+        [
+            libjcdump::StackMapFrame::SameLocals1StackItemFrameExtended {
+                stack: libjcdump::VerificationTypeInfo::ObjectVariable(
+                    "java/lang/RuntimeException"
+                ),
+                ..
+            },
+            libjcdump::StackMapFrame::SameFrame { .. },
+            libjcdump::StackMapFrame::SameFrameExtended { .. },
+        ]
+    ));
+
+    // Every frame is at an instruction.
+    let offsets = code
+        .instructions()?
+        .iter()
+        .map(|instruction| instruction.offset())
+        .collect::<Vec<_>>();
+    let mut offset = None;
+    for frame in frames {
+        let next = match offset {
+            None => frame.offset_delta() as u32,
+            Some(offset) => offset + frame.offset_delta() as u32 + 1,
+        };
+        assert!(offsets.contains(&next));
+        offset = Some(next);
+    }
+    assert!(frames[0].offset_delta() > 63);
+    assert!(frames[2].offset_delta() > 63);
+
+    Ok(())
+}
+
+#[test]
+fn stack_map_frame_offsets() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Main.java")])?;
+
+    let mut main = fs::File::open(output.path().join("./com/example/Main.class"))?;
+    let raw = libjcdump::parse_raw(&mut main)?;
+    let data = libjcdump::wrap(&raw)?;
+
+    let method = data.methods().iter().find(|m| m.name() == "frames");
+    let code = method.unwrap().code().unwrap();
+    let offsets = code
+        .instructions()?
+        .iter()
+        .map(|instruction| instruction.offset())
+        .collect::<Vec<_>>();
+    let frames = code.stack_map_frames().collect::<Vec<_>>();
+    assert_eq!(frames.len(), 3);
+    assert!(frames.iter().all(|(offset, _)| offsets.contains(offset)));
+    assert!(frames.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    // The first offset_delta is the offset itself, the next ones one less than the distance.
+    assert_eq!(frames[0].0, frames[0].1.offset_delta() as u32);
+    assert_eq!(
+        frames[1].0,
+        frames[0].0 + frames[1].1.offset_delta() as u32 + 1
+    );
+    assert!(matches!(
+        frames[2].1,
+        libjcdump::StackMapFrame::SameFrameExtended { .. }
+    ));
+
+    // Without a StackMapTable, e.g. in a method without branches.
+    let method = data.methods().iter().find(|m| m.name() == "main");
+    assert_eq!(
+        method.unwrap().code().unwrap().stack_map_frames().count(),
+        0
+    );
+
+    Ok(())
+}
+
+#[test]
+fn parse_descriptors() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Main.java")])?;
+
+    let mut main = fs::File::open(output.path().join("./com/example/Main.class"))?;
+    let raw = libjcdump::parse_raw(&mut main)?;
+    let data = libjcdump::wrap_with_options(
+        &raw,
+        &libjcdump::ParseOptions {
+            parse_descriptors: true,
+            ..Default::default()
+        },
+    )?;
+    let value = serde_json::to_value(&data)?;
+    let constant_pool = value["constant_pool"].as_array().unwrap();
+
+    let entries = |kind: &str| {
+        constant_pool
+            .iter()
+            .filter_map(|entry| attribute(&serde_json::json!([entry]), kind).cloned())
+            .collect::<Vec<_>>()
+    };
+    let methodrefs = entries("Methodref");
+    assert!(methodrefs.contains(&serde_json::json!({
+        "class": "java/lang/Object",
+        "name": "<init>",
+        "descriptor": { "parameters": [], "return_type": null },
+    })));
+    let fieldrefs = entries("Fieldref");
+    assert!(fieldrefs.contains(&serde_json::json!({
+        "class": "java/lang/System",
+        "name": "out",
+        "descriptor": { "Object": "java/io/PrintStream" },
+    })));
+    assert!(
+        entries("NameAndType")
+            .iter()
+            .all(|entry| entry["descriptor"].is_object())
+    );
+
+    // A NameAndType nothing refers to, with a descriptor which does not parse.
+    let mut pool = vec![];
+    let utf8 = |pool: &mut Vec<u8>, value: &str| {
+        pool.push(1);
+        pool.extend((value.len() as u16).to_be_bytes());
+        pool.extend(value.as_bytes());
+    };
+    utf8(&mut pool, "Unparsed"); // #1
+    pool.extend([7, 0, 1]); // #2 Class
+    utf8(&mut pool, "java/lang/Object"); // #3
+    pool.extend([7, 0, 3]); // #4 Class
+    utf8(&mut pool, "x"); // #5
+    utf8(&mut pool, "Q"); // #6
+    pool.extend([12, 0, 5, 0, 6]); // #7 NameAndType
+
+    let mut bytes = vec![0xca, 0xfe, 0xba, 0xbe, 0, 0, 0, 55, 0, 8];
+    bytes.extend(pool);
+    bytes.extend([0x00, 0x21, 0, 2, 0, 4, 0, 0, 0, 0, 0, 0, 0, 0]);
+    let raw = libjcdump::parse_raw(&mut bytes.as_slice())?;
+    let data = libjcdump::wrap_with_options(
+        &raw,
+        &libjcdump::ParseOptions {
+            parse_descriptors: true,
+            ..Default::default()
+        },
+    )?;
+    let value = serde_json::to_value(&data)?;
+    let constant_pool = serde_json::json!([value["constant_pool"][7]]);
+    assert_eq!(
+        attribute(&constant_pool, "NameAndType"),
+        Some(&serde_json::json!({ "name": "x", "descriptor": "Q" }))
+    );
+
+    Ok(())
+}
+
+#[test]
+fn non_exhaustive_match_with_wildcard() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Main.java")])?;
+
+    let bytes = fs::read(output.path().join("./com/example/Main.class"))?;
+    let raw = libjcdump::parse_raw(&mut bytes.as_slice())?;
+    let data = libjcdump::wrap(&raw)?;
+
+    let source_files = data
+        .attributes()
+        .iter()
+        .filter_map(|attribute| match attribute {
+            libjcdump::AttributeInfo::SourceFile(source_file) => Some(*source_file),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(source_files, ["Main.java"]);
+
+    let strings = raw
+        .resolved_constants()
+        .filter(|(_, constant)| match constant {
+            Ok(libjcdump::CpInfo::String { .. }) => true,
+            Ok(_) | Err(_) => false,
+        })
+        .count();
+    assert!(strings > 0);
+
+    let message = match libjcdump::parse_raw(&mut &bytes[..10]) {
+        Err(libjcdump::ParseError::UnexpectedEof { while_reading, .. }) => while_reading,
+        Err(_) => "other error",
+        Ok(_) => "no error",
+    };
+    assert_eq!(message, "cp_info tag");
+
+    Ok(())
+}
+
+#[test]
+fn reserved_constant_pool_tag() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Main.java")])?;
+
+    let mut bytes = fs::read(output.path().join("./com/example/Main.class"))?;
+    // The tag of the first constant follows magic, version and constant_pool_count.
+    for tag in [2, 13, 14] {
+        bytes[10] = tag;
+        assert!(matches!(
+            libjcdump::parse_raw(&mut bytes.as_slice()),
+            Err(libjcdump::ParseError::ReservedConstantPoolTag(t)) if t == tag
+        ));
+    }
+    bytes[10] = 21;
+    assert!(matches!(
+        libjcdump::parse_raw(&mut bytes.as_slice()),
+        Err(libjcdump::ParseError::InvalidConstant {
+            index: 1,
+            field: "tag",
+            value: 21
+        })
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn method_ref_interface_mismatch() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Main.java")])?;
+
+    let mut main = fs::File::open(output.path().join("./com/example/Main.class"))?;
+    let mut raw = libjcdump::parse_raw(&mut main)?;
+    // The method handle of the lambda refers to Main with a CONSTANT_Methodref.
+    raw.access_flags = 0x0001 | 0x0200 | 0x0400;
+    assert!(matches!(
+        libjcdump::wrap(&raw),
+        Err(libjcdump::ParseError::MethodRefInterfaceMismatch {
+            interface_method_ref: false,
+            ..
+        })
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn parse_limits() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Main.java")])?;
+
+    let bytes = fs::read(output.path().join("./com/example/Main.class"))?;
+    let parse = |limits: libjcdump::ParseLimits| {
+        libjcdump::parse_raw_with_limits(&mut bytes.as_slice(), &limits)
+    };
+    let limit_exceeded = |limits: libjcdump::ParseLimits| match parse(limits) {
+        Err(libjcdump::ParseError::LimitExceeded { limit, .. }) => limit,
+        _ => "none",
+    };
+
+    parse(libjcdump::ParseLimits {
+        max_input_size: bytes.len() as u64,
+        ..Default::default()
+    })?;
+    assert_eq!(
+        limit_exceeded(libjcdump::ParseLimits {
+            max_input_size: bytes.len() as u64 - 1,
+            ..Default::default()
+        }),
+        "max_input_size"
+    );
+    assert_eq!(
+        limit_exceeded(libjcdump::ParseLimits {
+            max_constant_pool: 10,
+            ..Default::default()
+        }),
+        "max_constant_pool"
+    );
+    assert_eq!(
+        limit_exceeded(libjcdump::ParseLimits {
+            max_fields: 1,
+            ..Default::default()
+        }),
+        "max_fields"
+    );
+    assert_eq!(
+        limit_exceeded(libjcdump::ParseLimits {
+            max_methods: 1,
+            ..Default::default()
+        }),
+        "max_methods"
+    );
+    assert_eq!(
+        limit_exceeded(libjcdump::ParseLimits {
+            max_attribute_length: 1,
+            ..Default::default()
+        }),
+        "max_attribute_length"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn omit_magic() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Main.java")])?;
+
+    let mut main = fs::File::open(output.path().join("./com/example/Main.class"))?;
+    let raw = libjcdump::parse_raw(&mut main)?;
+
+    let value = libjcdump::wrap(&raw)?.to_json_value()?;
+    assert_eq!(value["magic"], "0xCAFEBABE");
+
+    let options = libjcdump::ParseOptions {
+        omit_magic: true,
+        ..Default::default()
+    };
+    let value = libjcdump::wrap_with_options(&raw, &options)?.to_json_value()?;
+    assert!(value.get("magic").is_none());
+    assert!(value.get("version").is_some());
+
+    Ok(())
+}
+
+#[test]
+fn line_number_map() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Main.java")])?;
+
+    let mut main = fs::File::open(output.path().join("./com/example/Main.class"))?;
+    let raw = libjcdump::parse_raw(&mut main)?;
+    let data = libjcdump::wrap(&raw)?;
+
+    let source = fs::read_to_string(srcdir.join("Main.java"))?;
+    let line_of = |text: &str| {
+        source
+            .lines()
+            .position(|line| line.contains(text))
+            .map(|n| n as u16 + 1)
+    };
+
+    let method = data.methods().iter().find(|m| m.name() == "main").unwrap();
+    let code = method.code().unwrap();
+    let lines = code.line_number_map();
+    assert_eq!(lines.get(&0).copied(), line_of("Runnable indyPlease"));
+    assert_eq!(lines.len(), 3);
+
+    let Some(libjcdump::AttributeInfo::LineNumberTable(entries)) =
+        code.attributes().by_name("LineNumberTable")
+    else {
+        panic!("no LineNumberTable attribute");
+    };
+    assert_eq!(
+        entries
+            .iter()
+            .map(|entry| (entry.start_pc(), entry.line_number()))
+            .collect::<Vec<_>>(),
+        lines
+            .iter()
+            .map(|(pc, line)| (*pc, *line))
+            .collect::<Vec<_>>()
+    );
+
+    // The return after the last statement has a line of its own, the closing brace.
+    let instructions = code.instructions()?;
+    let pc = instructions.last().unwrap().offset() as u16;
+    assert_eq!(
+        lines.range(..=pc).next_back().map(|(_, line)| *line),
+        line_of("indyPlease.run()").map(|n| n + 1)
+    );
+    // The invocation before it belongs to the line of the statement.
+    let pc = instructions[instructions.len() - 2].offset() as u16;
+    assert_eq!(
+        lines.range(..=pc).next_back().map(|(_, line)| *line),
+        line_of("indyPlease.run()")
+    );
+
+    Ok(())
+}
+
+#[test]
+fn disassembly_text() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Main.java")])?;
+
+    let mut main = fs::File::open(output.path().join("./com/example/Main.class"))?;
+    let raw = libjcdump::parse_raw(&mut main)?;
+    let data = libjcdump::wrap(&raw)?;
+
+    let method = data.methods().iter().find(|m| m.name() == "main").unwrap();
+    let code = method.code().unwrap();
+    let text = code.disassembly_text()?;
+    let lines = text.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 5);
+    assert!(lines[0].starts_with("0: invokedynamic #"));
+    assert_eq!(lines[1..3], ["5: astore_0", "6: aload_0"]);
+    assert!(lines[3].starts_with("7: invokeinterface #") && lines[3].ends_with(", 1"));
+    assert_eq!(lines[4], "12: return");
+    assert_eq!(text, code.disassembly_text()?);
+
+    // Branch targets are absolute.
+    let method = data
+        .methods()
+        .iter()
+        .find(|m| m.name() == "frames")
+        .unwrap();
+    let text = method.code().unwrap().disassembly_text()?;
+    let branch = text.lines().find(|line| line.contains(": ifle ")).unwrap();
+    let target = branch.rsplit_once(' ').unwrap().1;
+    assert!(
+        text.lines()
+            .any(|line| line.starts_with(&format!("{target}: ")))
+    );
+
+    let instruction = serde_json::to_string(&code.instructions()?[4])?;
+    assert_eq!(instruction, r#"{"offset":12,"operation":"Return"}"#);
+
+    Ok(())
+}
+
+#[test]
+fn method_parameters() -> anyhow::Result<()> {
+    use libjcdump::MethodParameterAccessFlags::{AccFinal, AccMandated, AccSynthetic};
+
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac_with_args(
+        srcdir.clone(),
+        [srcdir.join("Parameters.java")],
+        ["-parameters"],
+    )?;
+
+    let parameters = |class: &str, method: &str| -> anyhow::Result<Vec<(String, Vec<_>)>> {
+        let path = output.path().join(format!("./com/example/{class}.class"));
+        let raw = libjcdump::parse_raw(&mut fs::File::open(path)?)?;
+        let data = libjcdump::wrap(&raw)?;
+        let method = data.methods().iter().find(|m| m.name() == method).unwrap();
+        let Some(libjcdump::AttributeInfo::MethodParameters(parameters)) =
+            method.attributes().by_name("MethodParameters")
+        else {
+            anyhow::bail!("no MethodParameters");
+        };
+        Ok(parameters
+            .iter()
+            .map(|p| (p.name().unwrap().to_string(), p.access_flags().to_vec()))
+            .collect())
+    };
+
+    // The enclosing instance and the parameter of valueOf are implicitly declared.
+    assert_eq!(
+        parameters("Parameters$Inner", "<init>")?,
+        [
+            ("this$0".to_string(), vec![AccFinal, AccMandated]),
+            ("value".to_string(), vec![AccFinal]),
+        ]
+    );
+    assert_eq!(
+        parameters("Parameters$Color", "valueOf")?,
+        [("name".to_string(), vec![AccMandated])]
+    );
+    // The name and ordinal of an enum constant are added by javac.
+    assert_eq!(
+        parameters("Parameters$Color", "<init>")?,
+        [
+            ("$enum$name".to_string(), vec![AccSynthetic]),
+            ("$enum$ordinal".to_string(), vec![AccSynthetic]),
+        ]
+    );
+    assert_eq!(AccMandated.to_string(), "mandated");
+
+    Ok(())
+}
+
+#[test]
+fn local_name_at() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac_with_args(srcdir.clone(), [srcdir.join("Main.java")], ["-g"])?;
+
+    let mut main = fs::File::open(output.path().join("./com/example/Main.class"))?;
+    let raw = libjcdump::parse_raw(&mut main)?;
+    let data = libjcdump::wrap(&raw)?;
+    let code = |name: &str| {
+        data.methods()
+            .iter()
+            .find(|m| m.name() == name)
+            .unwrap()
+            .code()
+            .unwrap()
+    };
+
+    // invokedynamic, astore_0, aload_0, ...: in scope after the store.
+    let main = code("main");
+    assert_eq!(main.local_name_at(0, 5), None);
+    assert_eq!(main.local_name_at(0, 6), Some("indyPlease"));
+    assert_eq!(main.local_name_at(0, 12), Some("indyPlease"));
+    assert_eq!(main.local_name_at(0, 13), None);
+    assert_eq!(main.local_name_at(1, 6), None);
+
+    // The exception is stored into slot 1 by the handler, in scope only within the catch block.
+    let frames = code("frames");
+    assert_eq!(frames.local_name_at(0, 0), Some("x"));
+    let handler = (0..u16::MAX)
+        .find(|pc| frames.local_name_at(1, *pc).is_some())
+        .unwrap();
+    assert_eq!(frames.local_name_at(1, handler), Some("e"));
+    assert_eq!(frames.local_name_at(1, handler - 1), None);
+    assert_eq!(frames.local_name_at(0, handler), Some("x"));
+
+    let Some(libjcdump::AttributeInfo::LocalVariableTable(entries)) =
+        frames.attributes().by_name("LocalVariableTable")
+    else {
+        panic!("no LocalVariableTable attribute");
+    };
+    let e = entries.iter().find(|entry| entry.name() == "e").unwrap();
+    assert_eq!(
+        (e.slot(), e.start_pc(), e.descriptor()),
+        (1, handler, "Ljava/lang/RuntimeException;")
+    );
+    let x = entries.iter().find(|entry| entry.name() == "x").unwrap();
+    assert_eq!((x.slot(), x.start_pc(), x.descriptor()), (0, 0, "I"));
+    assert_eq!(x.length() as usize, frames.bytecode().len());
+    let x = serde_json::to_value(x)?;
+    assert_eq!((&x["name"], &x["slot"]), (&"x".into(), &0.into()));
+
+    // Without -g, only `this` is known.
+    let output = javac(srcdir.clone(), [srcdir.join("Main.java")])?;
+    let mut main = fs::File::open(output.path().join("./com/example/Main.class"))?;
+    let raw = libjcdump::parse_raw(&mut main)?;
+    let data = libjcdump::wrap(&raw)?;
+    let method = |name: &str| data.methods().iter().find(|m| m.name() == name).unwrap();
+    let constructor = method("<init>");
+    assert_eq!(constructor.code().unwrap().local_name_at(0, 0), None);
+    assert_eq!(constructor.local_name_at(0, 0), Some("this"));
+    assert_eq!(constructor.local_name_at(1, 0), None);
+    assert_eq!(constructor.local_name_at(0, u16::MAX), None);
+    assert_eq!(method("main").local_name_at(0, 0), None);
+
+    Ok(())
+}
+
+#[test]
+fn package_info() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(
+        srcdir.clone(),
+        [srcdir.join("package-info.java"), srcdir.join("Main.java")],
+    )?;
+
+    let mut main = fs::File::open(output.path().join("./com/example/Main.class"))?;
+    let raw = libjcdump::parse_raw(&mut main)?;
+    assert!(!libjcdump::wrap(&raw)?.is_package_info());
+
+    let mut package_info = fs::File::open(output.path().join("./com/example/package-info.class"))?;
+    let raw = libjcdump::parse_raw(&mut package_info)?;
+    let data = libjcdump::wrap(&raw)?;
+    assert!(data.is_package_info());
+
+    let Some(libjcdump::AttributeInfo::RuntimeVisibleAnnotations(annotations)) =
+        data.attributes().by_name("RuntimeVisibleAnnotations")
+    else {
+        panic!("no RuntimeVisibleAnnotations");
+    };
+    assert_eq!(annotations[0].type_descriptor(), "Ljava/lang/Deprecated;");
+    assert!(matches!(
+        annotations[0].elements(),
+        [
+            ("since", libjcdump::ElementValue::String("1.0")),
+            ("forRemoval", libjcdump::ElementValue::Boolean(true)),
+        ]
+    ));
+
+    assert_eq!(
+        data.to_text(&libjcdump::TextOptions::default()),
+        "Compiled from \"package-info.java\"\n\
+         @java.lang.Deprecated(since=\"1.0\", forRemoval=true)\n\
+         package com.example;\n"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn annotation_default() -> anyhow::Result<()> {
+    use libjcdump::ElementValue;
+
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Marker.java")])?;
+
+    let mut marker = fs::File::open(output.path().join("./com/example/Marker.class"))?;
+    let raw = libjcdump::parse_raw(&mut marker)?;
+    let data = libjcdump::wrap(&raw)?;
+    let method = |name| data.methods().iter().find(|m| m.name() == name).unwrap();
+    assert!(matches!(
+        method("value").annotation_default(),
+        Some(ElementValue::String("none"))
+    ));
+    assert!(matches!(
+        method("counts").annotation_default(),
+        Some(ElementValue::Array(values))
+            if matches!(values.as_slice(), [ElementValue::Int(1), ElementValue::Int(2)])
+    ));
+    assert!(method("type").annotation_default().is_none());
+
+    Ok(())
+}
+
+#[test]
+fn type_annotations() -> anyhow::Result<()> {
+    use libjcdump::{AnnotationTarget, AttributeInfo, TypePathKind};
+
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("TypeUses.java")])?;
+
+    let mut type_uses = fs::File::open(output.path().join("./com/example/TypeUses.class"))?;
+    let raw = libjcdump::parse_raw(&mut type_uses)?;
+    let data = libjcdump::wrap(&raw)?;
+    let single = |attributes: &libjcdump::Attributes<&str, &[u8]>| {
+        let Some(AttributeInfo::RuntimeVisibleTypeAnnotations(annotations)) =
+            attributes.by_name("RuntimeVisibleTypeAnnotations")
+        else {
+            panic!("no RuntimeVisibleTypeAnnotations");
+        };
+        let [annotation] = annotations.as_slice() else {
+            panic!("{annotations:?}");
+        };
+        assert_eq!(
+            annotation.annotation().type_descriptor(),
+            "Lcom/example/TypeUses$Nullable;"
+        );
+        let path = annotation
+            .target_path()
+            .iter()
+            .map(|entry| (entry.kind(), entry.type_argument_index()))
+            .collect::<Vec<_>>();
+        (annotation.target().clone(), path)
+    };
+
+    assert_eq!(
+        single(data.attributes()),
+        (
+            AnnotationTarget::ClassTypeParameter {
+                type_parameter_index: 0
+            },
+            vec![]
+        )
+    );
+    assert_eq!(
+        single(data.fields()[0].attributes()),
+        (
+            AnnotationTarget::Field,
+            vec![(TypePathKind::TypeArgument, 0)]
+        )
+    );
+    let method = |name| data.methods().iter().find(|m| m.name() == name).unwrap();
+    assert_eq!(
+        single(method("array").attributes()),
+        (AnnotationTarget::Return, vec![(TypePathKind::Array, 0)])
+    );
+    let code = method("cast").code().unwrap();
+    let (target, path) = single(code.attributes());
+    let AnnotationTarget::Cast {
+        offset,
+        type_argument_index: 0,
+    } = target
+    else {
+        panic!("{target:?}");
+    };
+    assert!(path.is_empty());
+    let checkcast = code
+        .instructions()?
+        .into_iter()
+        .find(|instruction| instruction.offset() == offset as u32)
+        .unwrap();
+    assert!(checkcast.to_string().contains("checkcast"));
+
+    Ok(())
+}
+
+#[test]
+fn type_annotation_references() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("TypeUses.java")])?;
+    let path = output.path().join("./com/example/TypeUses.class");
+
+    let utf8 = |value| serde_json::to_value(libjcdump::CpInfo::Utf8(value)).unwrap();
+    assert_eq!(
+        attribute_references(&path, "RuntimeVisibleTypeAnnotations")?,
+        [
+            utf8("Lcom/example/TypeUses$Nullable;"),
+            utf8("RuntimeVisibleTypeAnnotations")
+        ]
+    );
+
+    let bytes = fs::read(&path)?;
+    let mut raw = libjcdump::parse_raw(&mut bytes.as_slice())?;
+    // The descriptor of the annotation is only referred to by the type annotations.
+    assert!(raw.pool_analysis()?.unreferenced().is_empty());
+
+    raw.rename_class("com/example/TypeUses$Nullable", "com/example/Renamed")?;
+    let renamed = raw.to_bytes()?;
+    let raw = libjcdump::parse_raw(&mut renamed.as_slice())?;
+    let data = libjcdump::wrap(&raw)?;
+    let Some(libjcdump::AttributeInfo::RuntimeVisibleTypeAnnotations(annotations)) = data.fields()
+        [0]
+    .attributes()
+    .by_name("RuntimeVisibleTypeAnnotations") else {
+        panic!("no RuntimeVisibleTypeAnnotations");
+    };
+    assert_eq!(
+        annotations[0].annotation().type_descriptor(),
+        "Lcom/example/Renamed;"
+    );
+
+    let mut raw = libjcdump::parse_raw(&mut bytes.as_slice())?;
+    raw.canonicalize_pool()?;
+    let canonical = raw.to_bytes()?;
+    assert_eq!(canonical.len(), bytes.len());
+    let raw = libjcdump::parse_raw(&mut canonical.as_slice())?;
+    assert_eq!(
+        libjcdump::wrap(&raw)?.to_text(&libjcdump::TextOptions::default()),
+        libjcdump::wrap(&libjcdump::parse_raw(&mut bytes.as_slice())?)?
+            .to_text(&libjcdump::TextOptions::default())
+    );
+
+    Ok(())
+}
+
+#[test]
+fn opcodes() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Main.java")])?;
+
+    let mut main = fs::File::open(output.path().join("./com/example/Main.class"))?;
+    let raw = libjcdump::parse_raw(&mut main)?;
+    let data = libjcdump::wrap(&raw)?;
+
+    for method in data.methods() {
+        let Some(code) = method.code() else {
+            continue;
+        };
+        let offsets = code
+            .instructions()?
+            .iter()
+            .map(|instruction| instruction.offset())
+            .collect::<Vec<_>>();
+        let opcodes = code.opcodes().map(|(offset, _)| offset).collect::<Vec<_>>();
+        assert_eq!(opcodes, offsets, "{}", method.name());
+    }
+
+    let method = data.methods().iter().find(|m| m.name() == "main").unwrap();
+    let opcodes = method.code().unwrap().opcodes().collect::<Vec<_>>();
+    // invokedynamic, astore_0, aload_0, invokeinterface, return
+    assert_eq!(
+        opcodes,
+        [(0, 0xba), (5, 0x4b), (6, 0x2a), (7, 0xb9), (12, 0xb1)]
+    );
+
+    // `wide iload 0; wide iinc 0 1; wide nop; nop; nop; return`, wide only modifying loads,
+    // stores, ret and iinc.
+    let mut pool = vec![];
+    let utf8 = |pool: &mut Vec<u8>, value: &str| {
+        pool.push(1);
+        pool.extend((value.len() as u16).to_be_bytes());
+        pool.extend(value.as_bytes());
+    };
+    utf8(&mut pool, "Wide"); // #1
+    pool.extend([7, 0, 1]); // #2 Class
+    utf8(&mut pool, "java/lang/Object"); // #3
+    pool.extend([7, 0, 3]); // #4 Class
+    utf8(&mut pool, "wide"); // #5
+    utf8(&mut pool, "(I)V"); // #6
+    utf8(&mut pool, "Code"); // #7
+
+    let code = [
+        0xc4, 0x15, 0, 0, 0xc4, 0x84, 0, 0, 0, 1, 0xc4, 0, 0, 0, 0xb1,
+    ];
+    let mut bytes = vec![0xca, 0xfe, 0xba, 0xbe, 0, 0, 0, 55, 0, 8];
+    bytes.extend(pool);
+    bytes.extend([0x00, 0x21, 0, 2, 0, 4, 0, 0, 0, 0]);
+    bytes.extend([0, 1, 0x00, 0x09, 0, 5, 0, 6, 0, 1]);
+    bytes.extend([0, 7]);
+    bytes.extend((12 + code.len() as u32).to_be_bytes());
+    bytes.extend([0, 1, 0, 1]);
+    bytes.extend((code.len() as u32).to_be_bytes());
+    bytes.extend(code);
+    bytes.extend([0, 0, 0, 0, 0, 0]);
+
+    let raw = libjcdump::parse_raw(&mut bytes.as_slice())?;
+    let data = libjcdump::wrap(&raw)?;
+    let code = data.methods()[0].code().unwrap();
+    assert_eq!(code.opcodes().collect::<Vec<_>>(), [(0, 0xc4), (4, 0xc4)]);
+    assert!(matches!(
+        code.instructions(),
+        Err(libjcdump::ParseError::IllegalOpcode {
+            opcode: 0,
+            offset: 11
+        })
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn parse_from_base64() -> anyhow::Result<()> {
+    use base64::Engine as _;
+
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Main.java")])?;
+
+    let bytes = fs::read(output.path().join("./com/example/Main.class"))?;
+    let encoded = base64::engine::general_purpose::STANDARD.encode(&bytes);
+    let wrapped = encoded
+        .as_bytes()
+        .chunks(76)
+        .map(|line| std::str::from_utf8(line).unwrap())
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let raw = libjcdump::parse_from_base64(&wrapped)?;
+    assert_eq!(raw.to_bytes()?, bytes);
+
+    assert!(matches!(
+        libjcdump::parse_from_base64("yv66vg=!"),
+        Err(libjcdump::ParseError::Base64Decode(..))
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn rename_class() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac_with_args(srcdir.clone(), [srcdir.join("Main.java")], ["-g"])?;
+
+    let mut main = fs::File::open(output.path().join("./com/example/Main.class"))?;
+    let mut raw = libjcdump::parse_raw(&mut main)?;
+    raw.rename_class("com/example/Main", "org/example/Renamed")?;
+    let bytes = raw.to_bytes()?;
+    let raw = libjcdump::parse_raw(&mut bytes.as_slice())?;
+    assert!(libjcdump::validate(&raw).is_clean());
+    let data = libjcdump::wrap(&raw)?;
+    assert_eq!(data.this_class(), "org/example/Renamed");
+
+    let data = data.to_json_value()?;
+    let field = |name: &str| {
+        data["fields"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|field| field["name"] == name)
+            .unwrap()
+    };
+    // The string constant shares its CONSTANT_Utf8 with the class name but keeps its value.
+    let constant_value = attribute(&field("INTERNAL_NAME")["attributes"], "ConstantValue");
+    assert_eq!(constant_value.unwrap()["String"], "com/example/Main");
+
+    let mut anonymous = fs::File::open(output.path().join("./com/example/Main$1.class"))?;
+    let mut raw = libjcdump::parse_raw(&mut anonymous)?;
+    raw.rename_class("com/example/Main", "org/example/Renamed")?;
+    let data = libjcdump::wrap(&raw)?;
+    // Nested classes keep their own name.
+    assert_eq!(data.this_class(), "com/example/Main$1");
+    let data = data.to_json_value()?;
+    let enclosing = attribute(&data["attributes"], "EnclosingMethod").unwrap();
+    assert_eq!(enclosing["class"], "org/example/Renamed");
+    let outer = data["fields"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|field| field["name"] == "this$0")
+        .unwrap();
+    assert_eq!(outer["descriptor"], "Lorg/example/Renamed;");
+
+    Ok(())
+}
+
+#[test]
+fn pool_analysis() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Main.java")])?;
+
+    let mut main = fs::File::open(output.path().join("./com/example/Main.class"))?;
+    let mut raw = libjcdump::parse_raw(&mut main)?;
+    let analysis = raw.pool_analysis()?;
+    assert!(analysis.duplicates().is_empty());
+    assert_eq!(analysis.removable_count(), analysis.unreferenced().len());
+
+    type Constant<'a> = (
+        u16,
+        Result<libjcdump::CpInfo<&'a str>, libjcdump::ParseError>,
+    );
+    fn utf8<'a>(constants: impl Iterator<Item = Constant<'a>>, value: &str) -> Vec<u16> {
+        constants
+            .filter(
+                |(_, constant)| matches!(constant, Ok(libjcdump::CpInfo::Utf8(v)) if *v == value),
+            )
+            .map(|(index, _)| index)
+            .collect()
+    }
+    // Renaming the class away and back leaves the string constant with a copy of its name.
+    raw.rename_class("com/example/Main", "org/example/Renamed")?;
+    raw.rename_class("org/example/Renamed", "com/example/Main")?;
+    // The old name of the method is no longer used.
+    let frames = utf8(raw.resolved_constants(), "frames");
+    raw.rename_method("frames", "(I)I", "hash")?;
+
+    let analysis = raw.pool_analysis()?;
+    assert_eq!(
+        analysis.duplicates(),
+        [utf8(raw.resolved_constants(), "com/example/Main")]
+    );
+    assert!(analysis.unreferenced().contains(&frames[0]));
+    assert_eq!(
+        analysis.removable_count(),
+        analysis.unreferenced().len() + 1
+    );
+
+    Ok(())
+}
+
+#[test]
+fn pool_analysis_module() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Main.java")])?;
+
+    let mut module = fs::File::open(output.path().join("./module-info.class"))?;
+    let raw = libjcdump::parse_raw(&mut module)?;
+    // The modules, the package and the version are only referred to by the Module attribute.
+    let analysis = raw.pool_analysis()?;
+    let unreferenced = analysis
+        .unreferenced()
+        .iter()
+        .map(|index| {
+            let (_, constant) = raw.resolved_constants().find(|(i, _)| i == index).unwrap();
+            Ok(serde_json::to_value(constant?)?)
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    assert!(unreferenced.is_empty(), "{unreferenced:?}");
+
+    Ok(())
+}
+
+#[test]
+fn canonicalize_pool() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Main.java")])?;
+
+    // Instructions with their constants resolved, which do not depend on the pool order.
+    fn disassembly(bytes: &[u8]) -> anyhow::Result<Vec<String>> {
+        let raw = libjcdump::parse_raw(&mut &bytes[..])?;
+        let constants = raw
+            .resolved_constants()
+            .map(|(index, constant)| Ok((index, serde_json::to_string(&constant?)?)))
+            .collect::<anyhow::Result<std::collections::HashMap<_, _>>>()?;
+        let data = libjcdump::wrap(&raw)?;
+        let mut lines = vec![];
+        for method in data.methods() {
+            for instruction in method
+                .code()
+                .map(|code| code.instructions())
+                .transpose()?
+                .into_iter()
+                .flatten()
+            {
+                let operation = instruction.operation();
+                let constant = operation.constant_index().map(|index| &constants[&index]);
+                lines.push(format!(
+                    "{} {} {constant:?}",
+                    instruction.offset(),
+                    operation.mnemonic()
+                ));
+            }
+        }
+        Ok(lines)
+    }
+
+    for class in ["./com/example/Main.class", "./module-info.class"] {
+        let bytes = fs::read(output.path().join(class))?;
+        let mut raw = libjcdump::parse_raw(&mut bytes.as_slice())?;
+        let text = libjcdump::wrap(&raw)?.to_text(&libjcdump::TextOptions::default());
+        let instructions = disassembly(&bytes)?;
+
+        raw.canonicalize_pool()?;
+        let canonical = raw.to_bytes()?;
+        assert_eq!(canonical.len(), bytes.len());
+        let mut raw = libjcdump::parse_raw(&mut canonical.as_slice())?;
+        assert!(libjcdump::validate(&raw).is_clean());
+        assert_eq!(
+            libjcdump::wrap(&raw)?.to_text(&libjcdump::TextOptions::default()),
+            text
+        );
+        assert_eq!(disassembly(&canonical)?, instructions);
+
+        raw.canonicalize_pool()?;
+        assert_eq!(raw.to_bytes()?, canonical);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn rename_member() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Main.java")])?;
+
+    let mut main = fs::File::open(output.path().join("./com/example/Main.class"))?;
+    let mut raw = libjcdump::parse_raw(&mut main)?;
+    let len = raw.constant_pool.len();
+
+    // The name is shared with the CONSTANT_NameAndType of the Fieldref in <clinit>.
+    raw.rename_field("NAMES", "Ljava/util/List;", "ITEMS")?;
+    assert_eq!(raw.constant_pool.len(), len + 1);
+    // An existing constant is reused.
+    raw.rename_method("main", "()V", "of")?;
+    assert_eq!(raw.constant_pool.len(), len + 1);
+
+    assert!(matches!(
+        raw.rename_method("main", "()V", "start").unwrap_err(),
+        libjcdump::ParseError::MemberNotFound { .. }
+    ));
+    match raw.rename_method("of", "()V", "<clinit>").unwrap_err() {
+        libjcdump::ParseError::DuplicateMember { name, descriptor } => {
+            assert_eq!(name, "<clinit>");
+            assert_eq!(descriptor, "()V");
+        }
+        err => panic!("{err}"),
+    }
+
+    let bytes = raw.to_bytes()?;
+    let raw = libjcdump::parse_raw(&mut bytes.as_slice())?;
+    assert!(libjcdump::validate(&raw).is_clean());
+    let data = libjcdump::wrap(&raw)?;
+    assert!(
+        data.methods()
+            .iter()
+            .any(|method| method.name() == "of" && method.descriptor() == "()V")
+    );
+    let data = data.to_json_value()?;
+    let fields = data["fields"].as_array().unwrap();
+    assert!(fields.iter().any(|field| field["name"] == "ITEMS"));
+    assert!(!fields.iter().any(|field| field["name"] == "NAMES"));
+    // References are left as they are.
+    assert!(
+        raw.resolved_constants().any(|(_, entry)| matches!(
+            entry,
+            Ok(libjcdump::CpInfo::Fieldref { name: "NAMES", .. })
+        ))
+    );
+
+    Ok(())
+}
+
+#[test]
+fn max_nesting_depth() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(
+        srcdir.clone(),
+        [srcdir.join("package-info.java"), srcdir.join("Main.java")],
+    )?;
+
+    // `Code` attributes within each other, each with a `return`.
+    let mut main = fs::File::open(output.path().join("./com/example/Main.class"))?;
+    let mut raw = libjcdump::parse_raw(&mut main)?;
+    // javac puts `Code` first.
+    let code_name_index = raw.methods[0].attributes[0].attribute_name_index;
+    let nested_code = |levels: usize| {
+        let mut info = vec![0, 0, 0, 0, 0, 0, 0, 1, 0xb1, 0, 0, 0, 0];
+        for _ in 0..levels {
+            let mut outer = vec![0, 0, 0, 0, 0, 0, 0, 1, 0xb1, 0, 0, 0, 1];
+            outer.extend(code_name_index.to_be_bytes());
+            outer.extend((info.len() as u32).to_be_bytes());
+            outer.extend(info);
+            info = outer;
+        }
+        info
+    };
+
+    raw.methods[0].attributes[0].info = nested_code(64);
+    assert!(libjcdump::wrap(&raw).is_ok());
+    raw.methods[0].attributes[0].info = nested_code(65);
+    assert!(matches!(
+        libjcdump::wrap(&raw),
+        Err(libjcdump::ParseError::MaxNestingExceeded)
+    ));
+    assert!(matches!(
+        raw.constant_reference_counts(),
+        Err(libjcdump::ParseError::MaxNestingExceeded)
+    ));
+    let options = libjcdump::ParseOptions {
+        max_nesting_depth: 65,
+        ..Default::default()
+    };
+    assert!(libjcdump::wrap_with_options(&raw, &options).is_ok());
+
+    // An annotation element of arrays within each other, too deep for the stack if unchecked.
+    let mut package_info = fs::File::open(output.path().join("./com/example/package-info.class"))?;
+    let mut raw = libjcdump::parse_raw(&mut package_info)?;
+    let position = libjcdump::wrap(&raw)?
+        .attributes()
+        .iter()
+        .position(|attribute| attribute.name() == "RuntimeVisibleAnnotations")
+        .unwrap();
+    let annotations = &mut raw.attributes[position].info;
+    // num_annotations, type_index, num_element_value_pairs and element_name_index.
+    let mut info = annotations[..4].to_vec();
+    info.extend([0, 1]);
+    info.extend(&annotations[6..8]);
+    for _ in 0..100_000 {
+        info.extend([b'[', 0, 1]);
+    }
+    info.extend([b'[', 0, 0]);
+    *annotations = info;
+    assert!(matches!(
+        libjcdump::wrap(&raw),
+        Err(libjcdump::ParseError::MaxNestingExceeded)
+    ));
+    assert!(matches!(
+        raw.constant_reference_counts(),
+        Err(libjcdump::ParseError::MaxNestingExceeded)
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn source_file() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Main.java")])?;
+
+    let mut anonymous = fs::File::open(output.path().join("./com/example/Main$1.class"))?;
+    let raw = libjcdump::parse_raw(&mut anonymous)?;
+    let data = libjcdump::wrap(&raw)?;
+    assert_eq!(data.source_file(), Some("Main.java"));
+    assert!(matches!(
+        data.source_file_or_synthesized(),
+        Cow::Borrowed("Main.java")
+    ));
+
+    // Without debugging information.
+    let output = javac_with_args(srcdir.clone(), [srcdir.join("Main.java")], ["-g:none"])?;
+    let mut anonymous = fs::File::open(output.path().join("./com/example/Main$1.class"))?;
+    let raw = libjcdump::parse_raw(&mut anonymous)?;
+    let data = libjcdump::wrap(&raw)?;
+    assert_eq!(data.source_file(), None);
+    assert_eq!(data.source_file_or_synthesized(), "Main.java");
+
+    Ok(())
+}
+
+#[test]
+fn max_java_version() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Main.java")])?;
+
+    let mut main = fs::File::open(output.path().join("./com/example/Main.class"))?;
+    let raw = libjcdump::parse_raw(&mut main)?;
+    let wrap = |max_java_version| {
+        libjcdump::wrap_with_options(
+            &raw,
+            &libjcdump::ParseOptions {
+                max_java_version: Some(max_java_version),
+                ..Default::default()
+            },
+        )
+    };
+
+    // The nest of the anonymous class.
+    assert!(wrap(11).is_ok());
+    match wrap(10) {
+        Err(libjcdump::ParseError::AttributeNewerThanTarget {
+            name,
+            introduced_in: 11,
+            target: 10,
+        }) => assert_eq!(name, "NestMembers"),
+        result => panic!("{:?}", result.map(|_| ())),
+    }
+
+    let status = Command::new(env!("CARGO_BIN_EXE_jcdump"))
+        .args(["--max-java-version", "10"])
+        .arg(output.path().join("./com/example/Main.class"))
+        .output()?
+        .status;
+    assert!(!status.success());
+
+    Ok(())
+}
+
+#[test]
+fn java_se_name() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac_with_args(
+        srcdir.clone(),
+        [srcdir.join("Main.java")],
+        ["--release", "8"],
+    )?;
+
+    let mut bytes = fs::read(output.path().join("./com/example/Main.class"))?;
+    let raw = libjcdump::parse_raw(&mut bytes.as_slice())?;
+    let data = libjcdump::wrap(&raw)?;
+    assert_eq!(data.version().major_version(), 52);
+    assert_eq!(data.version().java_se_name(), Some("Java SE 8"));
+
+    bytes[6..8].copy_from_slice(&99u16.to_be_bytes());
+    let raw = libjcdump::parse_raw(&mut bytes.as_slice())?;
+    assert_eq!(libjcdump::wrap(&raw)?.version().java_se_name(), None);
+
+    Ok(())
+}
+
+#[test]
+fn reject_preview() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Main.java")])?;
+
+    let mut main = fs::File::open(output.path().join("./com/example/Main.class"))?;
+    let mut raw = libjcdump::parse_raw(&mut main)?;
+    let options = libjcdump::ParseOptions {
+        reject_preview: true,
+        ..Default::default()
+    };
+    assert!(!libjcdump::wrap_with_options(&raw, &options)?.uses_preview_features());
+
+    raw.minor_version = 0xFFFF;
+    assert!(matches!(
+        libjcdump::wrap_with_options(&raw, &options),
+        Err(libjcdump::ParseError::PreviewFeaturesNotAllowed)
+    ));
+    assert!(libjcdump::wrap(&raw)?.uses_preview_features());
+
+    // Preview features only exist since Java SE 12.
+    raw.major_version = 55;
+    assert!(!libjcdump::wrap_with_options(&raw, &options)?.uses_preview_features());
+
+    Ok(())
+}
+
+#[test]
+fn implausible_header() -> anyhow::Result<()> {
+    let header = |minor: u16, major: u16, constant_pool_count: u16| {
+        let mut bytes = vec![0xca, 0xfe, 0xba, 0xbe];
+        bytes.extend(minor.to_be_bytes());
+        bytes.extend(major.to_be_bytes());
+        bytes.extend(constant_pool_count.to_be_bytes());
+        match libjcdump::parse_raw(&mut bytes.as_slice()) {
+            Err(libjcdump::ParseError::ImplausibleHeader { field, value }) => Some((field, value)),
+            _ => None,
+        }
+    };
+
+    // A Mach-O universal binary with two architectures.
+    assert_eq!(header(0, 2, 0x0100), Some(("major_version", 2)));
+    assert_eq!(header(3, 65, 20), Some(("minor_version", 3)));
+    assert_eq!(header(0, 65, 1), Some(("constant_pool_count", 1)));
+    // Past the header, so failing at the first constant.
+    assert_eq!(header(0xffff, 65, 20), None);
+    assert_eq!(header(3, 49, 20), None);
+
+    Ok(())
+}
+
+#[test]
+fn constant_pool_slot_misalignment() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Main.java")])?;
+
+    let mut bytes = fs::read(output.path().join("./com/example/Main.class"))?;
+    let raw = libjcdump::parse_raw(&mut bytes.as_slice())?;
+    let wide = raw
+        .resolved_constants()
+        .filter(|(_, entry)| {
+            matches!(
+                entry,
+                Ok(libjcdump::CpInfo::Long(..) | libjcdump::CpInfo::Double(..))
+            )
+        })
+        .map(|(index, _)| index)
+        .collect::<Vec<_>>();
+    assert!(!wide.is_empty());
+
+    // As counted by a generator which forgets the second slots.
+    let count = u16::from_be_bytes([bytes[8], bytes[9]]) - wide.len() as u16;
+    bytes[8..10].copy_from_slice(&count.to_be_bytes());
+    // Reading a stream, the failure is only where the misalignment leads.
+    assert!(!matches!(
+        libjcdump::parse_raw(&mut bytes.as_slice()),
+        Ok(..) | Err(libjcdump::ParseError::ConstantPoolSlotMisalignment { .. })
+    ));
+    match libjcdump::parse_raw_bytes(&bytes) {
+        Err(libjcdump::ParseError::ConstantPoolSlotMisalignment { index }) => {
+            assert_eq!(index, wide[0])
+        }
+        result => panic!("{result:?}"),
+    }
+
+    // The command line tool reads the file again to tell.
+    let misaligned = output.path().join("Misaligned.class");
+    fs::write(&misaligned, &bytes)?;
+    let result = Command::new(env!("CARGO_BIN_EXE_jcdump"))
+        .arg(&misaligned)
+        .output()?;
+    assert!(!result.status.success());
+    assert!(String::from_utf8(result.stderr)?.contains("constant_pool_count does not account"));
+
+    // The last constant cannot take two slots.
+    let mut bytes = vec![0xca, 0xfe, 0xba, 0xbe, 0, 0, 0, 55, 0, 3];
+    bytes.extend([1, 0, 1, b'a']);
+    bytes.extend([5, 0, 0, 0, 0, 0, 0, 0, 0]);
+    assert!(matches!(
+        libjcdump::parse_raw(&mut bytes.as_slice()),
+        Err(libjcdump::ParseError::ConstantPoolSlotMisalignment { index: 2 })
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn constant_reference_counts() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Main.java")])?;
+
+    let mut main = fs::File::open(output.path().join("./com/example/Main.class"))?;
+    let raw = libjcdump::parse_raw(&mut main)?;
+    let counts = raw.constant_reference_counts()?;
+    assert_eq!(counts.len(), raw.constant_pool.len());
+    assert_eq!(counts[0], 0);
+
+    let index_of = |value: &libjcdump::CpInfo<&str>| {
+        let value = serde_json::to_value(value).unwrap();
+        raw.resolved_constants()
+            .find(|(_, constant)| {
+                serde_json::to_value(constant.as_ref().unwrap()).unwrap() == value
+            })
+            .map(|(index, _)| index as usize)
+            .unwrap()
+    };
+    // The name of the Code attributes of the six methods.
+    let code = index_of(&libjcdump::CpInfo::Utf8("Code"));
+    assert_eq!(counts[code], 6);
+    // Referred to by the ConstantValue attribute only.
+    let zero64 = index_of(&libjcdump::CpInfo::Long(0));
+    assert_eq!(counts[zero64], 1);
+    // The exception the main method declares.
+    let exception = index_of(&libjcdump::CpInfo::Class {
+        name: "java/lang/Exception",
+    });
+    assert_eq!(counts[exception], 1);
+
+    Ok(())
+}
+
+#[test]
+fn constant_reference_counts_attributes() -> anyhow::Result<()> {
+    use libjcdump::CpInfo;
+
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(
+        srcdir.clone(),
+        [srcdir.join("Main.java"), srcdir.join("TypeUses.java")],
+    )?;
+
+    let counts = |class: &str, value: CpInfo<&str>| -> anyhow::Result<u32> {
+        let mut file = fs::File::open(output.path().join(class))?;
+        let raw = libjcdump::parse_raw(&mut file)?;
+        let counts = raw.constant_reference_counts()?;
+        let value = serde_json::to_value(value)?;
+        let (index, _) = raw
+            .resolved_constants()
+            .find(|(_, constant)| {
+                serde_json::to_value(constant.as_ref().unwrap()).unwrap() == value
+            })
+            .unwrap();
+        Ok(counts[index as usize])
+    };
+
+    // NestMembers, InnerClasses, and the new instruction and the Methodref of the constructor.
+    let anonymous = CpInfo::Class {
+        name: "com/example/Main$1",
+    };
+    assert_eq!(counts("./com/example/Main.class", anonymous)?, 4);
+    // The Module attribute only, as the name of the module and the package it exports.
+    let module = CpInfo::Module {
+        name: "com.example",
+    };
+    assert_eq!(counts("./module-info.class", module)?, 1);
+    let package = CpInfo::Package {
+        name: "com/example",
+    };
+    assert_eq!(counts("./module-info.class", package)?, 1);
+    // The type annotations of the class, the field, the method and the cast.
+    let nullable = CpInfo::Utf8("Lcom/example/TypeUses$Nullable;");
+    assert_eq!(counts("./com/example/TypeUses.class", nullable)?, 4);
+
+    Ok(())
+}
+
+#[test]
+fn cli_ndjson() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Main.java")])?;
+
+    let classes = [
+        output.path().join("./com/example/Main.class"),
+        output.path().join("./com/example/Main$1.class"),
+    ];
+    let stdout = Command::new(env!("CARGO_BIN_EXE_jcdump"))
+        .args(["--format", "ndjson"])
+        .args(&classes)
+        .output()?
+        .stdout;
+    let lines = String::from_utf8(stdout)?
+        .lines()
+        .map(serde_json::from_str)
+        .collect::<Result<Vec<serde_json::Value>, _>>()?;
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0]["this_class"], "com/example/Main");
+    assert_eq!(lines[1]["this_class"], "com/example/Main$1");
+
+    let stdout = Command::new(env!("CARGO_BIN_EXE_jcdump"))
+        .args(&classes)
+        .output()?
+        .stdout;
+    let array: Vec<serde_json::Value> = serde_json::from_slice(&stdout)?;
+    assert_eq!(array, lines);
+
+    Ok(())
+}
+
+#[test]
+fn cli_pretty() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Main.java")])?;
+
+    let classes = [
+        output.path().join("./com/example/Main.class"),
+        output.path().join("./com/example/Main$1.class"),
+    ];
+    let compact = Command::new(env!("CARGO_BIN_EXE_jcdump"))
+        .args(&classes)
+        .output()?
+        .stdout;
+    assert_eq!(String::from_utf8(compact.clone())?.lines().count(), 1);
+    let pretty = Command::new(env!("CARGO_BIN_EXE_jcdump"))
+        .arg("--pretty")
+        .args(&classes)
+        .output()?
+        .stdout;
+    assert!(String::from_utf8(pretty.clone())?.lines().count() > 1);
+    assert_eq!(
+        serde_json::from_slice::<serde_json::Value>(&pretty)?,
+        serde_json::from_slice::<serde_json::Value>(&compact)?,
+    );
+
+    let output = Command::new(env!("CARGO_BIN_EXE_jcdump"))
+        .args(["-p", "--format", "ndjson"])
+        .args(&classes)
+        .output()?;
+    assert!(!output.status.success());
+
+    Ok(())
+}
+
+#[test]
+fn cli_output() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Main.java")])?;
+
+    let classes = [
+        output.path().join("./com/example/Main.class"),
+        output.path().join("./com/example/Main$1.class"),
+    ];
+    let out = output.path().join("out.json");
+    fs::write(&out, "stale")?;
+    let result = Command::new(env!("CARGO_BIN_EXE_jcdump"))
+        .args(["-p", "--output"])
+        .arg(&out)
+        .args(&classes)
+        .output()?;
+    assert!(result.status.success());
+    assert!(result.stdout.is_empty());
+    let array: Vec<serde_json::Value> = serde_json::from_slice(&fs::read(&out)?)?;
+    assert_eq!(array.len(), 2);
+    assert_eq!(array[0]["this_class"], "com/example/Main");
+
+    let result = Command::new(env!("CARGO_BIN_EXE_jcdump"))
+        .arg("-o")
+        .arg(output.path().join("missing/out.json"))
+        .args(&classes)
+        .output()?;
+    assert!(!result.status.success());
+    assert!(String::from_utf8(result.stderr)?.contains("missing/out.json"));
+
+    Ok(())
+}
+
+#[test]
+fn cli_skips_unreadable_files() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Main.java")])?;
+
+    let missing = output.path().join("./com/example/Missing.class");
+    let output = Command::new(env!("CARGO_BIN_EXE_jcdump"))
+        .arg(output.path().join("./com/example/Main.class"))
+        .arg(&missing)
+        .arg(srcdir.join("Main.java"))
+        .arg(output.path().join("./com/example/Main$1.class"))
+        .output()?;
+    assert!(!output.status.success());
+    let array: Vec<serde_json::Value> = serde_json::from_slice(&output.stdout)?;
+    assert_eq!(array.len(), 2);
+    assert_eq!(array[1]["this_class"], "com/example/Main$1");
+    let stderr = String::from_utf8(output.stderr)?;
+    assert!(stderr.contains(&missing.display().to_string()));
+    assert!(stderr.contains("Main.java"));
+
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn cli_stops_on_output_errors() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Main.java")])?;
+
+    // Enough output to fill the buffer before the last class file.
+    let main = output.path().join("./com/example/Main.class");
+    let output = Command::new(env!("CARGO_BIN_EXE_jcdump"))
+        .args(["-o", "/dev/full"])
+        .args(std::iter::repeat_n(&main, 20))
+        .output()?;
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr)?;
+    assert_eq!(
+        stderr.matches("No space left on device").count(),
+        1,
+        "{stderr}"
+    );
+    assert!(!stderr.contains("could not be read"));
+
+    Ok(())
+}
+
+#[test]
+fn parse_to_buffer() -> anyhow::Result<()> {
+    let srcdir = Path::new(file!()).parent().unwrap().join("./data/");
+    let output = javac(srcdir.clone(), [srcdir.join("Main.java")])?;
+
+    let bytes = fs::read(output.path().join("./com/example/Main.class"))?;
+    let mut written = 0;
+    let code = unsafe {
+        libjcdump::parse_to_buffer(
+            bytes.as_ptr(),
+            bytes.len(),
+            std::ptr::null_mut(),
+            0,
+            &mut written,
+        )
+    };
+    assert_eq!(code, 2);
+
+    let mut buf = vec![0; written];
+    let code = unsafe {
+        libjcdump::parse_to_buffer(
+            bytes.as_ptr(),
+            bytes.len(),
+            buf.as_mut_ptr(),
+            buf.len(),
+            &mut written,
+        )
+    };
+    assert_eq!(code, 0);
+    assert_eq!(written, buf.len());
+    let data: serde_json::Value = serde_json::from_slice(&buf)?;
+    assert_eq!(data["this_class"], "com/example/Main");
+
+    let code = unsafe {
+        libjcdump::parse_to_buffer(bytes.as_ptr(), 4, buf.as_mut_ptr(), buf.len(), &mut written)
+    };
+    assert_eq!(code, 1);
+
     Ok(())
 }